@@ -0,0 +1,274 @@
+//! Pool and validator analytics over the indexed events.
+//!
+//! Windows end at `now`, which defaults to the latest indexed event so a
+//! replayed ledger dump reports the same figures as a live index.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+pub const DAY: i64 = 86_400;
+pub const YEAR: i64 = 365 * DAY;
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    pub pool: String,
+    /// End of the window (unix seconds)
+    pub as_of: i64,
+    pub window: i64,
+    pub swaps: i64,
+    /// Swap volume over the window; SOL for SOL/LST pools
+    pub volume: i64,
+    /// Value accrued to LPs over the window
+    pub lp_income: i64,
+    /// Pool value after its latest swap
+    pub tvl: i64,
+    /// LP income annualized against `tvl` (basis points)
+    pub fee_apr_bps: i64,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct LpPnl {
+    pub pool: String,
+    pub from: i64,
+    pub to: i64,
+    /// Pool value at the start of the period
+    pub tvl_start: i64,
+    pub lp_income: i64,
+    /// LP income relative to `tvl_start` (basis points)
+    pub income_return_bps: i64,
+    /// Virtual price change from compounding, for pools that compound
+    pub virtual_price_start: Option<i64>,
+    pub virtual_price_end: Option<i64>,
+    pub virtual_price_return_bps: i64,
+    /// Return of one LP share held over the period (basis points)
+    pub total_return_bps: i64,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct ValidatorScore {
+    pub pool: String,
+    pub performance: i64,
+    pub mev_reward: i64,
+    pub total_mev_rewards: i64,
+    pub risk_score: i64,
+    pub timestamp: i64,
+}
+
+/// Latest indexed event time
+pub fn latest_timestamp(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(MAX(timestamp), 0) FROM (
+             SELECT timestamp FROM swaps UNION ALL
+             SELECT timestamp FROM lp_income UNION ALL
+             SELECT timestamp FROM virtual_prices UNION ALL
+             SELECT timestamp FROM validator_scores UNION ALL
+             SELECT timestamp FROM prices
+         )",
+        [],
+        |row| row.get(0),
+    )?)
+}
+
+pub fn pools(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT pool FROM swaps ORDER BY pool")?;
+    let pools = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+    Ok(pools)
+}
+
+/// Pool value after its latest swap at or before `at`, falling back to its
+/// first swap after `at`
+fn tvl_at(conn: &Connection, pool: &str, at: i64) -> Result<i64> {
+    let before = conn
+        .query_row(
+            "SELECT tvl_after FROM swaps WHERE pool = ?1 AND timestamp <= ?2
+             ORDER BY timestamp DESC, signature DESC, event_index DESC LIMIT 1",
+            params![pool, at],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(tvl) = before {
+        return Ok(tvl);
+    }
+    Ok(conn
+        .query_row(
+            "SELECT tvl_after FROM swaps WHERE pool = ?1 AND timestamp > ?2
+             ORDER BY timestamp, signature, event_index LIMIT 1",
+            params![pool, at],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0))
+}
+
+/// LP income in (`from`, `to`]
+fn lp_income(conn: &Connection, pool: &str, from: i64, to: i64) -> Result<i64> {
+    Ok(conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM lp_income WHERE pool = ?1 AND timestamp > ?2 AND timestamp <= ?3",
+        params![pool, from, to],
+        |row| row.get(0),
+    )?)
+}
+
+fn ratio_bps(numerator: i64, denominator: i64) -> i64 {
+    if denominator == 0 {
+        return 0;
+    }
+    (numerator as i128 * 10_000 / denominator as i128) as i64
+}
+
+pub fn pool_stats(conn: &Connection, pool: &str, window: i64, now: i64) -> Result<PoolStats> {
+    let from = now - window;
+    let (swaps, volume): (i64, i64) = conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(volume), 0) FROM swaps WHERE pool = ?1 AND timestamp > ?2 AND timestamp <= ?3",
+        params![pool, from, now],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let lp_income = lp_income(conn, pool, from, now)?;
+    let tvl = tvl_at(conn, pool, now)?;
+    let fee_apr_bps = if window == 0 {
+        0
+    } else {
+        (ratio_bps(lp_income, tvl) as i128 * YEAR as i128 / window as i128) as i64
+    };
+
+    Ok(PoolStats {
+        pool: pool.to_string(),
+        as_of: now,
+        window,
+        swaps,
+        volume,
+        lp_income,
+        tvl,
+        fee_apr_bps,
+    })
+}
+
+pub fn lp_pnl(conn: &Connection, pool: &str, from: i64, to: i64) -> Result<LpPnl> {
+    let tvl_start = tvl_at(conn, pool, from)?;
+    let income = lp_income(conn, pool, from, to)?;
+    let income_return_bps = ratio_bps(income, tvl_start);
+
+    let virtual_price_start: Option<i64> = conn
+        .query_row(
+            "SELECT price_before FROM virtual_prices WHERE pool = ?1 AND timestamp > ?2 AND timestamp <= ?3
+             ORDER BY timestamp, signature, event_index LIMIT 1",
+            params![pool, from, to],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let virtual_price_end: Option<i64> = conn
+        .query_row(
+            "SELECT price_after FROM virtual_prices WHERE pool = ?1 AND timestamp > ?2 AND timestamp <= ?3
+             ORDER BY timestamp DESC, signature DESC, event_index DESC LIMIT 1",
+            params![pool, from, to],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let virtual_price_return_bps = match (virtual_price_start, virtual_price_end) {
+        (Some(start), Some(end)) => ratio_bps(end - start, start),
+        _ => 0,
+    };
+
+    Ok(LpPnl {
+        pool: pool.to_string(),
+        from,
+        to,
+        tvl_start,
+        lp_income: income,
+        income_return_bps,
+        virtual_price_start,
+        virtual_price_end,
+        virtual_price_return_bps,
+        total_return_bps: income_return_bps + virtual_price_return_bps,
+    })
+}
+
+pub fn validator_scores(conn: &Connection, validator: &str, from: i64, to: i64) -> Result<Vec<ValidatorScore>> {
+    let mut stmt = conn.prepare(
+        "SELECT pool, performance, mev_reward, total_mev_rewards, risk_score, timestamp
+         FROM validator_scores WHERE validator = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+         ORDER BY timestamp",
+    )?;
+    let scores = stmt
+        .query_map(params![validator, from, to], |row| {
+            Ok(ValidatorScore {
+                pool: row.get(0)?,
+                performance: row.get(1)?,
+                mev_reward: row.get(2)?,
+                total_mev_rewards: row.get(3)?,
+                risk_score: row.get(4)?,
+                timestamp: row.get(5)?,
+            })
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::events::*;
+    use crate::source::TransactionLogs;
+
+    fn tx(signature: &str) -> TransactionLogs {
+        TransactionLogs {
+            signature: signature.to_string(),
+            slot: 1,
+            block_time: None,
+            logs: Vec::new(),
+            failed: false,
+        }
+    }
+
+    fn swap(timestamp: i64, amount_in: u64) -> Event {
+        // 1.1 SOL per LST, 10_000 SOL and 10_000 LST left in the pool
+        Event::SwapExecuted(SwapExecuted {
+            pool: Pubkey([1; 32]),
+            user: Pubkey([2; 32]),
+            direction: SwapDirection::SolToLst,
+            amount_in,
+            amount_out: (amount_in - amount_in / 100) * 10 / 11,
+            fee: amount_in / 100,
+            fee_bps: 100,
+            sol_reserve_before: 0,
+            lst_reserve_before: 0,
+            sol_reserve_after: 10_000_000,
+            lst_reserve_after: 10_000_000,
+            risk_multiplier: 10_000,
+            timestamp,
+        })
+    }
+
+    #[test]
+    fn test_pool_stats_and_pnl() {
+        let mut conn = db::open(":memory:").unwrap();
+        let pool = Pubkey([1; 32]).to_string();
+        db::store_transaction(&mut conn, &tx("a"), &[swap(DAY, 110_000)]).unwrap();
+        db::store_transaction(&mut conn, &tx("b"), &[swap(2 * DAY, 220_000)]).unwrap();
+        let routed = Event::FeesRouted(FeesRouted {
+            pool: Pubkey([1; 32]),
+            fees: 3_300,
+            to_insurance: 1_200,
+            to_lps: 2_100,
+            released: 0,
+            total_assets: 0,
+            timestamp: 2 * DAY,
+        });
+        db::store_transaction(&mut conn, &tx("c"), std::slice::from_ref(&routed)).unwrap();
+        // Replays are ignored
+        assert_eq!(db::store_transaction(&mut conn, &tx("c"), &[routed]).unwrap(), 0);
+
+        let now = latest_timestamp(&conn).unwrap();
+        let stats = pool_stats(&conn, &pool, DAY, now).unwrap();
+        assert_eq!((stats.swaps, stats.volume, stats.lp_income), (1, 220_000, 2_100));
+        assert_eq!(stats.tvl, 21_000_000);
+        // 1 bp a day is 3.65% a year
+        assert_eq!(stats.fee_apr_bps, 365);
+
+        let pnl = lp_pnl(&conn, &pool, DAY, 2 * DAY).unwrap();
+        assert_eq!((pnl.tvl_start, pnl.income_return_bps, pnl.total_return_bps), (21_000_000, 1, 1));
+        assert_eq!(pools(&conn).unwrap(), vec![pool]);
+    }
+}
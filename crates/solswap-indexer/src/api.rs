@@ -0,0 +1,82 @@
+//! HTTP/JSON API for dashboards.
+//!
+//! - `GET /pools?window=` stats for every indexed pool
+//! - `GET /pools/{pool}?window=` volume, TVL and fee APR over the window
+//! - `GET /pools/{pool}/lp_pnl?from=&to=` return of one LP share over a period
+//! - `GET /validators/{validator}/scores?from=&to=` metric and risk score history
+//!
+//! Windows are in seconds (default one day) and times are unix seconds.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::analytics::{self, DAY};
+
+pub fn serve(conn: &Connection, listen: &str) -> Result<()> {
+    let server = Server::http(listen).map_err(|e| anyhow::anyhow!("cannot listen on {listen}: {e}"))?;
+    for request in server.incoming_requests() {
+        let (status, body) = match route(conn, &request) {
+            Ok(Some(body)) => (200, body),
+            Ok(None) => (404, r#"{"error":"not found"}"#.to_string()),
+            Err(e) => (400, serde_json::json!({ "error": e.to_string() }).to_string()),
+        };
+        let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+        let response = Response::from_string(body).with_status_code(status).with_header(header);
+        if let Err(e) = request.respond(response) {
+            eprintln!("failed to respond: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn route(conn: &Connection, request: &Request) -> Result<Option<String>> {
+    if request.method() != &Method::Get {
+        return Ok(None);
+    }
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let query = parse_query(query);
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let now = analytics::latest_timestamp(conn)?;
+    let param = |name: &str, default: i64| -> Result<i64> {
+        match query.get(name) {
+            Some(value) => Ok(value.parse()?),
+            None => Ok(default),
+        }
+    };
+
+    let body = match segments.as_slice() {
+        ["pools"] => {
+            let window = param("window", DAY)?;
+            let stats = analytics::pools(conn)?
+                .iter()
+                .map(|pool| analytics::pool_stats(conn, pool, window, now))
+                .collect::<Result<Vec<_>>>()?;
+            to_json(&stats)?
+        }
+        ["pools", pool] => to_json(&analytics::pool_stats(conn, pool, param("window", DAY)?, now)?)?,
+        ["pools", pool, "lp_pnl"] => {
+            let to = param("to", now)?;
+            to_json(&analytics::lp_pnl(conn, pool, param("from", to - DAY)?, to)?)?
+        }
+        ["validators", validator, "scores"] => {
+            to_json(&analytics::validator_scores(conn, validator, param("from", 0)?, param("to", now)?)?)?
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(body))
+}
+
+fn parse_query(query: &str) -> HashMap<&str, &str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect()
+}
+
+fn to_json(value: &impl Serialize) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
@@ -0,0 +1,272 @@
+//! SQLite storage for decoded events.
+//!
+//! Every row is keyed on (signature, event_index), so replaying a ledger
+//! range or re-polling overlapping signatures never double counts.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::events::{Event, SwapDirection};
+use crate::source::TransactionLogs;
+
+/// Scale of `swaps.mark_price` (SOL per LST)
+pub const PRICE_PRECISION: i64 = 1_000_000_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursors (
+    program TEXT PRIMARY KEY,
+    signature TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+-- Swaps in both pool families. `volume` and `fee` are in SOL for SOL/LST
+-- pools and in input token units for stableswap and weighted pools.
+CREATE TABLE IF NOT EXISTS swaps (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    user TEXT NOT NULL,
+    mint_in TEXT NOT NULL,
+    mint_out TEXT NOT NULL,
+    amount_in INTEGER NOT NULL,
+    amount_out INTEGER NOT NULL,
+    fee INTEGER NOT NULL,
+    volume INTEGER NOT NULL,
+    tvl_after INTEGER NOT NULL,
+    mark_price INTEGER NOT NULL,
+    risk INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS swaps_pool_time ON swaps (pool, timestamp);
+-- Value accruing to LPs: routed swap fees, flash loan fees, lending yield
+CREATE TABLE IF NOT EXISTS lp_income (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS lp_income_pool_time ON lp_income (pool, timestamp);
+CREATE TABLE IF NOT EXISTS virtual_prices (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    price_before INTEGER NOT NULL,
+    price_after INTEGER NOT NULL,
+    staking_rewards INTEGER NOT NULL,
+    mev_rewards INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS validator_scores (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    validator TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    performance INTEGER NOT NULL,
+    mev_reward INTEGER NOT NULL,
+    total_mev_rewards INTEGER NOT NULL,
+    risk_score INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS validator_scores_time ON validator_scores (validator, timestamp);
+CREATE TABLE IF NOT EXISTS prices (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    oracle TEXT NOT NULL,
+    price INTEGER NOT NULL,
+    ema_price INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+";
+
+pub fn open(path: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    // The HTTP server reads while the ingester writes
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+pub fn cursor(conn: &Connection, program: &str) -> Result<Option<String>> {
+    Ok(conn
+        .query_row("SELECT signature FROM cursors WHERE program = ?1", [program], |row| row.get(0))
+        .optional()?)
+}
+
+pub fn set_cursor(conn: &Connection, program: &str, signature: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO cursors (program, signature) VALUES (?1, ?2)
+         ON CONFLICT (program) DO UPDATE SET signature = excluded.signature",
+        params![program, signature],
+    )?;
+    Ok(())
+}
+
+/// Stores a transaction and the events it emitted, returning how many were new
+pub fn store_transaction(conn: &mut Connection, tx: &TransactionLogs, events: &[Event]) -> Result<usize> {
+    let db = conn.transaction()?;
+    db.execute(
+        "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+        params![tx.signature, tx.slot as i64, tx.block_time],
+    )?;
+    let mut stored = 0;
+    for (index, event) in events.iter().enumerate() {
+        stored += store_event(&db, &tx.signature, index as i64, event)?;
+    }
+    db.commit()?;
+    Ok(stored)
+}
+
+fn store_event(db: &Connection, signature: &str, index: i64, event: &Event) -> Result<usize> {
+    let stored = match event {
+        Event::SwapExecuted(e) => {
+            let (mint_in, mint_out, sol_gross, lst) = match e.direction {
+                SwapDirection::SolToLst => ("SOL", "LST", e.amount_in, e.amount_out),
+                SwapDirection::LstToSol => ("LST", "SOL", e.amount_out + e.fee, e.amount_in),
+            };
+            // Price the LST reserve at this trade's own execution price
+            let mark_price = if lst == 0 {
+                PRICE_PRECISION
+            } else {
+                ((sol_gross - e.fee) as i128 * PRICE_PRECISION as i128 / lst as i128) as i64
+            };
+            let tvl = e.sol_reserve_after as i128
+                + e.lst_reserve_after as i128 * mark_price as i128 / PRICE_PRECISION as i128;
+            db.execute(
+                "INSERT OR IGNORE INTO swaps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    signature,
+                    index,
+                    e.pool.to_string(),
+                    e.user.to_string(),
+                    mint_in,
+                    mint_out,
+                    e.amount_in as i64,
+                    e.amount_out as i64,
+                    e.fee as i64,
+                    sol_gross as i64,
+                    tvl as i64,
+                    mark_price,
+                    e.risk_multiplier as i64,
+                    e.timestamp,
+                ],
+            )?
+        }
+        Event::PoolSwapped(e) => {
+            let fee = e.amount_in - (e.amount_in as u128 * (10_000 - e.fee_bps as u128) / 10_000) as u64;
+            let tvl: u128 = e.reserves_after.iter().map(|r| *r as u128).sum();
+            let pool = e.pool.to_string();
+            let swaps = db.execute(
+                "INSERT OR IGNORE INTO swaps VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                params![
+                    signature,
+                    index,
+                    pool,
+                    e.user.to_string(),
+                    e.mint_in.to_string(),
+                    e.mint_out.to_string(),
+                    e.amount_in as i64,
+                    e.amount_out as i64,
+                    fee as i64,
+                    e.amount_in as i64,
+                    tvl as i64,
+                    PRICE_PRECISION,
+                    e.risk_premium as i64,
+                    e.timestamp,
+                ],
+            )?;
+            // Stableswap and weighted pool fees stay in the pool for LPs
+            insert_income(db, signature, index, &pool, "swap_fee", fee as i64, e.timestamp)?;
+            swaps
+        }
+        Event::FeesRouted(e) => insert_income(
+            db,
+            signature,
+            index,
+            &e.pool.to_string(),
+            "swap_fee",
+            (e.to_lps + e.released) as i64,
+            e.timestamp,
+        )?,
+        Event::FlashLoanRepaid(e) => insert_income(
+            db,
+            signature,
+            index,
+            &e.pool.to_string(),
+            "flash_fee",
+            e.fee as i64,
+            e.timestamp,
+        )?,
+        Event::LendingRebalanced(e) => {
+            let pool = e.pool.to_string();
+            // Harvested interest is in LST; value it at the pool's latest mark
+            let mark: i64 = db
+                .query_row(
+                    "SELECT mark_price FROM swaps WHERE pool = ?1 AND timestamp <= ?2
+                     ORDER BY timestamp DESC LIMIT 1",
+                    params![pool, e.timestamp],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .unwrap_or(PRICE_PRECISION);
+            let value = (e.harvested as i128 * mark as i128 / PRICE_PRECISION as i128) as i64;
+            insert_income(db, signature, index, &pool, "lending_yield", value, e.timestamp)?
+        }
+        Event::RewardsCompounded(e) => db.execute(
+            "INSERT OR IGNORE INTO virtual_prices VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                signature,
+                index,
+                e.pool.to_string(),
+                e.virtual_price_before as i64,
+                e.virtual_price_after as i64,
+                e.staking_rewards,
+                e.mev_rewards as i64,
+                e.timestamp,
+            ],
+        )?,
+        Event::ValidatorMetricsUpdated(e) => db.execute(
+            "INSERT OR IGNORE INTO validator_scores VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                signature,
+                index,
+                e.validator.to_string(),
+                e.pool.to_string(),
+                e.performance as i64,
+                e.mev_reward as i64,
+                e.total_mev_rewards as i64,
+                e.risk_score as i64,
+                e.timestamp,
+            ],
+        )?,
+        Event::PriceUpdated(e) => db.execute(
+            "INSERT OR IGNORE INTO prices VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![signature, index, e.oracle.to_string(), e.price, e.ema_price, e.timestamp],
+        )?,
+    };
+    Ok(stored)
+}
+
+fn insert_income(
+    db: &Connection,
+    signature: &str,
+    index: i64,
+    pool: &str,
+    kind: &str,
+    amount: i64,
+    timestamp: i64,
+) -> Result<usize> {
+    Ok(db.execute(
+        "INSERT OR IGNORE INTO lp_income VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![signature, index, pool, kind, amount, timestamp],
+    )?)
+}
@@ -0,0 +1,252 @@
+//! Borsh mirrors of the SolSwap events the indexer stores, and decoding of
+//! Anchor `emit!` log lines.
+//!
+//! Field order must match the `#[event]` structs in the programs; Anchor
+//! serializes each event as its 8-byte discriminator followed by the Borsh
+//! encoding of its fields.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use sha2::{Digest, Sha256};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+#[derive(BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pubkey(pub [u8; 32]);
+
+impl std::fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl std::str::FromStr for Pubkey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let bytes = bs58::decode(s).into_vec()?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("{s} is not a 32-byte public key"))?;
+        Ok(Self(bytes))
+    }
+}
+
+#[derive(BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    SolToLst,
+    LstToSol,
+}
+
+/// Swap against a SOL/LST pool
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SwapExecuted {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    pub fee_bps: u64,
+    pub sol_reserve_before: u64,
+    pub lst_reserve_before: u64,
+    pub sol_reserve_after: u64,
+    pub lst_reserve_after: u64,
+    pub risk_multiplier: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FeesRouted {
+    pub pool: Pubkey,
+    pub fees: u64,
+    pub to_insurance: u64,
+    pub to_lps: u64,
+    pub released: u64,
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LendingRebalanced {
+    pub pool: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub harvested: u64,
+    pub principal: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PriceUpdated {
+    pub oracle: Pubkey,
+    pub price: i64,
+    pub ema_price: i64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorMetricsUpdated {
+    pub validator: Pubkey,
+    pub pool: Pubkey,
+    pub performance: u64,
+    pub mev_reward: u64,
+    pub total_mev_rewards: u64,
+    pub risk_score: u64,
+    pub timestamp: i64,
+}
+
+/// Swap against a stableswap or weighted pool, one per route leg
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolSwapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_bps: u16,
+    pub reserves_before: Vec<u64>,
+    pub reserves_after: Vec<u64>,
+    pub risk_premium: u8,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FlashLoanRepaid {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub repaid: u64,
+    pub fee: u64,
+    pub reserve_after: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RewardsCompounded {
+    pub pool: Pubkey,
+    pub virtual_price_before: u64,
+    pub virtual_price_after: u64,
+    pub staking_rewards: i64,
+    pub mev_rewards: u64,
+    pub total_reserves: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    SwapExecuted(SwapExecuted),
+    FeesRouted(FeesRouted),
+    LendingRebalanced(LendingRebalanced),
+    PriceUpdated(PriceUpdated),
+    ValidatorMetricsUpdated(ValidatorMetricsUpdated),
+    PoolSwapped(PoolSwapped),
+    FlashLoanRepaid(FlashLoanRepaid),
+    RewardsCompounded(RewardsCompounded),
+}
+
+/// Anchor event discriminator: the first 8 bytes of sha256("event:<name>")
+pub fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("event:{name}").as_bytes());
+    hash[..8].try_into().unwrap()
+}
+
+impl Event {
+    /// Decodes one `emit!` payload, or None for events the indexer does not store
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (tag, mut body) = data.split_at_checked(8)?;
+        let event = match tag {
+            t if t == discriminator("SwapExecuted") => Self::SwapExecuted(BorshDeserialize::deserialize(&mut body).ok()?),
+            t if t == discriminator("FeesRouted") => Self::FeesRouted(BorshDeserialize::deserialize(&mut body).ok()?),
+            t if t == discriminator("LendingRebalanced") => {
+                Self::LendingRebalanced(BorshDeserialize::deserialize(&mut body).ok()?)
+            }
+            t if t == discriminator("PriceUpdated") => Self::PriceUpdated(BorshDeserialize::deserialize(&mut body).ok()?),
+            t if t == discriminator("ValidatorMetricsUpdated") => {
+                Self::ValidatorMetricsUpdated(BorshDeserialize::deserialize(&mut body).ok()?)
+            }
+            t if t == discriminator("PoolSwapped") => Self::PoolSwapped(BorshDeserialize::deserialize(&mut body).ok()?),
+            t if t == discriminator("FlashLoanRepaid") => {
+                Self::FlashLoanRepaid(BorshDeserialize::deserialize(&mut body).ok()?)
+            }
+            t if t == discriminator("RewardsCompounded") => {
+                Self::RewardsCompounded(BorshDeserialize::deserialize(&mut body).ok()?)
+            }
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+/// Decodes the events emitted by `programs` from a transaction's log messages.
+///
+/// `Program data:` lines are attributed to the program on top of the invoke
+/// stack, so events logged by other programs, including ones invoked by
+/// SolSwap, are ignored.
+pub fn parse_logs(logs: &[String], programs: &[String]) -> Vec<Event> {
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            let ours = stack.last().is_some_and(|top| programs.iter().any(|p| p == top));
+            if let (true, Ok(bytes)) = (ours, STANDARD.decode(data.trim())) {
+                events.extend(Event::decode(&bytes));
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => stack.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program_data(name: &str, body: &[u8]) -> String {
+        let mut data = discriminator(name).to_vec();
+        data.extend_from_slice(body);
+        format!("{PROGRAM_DATA}{}", STANDARD.encode(data))
+    }
+
+    fn price_body(price: i64) -> Vec<u8> {
+        let mut body = vec![7u8; 32];
+        body.extend_from_slice(&price.to_le_bytes());
+        body.extend_from_slice(&(price - 1).to_le_bytes());
+        body.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        body
+    }
+
+    #[test]
+    fn test_events_attributed_to_invoking_program() {
+        let solswap = "SoLSwap1111111111111111111111111111111111111".to_string();
+        let logs = vec![
+            format!("Program {solswap} invoke [1]"),
+            "Program log: Instruction: UpdatePrice".to_string(),
+            program_data("PriceUpdated", &price_body(1_050_000_000)),
+            "Program Other11111111111111111111111111111111 invoke [2]".to_string(),
+            program_data("PriceUpdated", &price_body(1)),
+            "Program Other11111111111111111111111111111111 success".to_string(),
+            program_data("Unknown", &[1, 2, 3]),
+            format!("Program {solswap} success"),
+        ];
+
+        let events = parse_logs(&logs, &[solswap]);
+        assert_eq!(
+            events,
+            vec![Event::PriceUpdated(PriceUpdated {
+                oracle: Pubkey([7; 32]),
+                price: 1_050_000_000,
+                ema_price: 1_049_999_999,
+                timestamp: 1_700_000_000,
+            })]
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::db;
+use crate::events::parse_logs;
+use crate::source::{RpcClient, TransactionLogs};
+
+/// Decodes and stores the SolSwap events in a batch of transactions, returning
+/// the number of new events. Failed transactions emit nothing and are skipped.
+pub fn ingest(conn: &mut Connection, programs: &[String], transactions: &[TransactionLogs]) -> Result<usize> {
+    let mut stored = 0;
+    for tx in transactions.iter().filter(|tx| !tx.failed) {
+        let events = parse_logs(&tx.logs, programs);
+        if !events.is_empty() {
+            stored += db::store_transaction(conn, tx, &events)?;
+        }
+    }
+    Ok(stored)
+}
+
+/// Fetches and stores every transaction for `programs` since the last poll,
+/// advancing each program's cursor only once its transactions are stored
+pub fn poll(conn: &mut Connection, rpc: &RpcClient, programs: &[String]) -> Result<usize> {
+    let mut stored = 0;
+    for program in programs {
+        let cursor = db::cursor(conn, program)?;
+        let signatures = rpc.signatures_since(program, cursor.as_deref())?;
+        for signature in &signatures {
+            let tx = rpc.transaction(signature)?;
+            stored += ingest(conn, programs, std::slice::from_ref(&tx))?;
+            db::set_cursor(conn, program, signature)?;
+        }
+    }
+    Ok(stored)
+}
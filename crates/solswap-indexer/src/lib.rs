@@ -0,0 +1,9 @@
+//! Indexes SolSwap events from transaction logs into SQLite and computes pool
+//! volume, fee APR, LP PnL and validator score history for dashboards.
+
+pub mod analytics;
+pub mod api;
+pub mod db;
+pub mod events;
+pub mod ingest;
+pub mod source;
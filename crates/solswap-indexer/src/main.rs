@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use solswap_indexer::source::{read_dump, RpcClient};
+use solswap_indexer::{api, db, ingest};
+
+const USAGE: &str = "\
+usage:
+  solswap-indexer import --db <path> --program <id>... <dump.jsonl>
+  solswap-indexer serve --db <path> --program <id>... [--rpc <url>] [--poll <secs>] [--listen <addr>]
+
+A dump holds one getTransaction result (json encoding) per line. `serve`
+polls the RPC node for new transactions when --rpc is given and answers API
+requests on --listen (default 127.0.0.1:8080).";
+
+#[derive(Default)]
+struct Args {
+    db: Option<String>,
+    programs: Vec<String>,
+    rpc: Option<String>,
+    poll: Option<u64>,
+    listen: Option<String>,
+    files: Vec<String>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args::default();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().with_context(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--db" => parsed.db = Some(value()?),
+            "--program" => parsed.programs.push(value()?),
+            "--rpc" => parsed.rpc = Some(value()?),
+            "--poll" => parsed.poll = Some(value()?.parse()?),
+            "--listen" => parsed.listen = Some(value()?),
+            flag if flag.starts_with("--") => bail!("unknown option {flag}"),
+            _ => parsed.files.push(arg),
+        }
+    }
+    if parsed.programs.is_empty() {
+        bail!("at least one --program is required");
+    }
+    Ok(parsed)
+}
+
+fn main() -> Result<()> {
+    let mut argv = std::env::args().skip(1);
+    let command = argv.next().unwrap_or_default();
+    let args = match command.as_str() {
+        "import" | "serve" => parse_args(argv)?,
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let db_path = args.db.clone().context("--db is required")?;
+
+    match command.as_str() {
+        "import" => {
+            let mut conn = db::open(&db_path)?;
+            for path in &args.files {
+                let file = File::open(path).with_context(|| format!("cannot open {path}"))?;
+                let transactions = read_dump(BufReader::new(file))?;
+                let stored = ingest::ingest(&mut conn, &args.programs, &transactions)?;
+                println!("{path}: {} transactions, {stored} new events", transactions.len());
+            }
+        }
+        _ => {
+            if let Some(url) = args.rpc.clone() {
+                let mut conn = db::open(&db_path)?;
+                let programs = args.programs.clone();
+                let interval = Duration::from_secs(args.poll.unwrap_or(5));
+                thread::spawn(move || {
+                    let rpc = RpcClient::new(&url);
+                    loop {
+                        match ingest::poll(&mut conn, &rpc, &programs) {
+                            Ok(0) => {}
+                            Ok(stored) => println!("indexed {stored} new events"),
+                            Err(e) => eprintln!("poll failed: {e:#}"),
+                        }
+                        thread::sleep(interval);
+                    }
+                });
+            }
+            let conn = db::open(&db_path)?;
+            let listen = args.listen.as_deref().unwrap_or("127.0.0.1:8080");
+            println!("serving on http://{listen}");
+            api::serve(&conn, listen)?;
+        }
+    }
+    Ok(())
+}
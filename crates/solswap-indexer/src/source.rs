@@ -0,0 +1,122 @@
+//! Transaction sources: a Solana JSON-RPC node polled for new signatures, or
+//! a ledger dump with one `getTransaction` result per line.
+
+use std::io::BufRead;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{json, Value};
+
+/// Maximum signatures per `getSignaturesForAddress` page
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Log messages of one confirmed transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionLogs {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+    pub failed: bool,
+}
+
+impl TransactionLogs {
+    /// Parses a `getTransaction` result in `json` encoding
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let signature = value["transaction"]["signatures"][0]
+            .as_str()
+            .ok_or_else(|| anyhow!("transaction has no signature"))?
+            .to_string();
+        let meta = &value["meta"];
+        let logs = meta["logMessages"]
+            .as_array()
+            .map(|logs| logs.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        Ok(Self {
+            signature,
+            slot: value["slot"].as_u64().unwrap_or(0),
+            block_time: value["blockTime"].as_i64(),
+            logs,
+            failed: !meta["err"].is_null(),
+        })
+    }
+}
+
+/// Reads a ledger dump, skipping blank lines
+pub fn read_dump(reader: impl BufRead) -> Result<Vec<TransactionLogs>> {
+    let mut transactions = Vec::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(&line).with_context(|| format!("line {}", number + 1))?;
+        transactions.push(TransactionLogs::from_json(&value).with_context(|| format!("line {}", number + 1))?);
+    }
+    Ok(transactions)
+}
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string(), agent: ureq::Agent::new() }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+        Ok(response["result"].clone())
+    }
+
+    /// Confirmed signatures for `program` newer than `until`, oldest first
+    pub fn signatures_since(&self, program: &str, until: Option<&str>) -> Result<Vec<String>> {
+        let mut signatures = Vec::new();
+        let mut before: Option<String> = None;
+        loop {
+            let mut config = json!({ "limit": SIGNATURE_PAGE, "commitment": "confirmed" });
+            if let Some(until) = until {
+                config["until"] = json!(until);
+            }
+            if let Some(before) = &before {
+                config["before"] = json!(before);
+            }
+            let page = self.call("getSignaturesForAddress", json!([program, config]))?;
+            let page = page.as_array().ok_or_else(|| anyhow!("unexpected getSignaturesForAddress result"))?;
+            for entry in page {
+                if let Some(signature) = entry["signature"].as_str() {
+                    signatures.push(signature.to_string());
+                }
+            }
+            if page.len() < SIGNATURE_PAGE {
+                break;
+            }
+            before = signatures.last().cloned();
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    pub fn transaction(&self, signature: &str) -> Result<TransactionLogs> {
+        let result = self.call(
+            "getTransaction",
+            json!([signature, {
+                "encoding": "json",
+                "commitment": "confirmed",
+                "maxSupportedTransactionVersion": 0,
+            }]),
+        )?;
+        if result.is_null() {
+            bail!("transaction {signature} not found");
+        }
+        TransactionLogs::from_json(&result)
+    }
+}
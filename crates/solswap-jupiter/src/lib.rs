@@ -0,0 +1,358 @@
+//! Jupiter `Amm` implementation for SolSwap pools, both stableswap `Pool`s and
+//! weighted `RiskAdjustedPool`s.
+//!
+//! Quotes run the program's own pricing: `solswap_math` for stableswap pools,
+//! and for weighted pools the program's weighted quote at the cluster clock,
+//! with stake pool backed tokens valued at their stake pools' rates.
+//!
+//! The program accepts any token account owned by the pool authority as a
+//! vault, so vault addresses cannot be derived. Markets list them in their
+//! params as `{"vaults": [...]}`, in pool token order, and `update` checks
+//! each one the way the program does: owned by the authority and holding the
+//! token's mint.
+//!
+//! Routing is out of scope for this adapter. Jupiter has no swap variant for
+//! SolSwap, and adding one is a change to Jupiter's program, so the adapter
+//! reports itself inactive and `get_swap_and_account_metas` fails rather than
+//! encoding another program's instruction. Until a variant exists the pools
+//! can be quoted, and `swap_account_metas` gives the accounts for a
+//! single-leg `swap_route` (exact in) or `swap_exact_out` (exact out) for
+//! callers that build the instruction themselves.
+
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use jupiter_amm_interface::{
+    AccountMap, Amm, AmmContext, ClockRef, KeyedAccount, Quote, QuoteParams, SwapAndAccountMetas, SwapMode,
+    SwapParams,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solswap::instructions::weighted_swap;
+use solswap::oracles::stake_pool_rate::{parse_stake_pool_rate, StakePoolRate};
+use solswap::state::{Pool, RiskAdjustedPool};
+use solswap_math::{stable, Quoter};
+
+/// Market params listing the pool's vaults
+#[derive(Deserialize)]
+struct MarketParams {
+    vaults: Vec<String>,
+}
+
+#[derive(Clone)]
+enum SolSwapPool {
+    Stable(Pool),
+    Weighted(Box<RiskAdjustedPool>),
+}
+
+#[derive(Clone)]
+pub struct SolSwapAmm {
+    key: Pubkey,
+    authority: Pubkey,
+    pool: SolSwapPool,
+    /// Vault of each pool token, in token order
+    vaults: Vec<Pubkey>,
+    /// Rates of a weighted pool's stake pools, once loaded by `update`
+    stake_pool_rates: Option<Vec<StakePoolRate>>,
+    clock_ref: ClockRef,
+}
+
+impl SolSwapAmm {
+    /// Parses a `Pool` or `RiskAdjustedPool` account owned by the SolSwap
+    /// program, with the vault of each of its tokens
+    pub fn from_account_data(
+        key: Pubkey,
+        owner: &Pubkey,
+        data: &[u8],
+        vaults: Vec<Pubkey>,
+        clock_ref: ClockRef,
+    ) -> Result<Self> {
+        ensure!(*owner == solswap::ID, "account {key} is not owned by the SolSwap program");
+        let (pool, authority) = if data.get(..8) == Some(&Pool::DISCRIMINATOR[..]) {
+            let pool = Pool::try_deserialize(&mut &data[..]).context("invalid SolSwap pool account")?;
+            ensure!(
+                pool.balances.len() == pool.tokens.len(),
+                "pool {key} has {} tokens but {} balances",
+                pool.tokens.len(),
+                pool.balances.len()
+            );
+            let (authority, _) = Pubkey::find_program_address(&[b"authority", key.as_ref()], &solswap::ID);
+            (SolSwapPool::Stable(pool), authority)
+        } else {
+            let pool = RiskAdjustedPool::try_deserialize(&mut &data[..])
+                .context("invalid SolSwap weighted pool account")?;
+            let authority = pool.authority;
+            (SolSwapPool::Weighted(Box::new(pool)), authority)
+        };
+
+        let mut amm = Self {
+            key,
+            authority,
+            pool,
+            vaults,
+            stake_pool_rates: None,
+            clock_ref,
+        };
+        ensure!(
+            amm.vaults.len() == amm.mints().len(),
+            "pool {key} has {} tokens but {} vaults",
+            amm.mints().len(),
+            amm.vaults.len()
+        );
+        if amm.stake_pools().is_empty() {
+            amm.stake_pool_rates = Some(Vec::new());
+        }
+        Ok(amm)
+    }
+
+    /// Vault holding `mint` for this pool
+    pub fn vault(&self, mint: &Pubkey) -> Option<Pubkey> {
+        self.mints().iter().position(|m| m == mint).map(|index| self.vaults[index])
+    }
+
+    /// Accounts for a single-leg `swap_route` or `swap_exact_out`, which share
+    /// this layout: the user's accounts, then the pool, its authority, the two
+    /// vaults and, for a weighted pool, its stake pools
+    pub fn swap_account_metas(&self, swap_params: &SwapParams) -> Result<Vec<AccountMeta>> {
+        let (index_in, index_out) = self.indices(&swap_params.source_mint, &swap_params.destination_mint)?;
+
+        let mut account_metas = vec![
+            AccountMeta::new(swap_params.source_token_account, false),
+            AccountMeta::new(swap_params.destination_token_account, false),
+            AccountMeta::new_readonly(swap_params.token_transfer_authority, true),
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(self.key, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(self.vaults[index_in], false),
+            AccountMeta::new(self.vaults[index_out], false),
+        ];
+        account_metas.extend(
+            self.stake_pools()
+                .into_iter()
+                .map(|stake_pool| AccountMeta::new_readonly(stake_pool, false)),
+        );
+        Ok(account_metas)
+    }
+
+    fn mints(&self) -> Vec<Pubkey> {
+        match &self.pool {
+            SolSwapPool::Stable(pool) => pool.tokens.clone(),
+            SolSwapPool::Weighted(pool) => pool.token_weights.iter().map(|token| token.mint).collect(),
+        }
+    }
+
+    /// Stake pools of a weighted pool's tokens, in token order, for the
+    /// tokens that have one
+    fn stake_pools(&self) -> Vec<Pubkey> {
+        match &self.pool {
+            SolSwapPool::Stable(_) => Vec::new(),
+            SolSwapPool::Weighted(pool) => pool
+                .token_weights
+                .iter()
+                .map(|token| token.stake_pool)
+                .filter(|stake_pool| *stake_pool != Pubkey::default())
+                .collect(),
+        }
+    }
+
+    fn indices(&self, input_mint: &Pubkey, output_mint: &Pubkey) -> Result<(usize, usize)> {
+        let mints = self.mints();
+        let index = |mint: &Pubkey| {
+            mints
+                .iter()
+                .position(|m| m == mint)
+                .ok_or_else(|| anyhow!("{mint} is not in pool {}", self.key))
+        };
+        Ok((index(input_mint)?, index(output_mint)?))
+    }
+
+    /// Checks `vault` is a token account for `mint` owned by the pool authority
+    fn check_vault(&self, account_map: &AccountMap, vault: &Pubkey, mint: &Pubkey) -> Result<()> {
+        let account = account_map
+            .get(vault)
+            .ok_or_else(|| anyhow!("missing vault account {vault}"))?;
+        ensure!(account.owner == spl_token::ID, "vault {vault} is not a token account");
+        let token_account = spl_token::state::Account::unpack(&account.data)
+            .with_context(|| format!("invalid vault account {vault}"))?;
+        ensure!(
+            token_account.owner == self.authority && token_account.mint == *mint,
+            "vault {vault} does not hold {mint} for pool {}",
+            self.key
+        );
+        Ok(())
+    }
+
+    fn load_stake_pool_rates(&self, account_map: &AccountMap) -> Result<Vec<StakePoolRate>> {
+        let SolSwapPool::Weighted(pool) = &self.pool else {
+            return Ok(Vec::new());
+        };
+        pool.token_weights
+            .iter()
+            .filter(|token| token.stake_pool != Pubkey::default())
+            .map(|token| {
+                let account = account_map
+                    .get(&token.stake_pool)
+                    .ok_or_else(|| anyhow!("missing stake pool account {}", token.stake_pool))?;
+                parse_stake_pool_rate(&account.owner, &account.data, &token.mint)
+                    .with_context(|| format!("invalid stake pool {}", token.stake_pool))
+            })
+            .collect()
+    }
+
+    /// In and out amounts and fee rate of a weighted pool swap
+    fn quote_weighted(
+        &self,
+        pool: &RiskAdjustedPool,
+        index_in: usize,
+        index_out: usize,
+        quote_params: &QuoteParams,
+    ) -> Result<(u64, u64, u16)> {
+        let rates = self
+            .stake_pool_rates
+            .as_ref()
+            .ok_or_else(|| anyhow!("stake pool rates for pool {} are not loaded", self.key))?;
+        let values = weighted_swap::token_values(pool, rates)?;
+        let now = self.clock_ref.unix_timestamp.load(Ordering::Relaxed);
+
+        Ok(match quote_params.swap_mode {
+            SwapMode::ExactIn => {
+                let (out_amount, fee_bps) =
+                    weighted_swap::quote_exact_in_at(pool, index_in, index_out, quote_params.amount, &values, now)?;
+                (quote_params.amount, out_amount, fee_bps)
+            }
+            SwapMode::ExactOut => {
+                let (in_amount, fee_bps) =
+                    weighted_swap::quote_exact_out_at(pool, index_in, index_out, quote_params.amount, &values, now)?;
+                (in_amount, quote_params.amount, fee_bps)
+            }
+        })
+    }
+}
+
+impl Amm for SolSwapAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self> {
+        let params = keyed_account
+            .params
+            .clone()
+            .ok_or_else(|| anyhow!("market {} has no params listing its vaults", keyed_account.key))?;
+        let params: MarketParams = serde_json::from_value(params).context("invalid SolSwap market params")?;
+        let vaults = params
+            .vaults
+            .iter()
+            .map(|vault| Pubkey::from_str(vault).with_context(|| format!("invalid vault {vault}")))
+            .collect::<Result<_>>()?;
+
+        Self::from_account_data(
+            keyed_account.key,
+            &keyed_account.account.owner,
+            &keyed_account.account.data,
+            vaults,
+            amm_context.clock_ref.clone(),
+        )
+    }
+
+    fn label(&self) -> String {
+        "SolSwap".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        solswap::ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        self.mints()
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.key];
+        accounts.extend(&self.vaults);
+        accounts.extend(self.stake_pools());
+        accounts
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let account = account_map
+            .get(&self.key)
+            .ok_or_else(|| anyhow!("missing pool account {}", self.key))?;
+        let amm = Self::from_account_data(
+            self.key,
+            &account.owner,
+            &account.data,
+            self.vaults.clone(),
+            self.clock_ref.clone(),
+        )?;
+
+        for (vault, mint) in amm.vaults.iter().zip(amm.mints()) {
+            amm.check_vault(account_map, vault, &mint)?;
+        }
+        let stake_pool_rates = amm.load_stake_pool_rates(account_map)?;
+
+        *self = Self {
+            stake_pool_rates: Some(stake_pool_rates),
+            ..amm
+        };
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let (index_in, index_out) = self.indices(&quote_params.input_mint, &quote_params.output_mint)?;
+
+        let (in_amount, out_amount, fee_bps) = match &self.pool {
+            SolSwapPool::Stable(pool) => {
+                if pool.active_flash_loan.is_some() {
+                    bail!("pool {} has an outstanding flash loan", self.key);
+                }
+                match quote_params.swap_mode {
+                    SwapMode::ExactIn => {
+                        let quote = Quoter::quote_stable(pool, index_in, index_out, quote_params.amount)?;
+                        (quote_params.amount, quote.amount_out, pool.fee_bps)
+                    }
+                    SwapMode::ExactOut => {
+                        let in_amount =
+                            Quoter::quote_stable_exact_out(pool, index_in, index_out, quote_params.amount)?;
+                        (in_amount, quote_params.amount, pool.fee_bps)
+                    }
+                }
+            }
+            SolSwapPool::Weighted(pool) => self.quote_weighted(pool, index_in, index_out, quote_params)?,
+        };
+
+        Ok(Quote {
+            in_amount,
+            out_amount,
+            fee_amount: in_amount - stable::amount_after_fee(in_amount, fee_bps),
+            fee_mint: quote_params.input_mint,
+            fee_pct: Decimal::new(fee_bps as i64, 4),
+            ..Quote::default()
+        })
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        self.indices(&swap_params.source_mint, &swap_params.destination_mint)?;
+        // Any existing variant would have Jupiter encode another program's
+        // instruction against SolSwap's accounts
+        bail!("Jupiter has no swap variant for SolSwap; build the swap with `swap_account_metas`")
+    }
+
+    fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn supports_exact_out(&self) -> bool {
+        true
+    }
+
+    /// Keeps Jupiter from routing through pools it cannot build swaps for
+    fn is_active(&self) -> bool {
+        false
+    }
+}
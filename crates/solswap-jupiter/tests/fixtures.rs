@@ -0,0 +1,234 @@
+//! Replays pool accounts through the adapter and checks its quotes against
+//! the amounts the program moved for the same trades. The fixtures are
+//! recorded by `programs/solswap/tests/jupiter_fixtures.rs`.
+
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use jupiter_amm_interface::{AccountMap, Amm, ClockRef, QuoteParams, SwapMode, SwapParams};
+use serde::Deserialize;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solswap_jupiter::SolSwapAmm;
+
+#[derive(Deserialize)]
+struct Fixtures {
+    pools: Vec<PoolFixture>,
+}
+
+#[derive(Deserialize)]
+struct PoolFixture {
+    pool: String,
+    owner: String,
+    data: String,
+    mints: Vec<String>,
+    vaults: Vec<AccountFixture>,
+    quotes: Vec<QuoteFixture>,
+}
+
+#[derive(Deserialize)]
+struct AccountFixture {
+    key: String,
+    owner: String,
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct QuoteFixture {
+    input_mint: String,
+    output_mint: String,
+    swap_mode: String,
+    amount: u64,
+    in_amount: u64,
+    out_amount: u64,
+}
+
+fn pubkey(s: &str) -> Pubkey {
+    Pubkey::from_str(s).unwrap()
+}
+
+fn account(owner: &str, data: &str) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data: STANDARD.decode(data).unwrap(),
+        owner: pubkey(owner),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Stableswap pool fixture first, then the weighted pool
+fn load() -> Vec<PoolFixture> {
+    let fixtures: Fixtures = serde_json::from_str(include_str!("fixtures/pools.json")).unwrap();
+    fixtures.pools
+}
+
+impl PoolFixture {
+    fn key(&self) -> Pubkey {
+        pubkey(&self.pool)
+    }
+
+    fn account(&self) -> Account {
+        account(&self.owner, &self.data)
+    }
+
+    fn vault_keys(&self) -> Vec<Pubkey> {
+        self.vaults.iter().map(|vault| pubkey(&vault.key)).collect()
+    }
+
+    fn accounts(&self) -> AccountMap {
+        let mut accounts = AccountMap::default();
+        accounts.insert(self.key(), self.account());
+        for vault in &self.vaults {
+            accounts.insert(pubkey(&vault.key), account(&vault.owner, &vault.data));
+        }
+        accounts
+    }
+
+    /// Adapter for the pool, updated from the recorded accounts
+    fn amm(&self) -> SolSwapAmm {
+        let account = self.account();
+        let mut amm = SolSwapAmm::from_account_data(
+            self.key(),
+            &account.owner,
+            &account.data,
+            self.vault_keys(),
+            ClockRef::default(),
+        )
+        .unwrap();
+        amm.update(&self.accounts()).unwrap();
+        amm
+    }
+}
+
+#[test]
+fn test_quotes_match_program_swaps() {
+    for fixture in load() {
+        let amm = fixture.amm();
+        assert_eq!(
+            amm.get_reserve_mints(),
+            fixture.mints.iter().map(|m| pubkey(m)).collect::<Vec<_>>()
+        );
+
+        for expected in &fixture.quotes {
+            let swap_mode = match expected.swap_mode.as_str() {
+                "ExactIn" => SwapMode::ExactIn,
+                _ => SwapMode::ExactOut,
+            };
+            let quote = amm
+                .quote(&QuoteParams {
+                    amount: expected.amount,
+                    input_mint: pubkey(&expected.input_mint),
+                    output_mint: pubkey(&expected.output_mint),
+                    swap_mode,
+                })
+                .unwrap();
+            assert_eq!(quote.in_amount, expected.in_amount, "{} {}", fixture.pool, expected.swap_mode);
+            assert_eq!(quote.out_amount, expected.out_amount, "{} {}", fixture.pool, expected.swap_mode);
+            assert_eq!(quote.fee_mint, pubkey(&expected.input_mint));
+        }
+    }
+}
+
+#[test]
+fn test_update_reloads_pool_balances() {
+    let fixture = &load()[0];
+    let mut amm = fixture.amm();
+    let params = QuoteParams {
+        amount: 1_000_000,
+        input_mint: pubkey(&fixture.mints[0]),
+        output_mint: pubkey(&fixture.mints[1]),
+        swap_mode: SwapMode::ExactIn,
+    };
+    let before = amm.quote(&params).unwrap();
+
+    // Double token 1's balance; its price against token 0 falls
+    let mut accounts = fixture.accounts();
+    let updated = accounts.get_mut(&amm.key()).unwrap();
+    let balances_offset = 8 + 32 + 1 + 4 + 32 * fixture.mints.len() + 4;
+    let offset = balances_offset + 8;
+    let balance = u64::from_le_bytes(updated.data[offset..offset + 8].try_into().unwrap());
+    updated.data[offset..offset + 8].copy_from_slice(&(balance * 2).to_le_bytes());
+
+    let mut expected_accounts = vec![amm.key()];
+    expected_accounts.extend(fixture.vault_keys());
+    assert_eq!(amm.get_accounts_to_update(), expected_accounts);
+    amm.update(&accounts).unwrap();
+    assert!(amm.quote(&params).unwrap().out_amount > before.out_amount);
+}
+
+#[test]
+fn test_update_checks_vaults() {
+    for fixture in load() {
+        let mut amm = fixture.amm();
+        let vaults = fixture.vault_keys();
+
+        // A vault for another token, or owned by someone other than the pool
+        // authority, is not one the program would accept
+        let mut accounts = fixture.accounts();
+        let swapped = accounts[&vaults[1]].clone();
+        accounts.insert(vaults[0], swapped);
+        assert!(amm.update(&accounts).is_err());
+
+        let mut accounts = fixture.accounts();
+        accounts.get_mut(&vaults[0]).unwrap().data[32..64].copy_from_slice(&Pubkey::new_unique().to_bytes());
+        assert!(amm.update(&accounts).is_err());
+
+        let mut accounts = fixture.accounts();
+        accounts.remove(&vaults[2]);
+        assert!(amm.update(&accounts).is_err());
+    }
+}
+
+#[test]
+fn test_swap_accounts_follow_route_layout() {
+    for fixture in load() {
+        let amm = fixture.amm();
+        let (source_mint, destination_mint) = (pubkey(&fixture.mints[0]), pubkey(&fixture.mints[2]));
+        let user = Pubkey::new_unique();
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let jupiter_program_id = Pubkey::new_unique();
+        let swap_params = SwapParams {
+            in_amount: 1_000_000,
+            out_amount: 1_000_000,
+            source_mint,
+            destination_mint,
+            source_token_account: source,
+            destination_token_account: destination,
+            token_transfer_authority: user,
+            quote_mint_to_referrer: None,
+            jupiter_program_id: &jupiter_program_id,
+            missing_dynamic_accounts_as_default: false,
+        };
+
+        // No Jupiter swap variant encodes SolSwap's instructions, so the
+        // pools must not be offered for routing
+        assert!(!amm.is_active());
+        assert!(amm.get_swap_and_account_metas(&swap_params).is_err());
+
+        let metas = amm.swap_account_metas(&swap_params).unwrap();
+        let (authority, _) = Pubkey::find_program_address(&[b"authority", amm.key().as_ref()], &solswap::ID);
+        let keys: Vec<Pubkey> = metas.iter().map(|meta| meta.pubkey).collect();
+        let vaults = fixture.vault_keys();
+        assert_eq!(
+            keys,
+            vec![source, destination, user, spl_token::ID, amm.key(), authority, vaults[0], vaults[2]]
+        );
+        assert_eq!(amm.vault(&source_mint), Some(vaults[0]));
+        assert!(metas[2].is_signer && metas[4].is_writable && !metas[5].is_writable);
+    }
+}
+
+#[test]
+fn test_rejects_foreign_accounts() {
+    let fixture = &load()[0];
+    let account = fixture.account();
+    let key = fixture.key();
+    let vaults = fixture.vault_keys();
+    let from_data = |owner: &Pubkey, data: &[u8], vaults: Vec<Pubkey>| {
+        SolSwapAmm::from_account_data(key, owner, data, vaults, ClockRef::default())
+    };
+    assert!(from_data(&Pubkey::new_unique(), &account.data, vaults.clone()).is_err());
+    assert!(from_data(&account.owner, &account.data[..40], vaults.clone()).is_err());
+    assert!(from_data(&account.owner, &account.data, vaults[..2].to_vec()).is_err());
+}
@@ -0,0 +1,146 @@
+//! Keeper configuration, read from a JSON file.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub rpc_url: String,
+    /// Fee payer and signer for authority-gated cranks
+    pub keypair: String,
+    /// Program owning SOL/LST pools, oracles, validators and insurance funds
+    #[serde(with = "pubkey")]
+    pub lst_program: Pubkey,
+    /// Program owning stableswap and weighted pools
+    #[serde(with = "pubkey")]
+    pub pool_program: Pubkey,
+    /// Seconds between scheduling passes
+    #[serde(default = "default_tick_seconds")]
+    pub tick_seconds: u64,
+    /// Most cranks packed into one transaction
+    #[serde(default = "default_max_instructions")]
+    pub max_instructions_per_tx: usize,
+    /// Backoff after a crank's first failure, doubled on each further failure
+    #[serde(default = "default_retry_seconds")]
+    pub retry_seconds: i64,
+    #[serde(default = "default_max_retry_seconds")]
+    pub max_retry_seconds: i64,
+    /// Address serving `/metrics`; metrics are not served when unset
+    pub metrics_listen: Option<String>,
+    #[serde(default)]
+    pub price_feeds: Vec<PriceFeed>,
+    #[serde(default)]
+    pub validators: Vec<Validator>,
+    #[serde(default)]
+    pub weighted_pools: Vec<WeightedPool>,
+    #[serde(default)]
+    pub liquidity_pools: Vec<LiquidityPool>,
+}
+
+/// Accounts for `update_price`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriceFeed {
+    #[serde(with = "pubkey")]
+    pub pool: Pubkey,
+    #[serde(with = "pubkey")]
+    pub oracle_state: Pubkey,
+    #[serde(with = "pubkey")]
+    pub pyth_price_account: Pubkey,
+    #[serde(with = "pubkey")]
+    pub switchboard_feed: Pubkey,
+}
+
+/// Accounts for `update_validator_metrics`; the keypair must be the
+/// validator state's update authority
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Validator {
+    #[serde(with = "pubkey")]
+    pub validator_state: Pubkey,
+    #[serde(with = "pubkey")]
+    pub pool_state: Pubkey,
+}
+
+/// Accounts for `compound_rewards` and `rebalance_pool`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightedPool {
+    #[serde(with = "pubkey")]
+    pub pool: Pubkey,
+    #[serde(with = "pubkey")]
+    pub reserves_tracker: Pubkey,
+    #[serde(default = "enabled")]
+    pub compound: bool,
+    #[serde(default = "enabled")]
+    pub rebalance: bool,
+}
+
+/// Accounts for `update_liquidity_parameters`, which has no on-chain
+/// cooldown; `interval` is measured from the insurance fund's last rebalance
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiquidityPool {
+    #[serde(with = "pubkey")]
+    pub pool_state: Pubkey,
+    #[serde(with = "pubkey")]
+    pub insurance_fund: Pubkey,
+    #[serde(default = "default_liquidity_interval")]
+    pub interval: i64,
+}
+
+fn default_tick_seconds() -> u64 {
+    1
+}
+
+fn default_max_instructions() -> usize {
+    8
+}
+
+fn default_retry_seconds() -> i64 {
+    5
+}
+
+fn default_max_retry_seconds() -> i64 {
+    300
+}
+
+fn default_liquidity_interval() -> i64 {
+    300
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+        serde_json::from_str(&data).with_context(|| format!("invalid config {}", path.display()))
+    }
+
+    /// `keypair` with a leading `~/` expanded
+    pub fn keypair_path(&self) -> String {
+        match (self.keypair.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+            _ => self.keypair.clone(),
+        }
+    }
+}
+
+mod pubkey {
+    use std::str::FromStr;
+
+    use serde::{de::Error, Deserialize, Deserializer};
+    use solana_sdk::pubkey::Pubkey;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(|e| D::Error::custom(format!("{value}: {e}")))
+    }
+}
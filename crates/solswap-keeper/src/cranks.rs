@@ -0,0 +1,235 @@
+//! The cranked instructions and when each becomes callable again.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::config::{Config, LiquidityPool, PriceFeed, Validator, WeightedPool};
+use crate::{instructions, layout};
+
+/// `update_price` requires strictly more than this many seconds between updates
+pub const PRICE_UPDATE_INTERVAL: i64 = 3;
+/// `update_validator_metrics` accepts one update per validator a day
+pub const VALIDATOR_UPDATE_INTERVAL: i64 = 86_400;
+/// Scale of `update_validator_metrics`' performance argument
+pub const PERFORMANCE_BPS: u64 = 10_000;
+
+/// Cranks in the order they are packed into a transaction, so prices and
+/// liquidity parameters are refreshed before anything that reads them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Crank {
+    UpdatePrice,
+    UpdateLiquidityParameters,
+    UpdateValidatorMetrics,
+    CompoundRewards,
+    RebalancePool,
+}
+
+impl Crank {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Crank::UpdatePrice => "update_price",
+            Crank::UpdateLiquidityParameters => "update_liquidity_parameters",
+            Crank::UpdateValidatorMetrics => "update_validator_metrics",
+            Crank::CompoundRewards => "compound_rewards",
+            Crank::RebalancePool => "rebalance_pool",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Target {
+    Price(PriceFeed),
+    Liquidity(LiquidityPool),
+    Validator(Validator),
+    Compound(WeightedPool),
+    Rebalance(WeightedPool),
+}
+
+/// One crank of one account
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub crank: Crank,
+    target: Target,
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.crank.name(), self.account())
+    }
+}
+
+/// On-chain state a job was last observed in
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Observation {
+    /// Earliest cluster time at which the instruction passes its cooldown
+    pub ready_at: i64,
+    /// Vote account of a validator job
+    pub vote_account: Option<Pubkey>,
+    /// Remaining accounts of a compound job
+    pub stake_pools: Vec<Pubkey>,
+}
+
+/// Everything needed to build a due job's instruction
+pub struct Environment<'a> {
+    pub lst_program: &'a Pubkey,
+    pub pool_program: &'a Pubkey,
+    pub authority: &'a Pubkey,
+    /// Validator performance by vote account (basis points)
+    pub performance: &'a HashMap<Pubkey, u64>,
+}
+
+impl Job {
+    /// Every job the config describes, in packing order
+    pub fn from_config(config: &Config) -> Vec<Job> {
+        let mut jobs: Vec<Job> =
+            config
+                .price_feeds
+                .iter()
+                .map(|feed| Job { crank: Crank::UpdatePrice, target: Target::Price(feed.clone()) })
+                .chain(config.liquidity_pools.iter().map(|pool| Job {
+                    crank: Crank::UpdateLiquidityParameters,
+                    target: Target::Liquidity(pool.clone()),
+                }))
+                .chain(config.validators.iter().map(|validator| Job {
+                    crank: Crank::UpdateValidatorMetrics,
+                    target: Target::Validator(validator.clone()),
+                }))
+                .collect();
+        for pool in &config.weighted_pools {
+            if pool.compound {
+                jobs.push(Job { crank: Crank::CompoundRewards, target: Target::Compound(pool.clone()) });
+            }
+            if pool.rebalance {
+                jobs.push(Job { crank: Crank::RebalancePool, target: Target::Rebalance(pool.clone()) });
+            }
+        }
+        jobs.sort_by_key(|job| job.crank);
+        jobs
+    }
+
+    /// The account whose cooldown the job tracks
+    pub fn account(&self) -> Pubkey {
+        match &self.target {
+            Target::Price(feed) => feed.oracle_state,
+            Target::Liquidity(pool) => pool.insurance_fund,
+            Target::Validator(validator) => validator.validator_state,
+            Target::Compound(pool) | Target::Rebalance(pool) => pool.pool,
+        }
+    }
+
+    /// Accounts to fetch for `observe`
+    pub fn watched(&self) -> Vec<Pubkey> {
+        match &self.target {
+            Target::Compound(pool) => vec![pool.pool, pool.reserves_tracker],
+            _ => vec![self.account()],
+        }
+    }
+
+    /// Reads the job's cooldown from the data of its `watched` accounts
+    pub fn observe(&self, accounts: &[Option<Vec<u8>>]) -> Result<Observation> {
+        let data = |index: usize| -> Result<&[u8]> {
+            accounts
+                .get(index)
+                .and_then(Option::as_deref)
+                .ok_or_else(|| anyhow!("account {} not found", self.watched()[index]))
+        };
+        let observation = match &self.target {
+            Target::Price(_) => Observation {
+                ready_at: layout::oracle_last_update(data(0)?)? + PRICE_UPDATE_INTERVAL + 1,
+                ..Default::default()
+            },
+            Target::Liquidity(pool) => Observation {
+                ready_at: layout::insurance_last_rebalance(data(0)?)? + pool.interval,
+                ..Default::default()
+            },
+            Target::Validator(_) => {
+                let info = layout::validator_info(data(0)?)?;
+                let ready_at = match info.last_update {
+                    0 => 0,
+                    last => last + VALIDATOR_UPDATE_INTERVAL,
+                };
+                Observation { ready_at, vote_account: Some(info.vote_account), ..Default::default() }
+            }
+            Target::Compound(_) => {
+                let pool = layout::weighted_pool_info(data(0)?)?;
+                Observation {
+                    ready_at: layout::reserves_last_update(data(1)?)? + pool.min_compound_interval,
+                    stake_pools: pool.stake_pools,
+                    ..Default::default()
+                }
+            }
+            Target::Rebalance(_) => {
+                let pool = layout::weighted_pool_info(data(0)?)?;
+                Observation { ready_at: pool.last_rebalance + pool.rebalance_interval, ..Default::default() }
+            }
+        };
+        Ok(observation)
+    }
+
+    pub fn instruction(&self, observation: &Observation, env: &Environment) -> Result<Instruction> {
+        let instruction = match &self.target {
+            Target::Price(feed) => instructions::update_price(env.lst_program, feed),
+            Target::Liquidity(pool) => instructions::update_liquidity_parameters(env.lst_program, pool),
+            Target::Validator(validator) => {
+                let vote_account = observation.vote_account.context("validator observation has no vote account")?;
+                let performance = env
+                    .performance
+                    .get(&vote_account)
+                    .with_context(|| format!("vote account {vote_account} has no recent epoch credits"))?;
+                // MEV tips are paid through the stake pools and measured by
+                // `compound_rewards`; the keeper reports none here
+                instructions::update_validator_metrics(env.lst_program, validator, env.authority, *performance, 0)
+            }
+            Target::Compound(pool) => instructions::compound_rewards(env.pool_program, pool, &observation.stake_pools),
+            Target::Rebalance(pool) => instructions::rebalance_pool(env.pool_program, pool, env.authority),
+        };
+        Ok(instruction)
+    }
+}
+
+/// A vote account's `(epoch, credits, previous_credits)` entries as
+/// `getVoteAccounts` reports them
+pub type EpochCredits = (Pubkey, Vec<(u64, u64, u64)>);
+
+/// Performance of each vote account over `epoch` as its share of the best
+/// validator's vote credits that epoch (basis points)
+pub fn performance_bps(epoch_credits: &[EpochCredits], epoch: u64) -> HashMap<Pubkey, u64> {
+    let earned: Vec<(Pubkey, u64)> = epoch_credits
+        .iter()
+        .filter_map(|(vote_account, entries)| {
+            entries
+                .iter()
+                .find(|(entry_epoch, _, _)| *entry_epoch == epoch)
+                .map(|(_, credits, previous)| (*vote_account, credits.saturating_sub(*previous)))
+        })
+        .collect();
+    let best = earned.iter().map(|(_, credits)| *credits).max().unwrap_or(0);
+    earned
+        .into_iter()
+        .map(|(vote_account, credits)| (vote_account, (credits * PERFORMANCE_BPS).checked_div(best).unwrap_or(0)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_performance_bps() {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let credits = vec![
+            (a, vec![(9, 1_000, 600), (10, 1_400, 1_000)]),
+            (b, vec![(9, 500, 200), (10, 800, 500)]),
+            // Did not vote in epoch 9
+            (c, vec![(8, 300, 0)]),
+        ];
+        let performance = performance_bps(&credits, 9);
+        assert_eq!(performance.len(), 2);
+        assert_eq!(performance[&a], 10_000);
+        assert_eq!(performance[&b], 7_500);
+    }
+}
@@ -0,0 +1,84 @@
+//! Instruction builders for the cranked instructions, mirroring the
+//! programs' account contexts.
+
+use solana_sdk::hash::hashv;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{pubkey, sysvar};
+
+use crate::config::{LiquidityPool, PriceFeed, Validator, WeightedPool};
+
+pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+/// Anchor instruction data: `sha256("global:<name>")[..8]` then the
+/// borsh-encoded arguments
+fn data(name: &str, args: &[u8]) -> Vec<u8> {
+    let mut data = hashv(&[b"global:", name.as_bytes()]).to_bytes()[..8].to_vec();
+    data.extend_from_slice(args);
+    data
+}
+
+pub fn update_price(program: &Pubkey, feed: &PriceFeed) -> Instruction {
+    Instruction::new_with_bytes(
+        *program,
+        &data("update_price", &[]),
+        vec![
+            AccountMeta::new(feed.pool, false),
+            AccountMeta::new(feed.oracle_state, false),
+            AccountMeta::new_readonly(feed.pyth_price_account, false),
+            AccountMeta::new_readonly(feed.switchboard_feed, false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+        ],
+    )
+}
+
+pub fn update_validator_metrics(
+    program: &Pubkey,
+    validator: &Validator,
+    authority: &Pubkey,
+    performance: u64,
+    mev_reward: u64,
+) -> Instruction {
+    let mut args = performance.to_le_bytes().to_vec();
+    args.extend(mev_reward.to_le_bytes());
+    Instruction::new_with_bytes(
+        *program,
+        &data("update_validator_metrics", &args),
+        vec![
+            AccountMeta::new(validator.validator_state, false),
+            AccountMeta::new(validator.pool_state, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+        ],
+    )
+}
+
+pub fn compound_rewards(program: &Pubkey, pool: &WeightedPool, stake_pools: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(pool.pool, false), AccountMeta::new(pool.reserves_tracker, false)];
+    accounts.extend(stake_pools.iter().map(|stake_pool| AccountMeta::new_readonly(*stake_pool, false)));
+    Instruction::new_with_bytes(*program, &data("compound_rewards", &[]), accounts)
+}
+
+pub fn rebalance_pool(program: &Pubkey, pool: &WeightedPool, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program,
+        &data("rebalance_pool", &[]),
+        vec![
+            AccountMeta::new(pool.pool, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+    )
+}
+
+pub fn update_liquidity_parameters(program: &Pubkey, pool: &LiquidityPool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program,
+        &data("update_liquidity_parameters", &[]),
+        vec![
+            AccountMeta::new(pool.pool_state, false),
+            AccountMeta::new(pool.insurance_fund, false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+        ],
+    )
+}
@@ -0,0 +1,169 @@
+//! Readers for the cooldown fields of the accounts the keeper cranks.
+//!
+//! Only the leading fields each crank needs are decoded, so the keeper does
+//! not depend on the programs' crates and tolerates fields appended later.
+
+use anyhow::{bail, ensure, Context, Result};
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+
+/// Anchor account discriminator: `sha256("account:<Name>")[..8]`
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hashv(&[b"account:", name.as_bytes()]).to_bytes()[..8]);
+    discriminator
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], account: &str) -> Result<Self> {
+        ensure!(data.len() >= 8, "{account} account is too short");
+        if data[..8] != account_discriminator(account) {
+            bail!("not a {account} account");
+        }
+        Ok(Self { data, offset: 8 })
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self.data.get(self.offset..self.offset + len).context("account data ends early")?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into()?))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.bytes(8)?.try_into()?))
+    }
+
+    fn pubkey(&mut self) -> Result<Pubkey> {
+        Ok(Pubkey::try_from(self.bytes(32)?)?)
+    }
+}
+
+/// `OracleState::last_update_ts`
+pub fn oracle_last_update(data: &[u8]) -> Result<i64> {
+    let mut reader = Reader::new(data, "OracleState")?;
+    // last_price, ema_price
+    reader.skip(16)?;
+    reader.i64()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatorInfo {
+    /// Vote account the metrics describe
+    pub vote_account: Pubkey,
+    pub last_update: i64,
+}
+
+pub fn validator_info(data: &[u8]) -> Result<ValidatorInfo> {
+    let mut reader = Reader::new(data, "ValidatorState")?;
+    let vote_account = reader.pubkey()?;
+    // performance_history, current_index, mev_rewards, total_stake,
+    // average_apr, missed_blocks
+    reader.skip(30 * 8 + 1 + 4 * 8)?;
+    Ok(ValidatorInfo { vote_account, last_update: reader.i64()? })
+}
+
+/// `InsuranceFund::last_rebalance`, set by `update_liquidity_parameters`
+pub fn insurance_last_rebalance(data: &[u8]) -> Result<i64> {
+    let mut reader = Reader::new(data, "InsuranceFund")?;
+    // total_assets, target/minimum/maximum allocation, current_risk_level
+    reader.skip(8 + 4 * 2)?;
+    reader.i64()
+}
+
+/// `ReservesTracker::last_update`, set by `compound_rewards`
+pub fn reserves_last_update(data: &[u8]) -> Result<i64> {
+    let mut reader = Reader::new(data, "ReservesTracker")?;
+    // pool
+    reader.skip(32)?;
+    reader.i64()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedPoolInfo {
+    pub rebalance_interval: i64,
+    pub min_compound_interval: i64,
+    pub last_rebalance: i64,
+    /// Stake pools backing the pool's tokens in `token_weights` order, the
+    /// remaining accounts of `compound_rewards`
+    pub stake_pools: Vec<Pubkey>,
+}
+
+pub fn weighted_pool_info(data: &[u8]) -> Result<WeightedPoolInfo> {
+    let mut reader = Reader::new(data, "RiskAdjustedPool")?;
+    // authority, bump, config.max_weight
+    reader.skip(32 + 1 + 1)?;
+    let rebalance_interval = reader.i64()?;
+    // max_weight_deviation, swap_fee_bps, weight_change_window,
+    // max_weight_change_per_second, max_rebalance_slippage_bps
+    reader.skip(1 + 2 + 8 + 8 + 2)?;
+    let min_compound_interval = reader.i64()?;
+    // rebalance_fee_sensitivity, max_swap_fee_bps
+    reader.skip(2 + 2)?;
+
+    let tokens = reader.u32()?;
+    let mut stake_pools = Vec::new();
+    for _ in 0..tokens {
+        // mint, weight, start_weight, amount
+        reader.skip(32 + 2 + 2 + 8)?;
+        let stake_pool = reader.pubkey()?;
+        if stake_pool != Pubkey::default() {
+            stake_pools.push(stake_pool);
+        }
+    }
+
+    Ok(WeightedPoolInfo { rebalance_interval, min_compound_interval, last_rebalance: reader.i64()?, stake_pools })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_pool_info() {
+        let stake_pool = Pubkey::new_unique();
+        let mut data = account_discriminator("RiskAdjustedPool").to_vec();
+        data.extend([7; 32]);
+        data.push(255);
+        data.push(60);
+        data.extend(3_600i64.to_le_bytes());
+        data.extend([0; 1 + 2 + 8 + 8 + 2]);
+        data.extend(86_400i64.to_le_bytes());
+        data.extend([0; 4]);
+        data.extend(2u32.to_le_bytes());
+        for backing in [Pubkey::default(), stake_pool] {
+            data.extend([1; 32 + 2 + 2 + 8]);
+            data.extend(backing.to_bytes());
+        }
+        data.extend(1_700_000_000i64.to_le_bytes());
+        // Trailing fields are ignored
+        data.extend([9; 64]);
+
+        let info = weighted_pool_info(&data).unwrap();
+        assert_eq!(
+            info,
+            WeightedPoolInfo {
+                rebalance_interval: 3_600,
+                min_compound_interval: 86_400,
+                last_rebalance: 1_700_000_000,
+                stake_pools: vec![stake_pool],
+            }
+        );
+
+        data[0] ^= 1;
+        assert!(weighted_pool_info(&data).is_err());
+        assert!(oracle_last_update(&account_discriminator("OracleState")).is_err());
+    }
+}
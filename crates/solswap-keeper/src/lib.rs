@@ -0,0 +1,12 @@
+//! Keeper for SolSwap's permissionless and authority-gated cranks: oracle
+//! price updates, validator metrics, reward compounding, weight rebalancing
+//! and liquidity parameter updates, each sent once its on-chain cooldown
+//! has elapsed.
+
+pub mod config;
+pub mod cranks;
+pub mod instructions;
+pub mod layout;
+pub mod metrics;
+pub mod rpc;
+pub mod scheduler;
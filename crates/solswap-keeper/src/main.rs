@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::signature::{read_keypair_file, Signer};
+use solswap_keeper::config::Config;
+use solswap_keeper::metrics::{self, Metrics};
+use solswap_keeper::rpc::RpcChain;
+use solswap_keeper::scheduler::Scheduler;
+
+const USAGE: &str = "\
+usage: solswap-keeper --config <keeper.json> [--once]
+
+Sends every crank in the config once its on-chain cooldown has elapsed.
+--once runs a single pass and exits non-zero if any crank failed.";
+
+fn main() -> Result<()> {
+    let mut config_path = None;
+    let mut once = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => config_path = Some(args.next().context("--config needs a value")?),
+            "--once" => once = true,
+            _ => {
+                eprintln!("{USAGE}");
+                std::process::exit(2);
+            }
+        }
+    }
+    let Some(config_path) = config_path else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+
+    let config = Config::load(&config_path)?;
+    let payer =
+        read_keypair_file(config.keypair_path()).map_err(|e| anyhow!("cannot read keypair {}: {e}", config.keypair))?;
+    let metrics = Arc::new(Metrics::new());
+    let mut scheduler = Scheduler::new(&config, payer.pubkey(), metrics.clone());
+    let chain = RpcChain::new(&config.rpc_url, payer);
+    println!("keeping {} cranks", scheduler.jobs().len());
+
+    if once {
+        let tick = scheduler.tick(&chain)?;
+        println!("{} due, {} sent, {} failed", tick.due, tick.sent, tick.failed);
+        if tick.failed > 0 {
+            bail!("{} cranks failed", tick.failed);
+        }
+        return Ok(());
+    }
+
+    if let Some(listen) = &config.metrics_listen {
+        metrics::serve(metrics, listen)?;
+        println!("serving metrics on http://{listen}/metrics");
+    }
+    let interval = Duration::from_secs(config.tick_seconds.max(1));
+    loop {
+        if let Err(e) = scheduler.tick(&chain) {
+            eprintln!("scheduling pass failed: {e:#}");
+        }
+        thread::sleep(interval);
+    }
+}
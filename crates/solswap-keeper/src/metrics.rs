@@ -0,0 +1,101 @@
+//! Prometheus metrics, served in the text exposition format on `/metrics`.
+
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use anyhow::{anyhow, Result};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tiny_http::{Header, Response, Server};
+
+use crate::cranks::Job;
+
+pub struct Metrics {
+    registry: Registry,
+    /// Cranks sent, by crank and `ok` / `failed`
+    pub cranks: IntCounterVec,
+    /// Transactions sent, by `ok` / `failed`
+    pub transactions: IntCounterVec,
+    pub batch_size: Histogram,
+    /// Seconds until each job is due; negative while overdue
+    pub due_in: IntGaugeVec,
+    /// Cluster time of each job's last successful crank
+    pub last_success: IntGaugeVec,
+    /// Failures since each job last succeeded
+    pub consecutive_failures: IntGaugeVec,
+    /// Scheduling passes that could not read the cluster
+    pub tick_errors: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("solswap_keeper".into()), None).unwrap();
+        let job_labels = ["crank", "account"];
+        let metrics = Self {
+            cranks: IntCounterVec::new(Opts::new("cranks_total", "Cranks sent"), &["crank", "result"]).unwrap(),
+            transactions: IntCounterVec::new(Opts::new("transactions_total", "Transactions sent"), &["result"])
+                .unwrap(),
+            batch_size: Histogram::with_opts(
+                HistogramOpts::new("batch_size", "Cranks per transaction").buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0]),
+            )
+            .unwrap(),
+            due_in: IntGaugeVec::new(Opts::new("due_in_seconds", "Seconds until a crank is due"), &job_labels).unwrap(),
+            last_success: IntGaugeVec::new(
+                Opts::new("last_success_timestamp", "Cluster time of a crank's last success"),
+                &job_labels,
+            )
+            .unwrap(),
+            consecutive_failures: IntGaugeVec::new(
+                Opts::new("consecutive_failures", "Failures since a crank last succeeded"),
+                &job_labels,
+            )
+            .unwrap(),
+            tick_errors: IntCounter::new("tick_errors_total", "Scheduling passes that failed").unwrap(),
+            registry,
+        };
+        metrics.registry.register(Box::new(metrics.cranks.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.transactions.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.batch_size.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.due_in.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.last_success.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.consecutive_failures.clone())).unwrap();
+        metrics.registry.register(Box::new(metrics.tick_errors.clone())).unwrap();
+        metrics
+    }
+
+    /// `gauge`'s series for one job
+    pub fn job_gauge(gauge: &IntGaugeVec, job: &Job) -> IntGauge {
+        gauge.with_label_values(&[job.crank.name(), &job.account().to_string()])
+    }
+
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serves `/metrics` on `listen` from a background thread
+pub fn serve(metrics: Arc<Metrics>, listen: &str) -> Result<JoinHandle<()>> {
+    let server = Server::http(listen).map_err(|e| anyhow!("cannot listen on {listen}: {e}"))?;
+    Ok(thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = if request.url() == "/metrics" {
+                let header = Header::from_bytes("Content-Type", TextEncoder::new().format_type()).unwrap();
+                Response::from_string(metrics.encode()).with_header(header)
+            } else {
+                Response::from_string("not found").with_status_code(404)
+            };
+            if let Err(e) = request.respond(response) {
+                eprintln!("failed to respond: {e}");
+            }
+        }
+    }))
+}
@@ -0,0 +1,145 @@
+//! `Chain` over a Solana JSON-RPC node.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_sdk::clock::Clock;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
+
+use crate::cranks::performance_bps;
+use crate::scheduler::Chain;
+
+/// Most accounts per `getMultipleAccounts` request
+const ACCOUNTS_PAGE: usize = 100;
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+const CONFIRM_POLL: Duration = Duration::from_millis(500);
+
+pub struct RpcChain {
+    url: String,
+    agent: ureq::Agent,
+    payer: Keypair,
+}
+
+impl RpcChain {
+    pub fn new(url: &str, payer: Keypair) -> Self {
+        Self { url: url.to_string(), agent: ureq::Agent::new(), payer }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))?
+            .into_json()?;
+        if let Some(error) = response.get("error") {
+            bail!("{method} failed: {error}");
+        }
+        Ok(response["result"].clone())
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{ "commitment": "confirmed" }]))?;
+        let blockhash = result["value"]["blockhash"].as_str().context("unexpected getLatestBlockhash result")?;
+        Ok(Hash::from_str(blockhash)?)
+    }
+
+    /// Waits until `signature` is confirmed, failing if it landed with an error
+    fn confirm(&self, signature: &Signature) -> Result<()> {
+        let start = Instant::now();
+        while start.elapsed() < CONFIRM_TIMEOUT {
+            let result = self.call("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    bail!("transaction {signature} failed: {}", status["err"]);
+                }
+                if matches!(status["confirmationStatus"].as_str(), Some("confirmed" | "finalized")) {
+                    return Ok(());
+                }
+            }
+            thread::sleep(CONFIRM_POLL);
+        }
+        bail!("transaction {signature} not confirmed after {}s", CONFIRM_TIMEOUT.as_secs())
+    }
+}
+
+fn decode_account(value: &Value) -> Result<Option<Vec<u8>>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let data = value["data"][0].as_str().context("unexpected account encoding")?;
+    Ok(Some(STANDARD.decode(data)?))
+}
+
+impl Chain for RpcChain {
+    fn unix_timestamp(&self) -> Result<i64> {
+        let data = self.accounts(&[sysvar::clock::ID])?.pop().flatten().context("clock sysvar not found")?;
+        let clock: Clock = bincode::deserialize(&data)?;
+        Ok(clock.unix_timestamp)
+    }
+
+    fn accounts(&self, keys: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>> {
+        let mut accounts = Vec::with_capacity(keys.len());
+        for page in keys.chunks(ACCOUNTS_PAGE) {
+            let keys: Vec<String> = page.iter().map(Pubkey::to_string).collect();
+            let result =
+                self.call("getMultipleAccounts", json!([keys, { "encoding": "base64", "commitment": "confirmed" }]))?;
+            let values = result["value"].as_array().context("unexpected getMultipleAccounts result")?;
+            for value in values {
+                accounts.push(decode_account(value)?);
+            }
+        }
+        Ok(accounts)
+    }
+
+    fn vote_performance(&self) -> Result<HashMap<Pubkey, u64>> {
+        let epoch = self.call("getEpochInfo", json!([{ "commitment": "confirmed" }]))?["epoch"]
+            .as_u64()
+            .context("unexpected getEpochInfo result")?;
+        let completed = epoch.checked_sub(1).ok_or_else(|| anyhow!("no epoch has completed yet"))?;
+
+        let result = self.call("getVoteAccounts", json!([{ "commitment": "confirmed" }]))?;
+        let mut epoch_credits = Vec::new();
+        for account in ["current", "delinquent"].iter().filter_map(|list| result[*list].as_array()).flatten() {
+            let vote_account = Pubkey::from_str(account["votePubkey"].as_str().context("vote account has no key")?)?;
+            let entries = account["epochCredits"]
+                .as_array()
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter_map(|entry| Some((entry[0].as_u64()?, entry[1].as_u64()?, entry[2].as_u64()?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            epoch_credits.push((vote_account, entries));
+        }
+        Ok(performance_bps(&epoch_credits, completed))
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.latest_blockhash()?,
+        );
+        let encoded = STANDARD.encode(bincode::serialize(&transaction)?);
+        // Preflight simulation rejects cranks that would fail before they cost a fee
+        let result = self
+            .call("sendTransaction", json!([encoded, { "encoding": "base64", "preflightCommitment": "confirmed" }]))?;
+        let signature = Signature::from_str(result.as_str().context("unexpected sendTransaction result")?)?;
+        self.confirm(&signature)?;
+        Ok(signature)
+    }
+}
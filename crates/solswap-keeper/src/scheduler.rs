@@ -0,0 +1,349 @@
+//! Decides which cranks are due, packs them into transactions and retries
+//! the ones that fail.
+//!
+//! Cooldowns are read from the accounts on every pass and compared with the
+//! cluster clock, so the keeper never sends an instruction the program would
+//! reject for being early, and picks up where it left off after a restart or
+//! after another keeper has cranked. A transaction that fails takes all its
+//! cranks down with it; those cranks are retried alone so one bad account
+//! cannot hold the others back, and a crank failing alone backs off
+//! exponentially.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::Transaction;
+
+use crate::config::Config;
+use crate::cranks::{Crank, Environment, Job, Observation};
+use crate::metrics::Metrics;
+
+/// What the scheduler needs from the cluster
+pub trait Chain {
+    /// Cluster time from the clock sysvar
+    fn unix_timestamp(&self) -> Result<i64>;
+    /// Account data in `keys` order, `None` for missing accounts
+    fn accounts(&self, keys: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>>;
+    /// Performance of every vote account over the last completed epoch
+    /// (basis points)
+    fn vote_performance(&self) -> Result<HashMap<Pubkey, u64>>;
+    /// Signs, sends and confirms a transaction
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature>;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Retry {
+    failures: u32,
+    /// Cluster time before which the job is not retried
+    not_before: i64,
+    /// Send the job in a transaction of its own
+    alone: bool,
+}
+
+/// Outcome of one scheduling pass
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tick {
+    pub due: usize,
+    pub sent: usize,
+    pub failed: usize,
+    pub transactions: usize,
+}
+
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    retries: Vec<Retry>,
+    lst_program: Pubkey,
+    pool_program: Pubkey,
+    /// Fee payer and crank authority
+    authority: Pubkey,
+    max_instructions: usize,
+    retry_seconds: i64,
+    max_retry_seconds: i64,
+    metrics: Arc<Metrics>,
+}
+
+impl Scheduler {
+    pub fn new(config: &Config, authority: Pubkey, metrics: Arc<Metrics>) -> Self {
+        let jobs = Job::from_config(config);
+        Self {
+            retries: vec![Retry::default(); jobs.len()],
+            jobs,
+            lst_program: config.lst_program,
+            pool_program: config.pool_program,
+            authority,
+            max_instructions: config.max_instructions_per_tx.max(1),
+            retry_seconds: config.retry_seconds,
+            max_retry_seconds: config.max_retry_seconds,
+            metrics,
+        }
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// Sends every crank that is due
+    pub fn tick(&mut self, chain: &impl Chain) -> Result<Tick> {
+        let result = self.run(chain);
+        if result.is_err() {
+            self.metrics.tick_errors.inc();
+        }
+        result
+    }
+
+    fn run(&mut self, chain: &impl Chain) -> Result<Tick> {
+        let now = chain.unix_timestamp()?;
+        let watched: Vec<Pubkey> = self.jobs.iter().flat_map(Job::watched).collect();
+        let accounts = chain.accounts(&watched)?;
+
+        let mut due: Vec<(usize, Observation)> = Vec::new();
+        let mut offset = 0;
+        for (index, job) in self.jobs.iter().enumerate() {
+            let count = job.watched().len();
+            let observation = job.observe(&accounts[offset..offset + count]);
+            offset += count;
+            match observation {
+                Ok(observation) => {
+                    Metrics::job_gauge(&self.metrics.due_in, job).set(observation.ready_at - now);
+                    if observation.ready_at <= now && self.retries[index].not_before <= now {
+                        due.push((index, observation));
+                    }
+                }
+                Err(e) => eprintln!("{job}: {e:#}"),
+            }
+        }
+
+        let mut tick = Tick { due: due.len(), ..Default::default() };
+        let performance = if due.iter().any(|(index, _)| self.jobs[*index].crank == Crank::UpdateValidatorMetrics) {
+            chain.vote_performance().unwrap_or_else(|e| {
+                eprintln!("cannot read vote accounts: {e:#}");
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+        let env = Environment {
+            lst_program: &self.lst_program,
+            pool_program: &self.pool_program,
+            authority: &self.authority,
+            performance: &performance,
+        };
+
+        let mut ready: Vec<(usize, Instruction)> = Vec::new();
+        let mut failed: Vec<usize> = Vec::new();
+        for (index, observation) in &due {
+            match self.jobs[*index].instruction(observation, &env) {
+                Ok(instruction) => ready.push((*index, instruction)),
+                Err(e) => {
+                    eprintln!("{}: {e:#}", self.jobs[*index]);
+                    failed.push(*index);
+                }
+            }
+        }
+        for index in failed {
+            self.record_failure(index, now, false);
+            tick.failed += 1;
+        }
+
+        for batch in self.batches(ready) {
+            let indices: Vec<usize> = batch.iter().map(|(index, _)| *index).collect();
+            let instructions: Vec<Instruction> = batch.into_iter().map(|(_, instruction)| instruction).collect();
+            self.metrics.batch_size.observe(instructions.len() as f64);
+            tick.transactions += 1;
+            match chain.send(&instructions) {
+                Ok(signature) => {
+                    self.metrics.transactions.with_label_values(&["ok"]).inc();
+                    for index in indices {
+                        println!("{} in {signature}", self.jobs[index]);
+                        self.record_success(index, now);
+                        tick.sent += 1;
+                    }
+                }
+                Err(e) => {
+                    self.metrics.transactions.with_label_values(&["failed"]).inc();
+                    let shared = indices.len() > 1;
+                    for index in indices {
+                        eprintln!("{}: {e:#}", self.jobs[index]);
+                        self.record_failure(index, now, shared);
+                        tick.failed += 1;
+                    }
+                }
+            }
+        }
+        Ok(tick)
+    }
+
+    /// Packs instructions into transactions of at most `max_instructions`
+    /// that fit in a packet, keeping jobs marked `alone` on their own
+    fn batches(&self, ready: Vec<(usize, Instruction)>) -> Vec<Vec<(usize, Instruction)>> {
+        let mut batches: Vec<Vec<(usize, Instruction)>> = Vec::new();
+        let mut batch: Vec<(usize, Instruction)> = Vec::new();
+        for (index, instruction) in ready {
+            if self.retries[index].alone {
+                batches.push(vec![(index, instruction)]);
+                continue;
+            }
+            batch.push((index, instruction));
+            if batch.len() > self.max_instructions || !self.fits(&batch) {
+                let last = batch.pop().unwrap();
+                if !batch.is_empty() {
+                    batches.push(std::mem::take(&mut batch));
+                }
+                batch.push(last);
+            }
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+        batches
+    }
+
+    fn fits(&self, batch: &[(usize, Instruction)]) -> bool {
+        let instructions: Vec<Instruction> = batch.iter().map(|(_, instruction)| instruction.clone()).collect();
+        let transaction = Transaction::new_with_payer(&instructions, Some(&self.authority));
+        bincode::serialized_size(&transaction).is_ok_and(|size| size as usize <= PACKET_DATA_SIZE)
+    }
+
+    fn record_success(&mut self, index: usize, now: i64) {
+        let job = &self.jobs[index];
+        self.metrics.cranks.with_label_values(&[job.crank.name(), "ok"]).inc();
+        Metrics::job_gauge(&self.metrics.last_success, job).set(now);
+        Metrics::job_gauge(&self.metrics.consecutive_failures, job).set(0);
+        self.retries[index] = Retry::default();
+    }
+
+    /// A crank that failed alongside others is retried alone on the next
+    /// pass; one that failed alone backs off
+    fn record_failure(&mut self, index: usize, now: i64, shared: bool) {
+        let job = &self.jobs[index];
+        self.metrics.cranks.with_label_values(&[job.crank.name(), "failed"]).inc();
+        let retry = &mut self.retries[index];
+        retry.alone = true;
+        if !shared {
+            retry.failures += 1;
+            let backoff =
+                self.retry_seconds.saturating_mul(1 << (retry.failures - 1).min(16)).min(self.max_retry_seconds);
+            retry.not_before = now + backoff;
+        }
+        Metrics::job_gauge(&self.metrics.consecutive_failures, job).set(retry.failures as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::layout::account_discriminator;
+
+    struct MockChain {
+        now: i64,
+        /// Oracle `last_update_ts` by account
+        oracles: HashMap<Pubkey, i64>,
+        /// Oracles whose `update_price` always fails
+        broken: Vec<Pubkey>,
+        sent: RefCell<Vec<Vec<Pubkey>>>,
+    }
+
+    impl Chain for MockChain {
+        fn unix_timestamp(&self) -> Result<i64> {
+            Ok(self.now)
+        }
+
+        fn accounts(&self, keys: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>> {
+            Ok(keys
+                .iter()
+                .map(|key| {
+                    self.oracles.get(key).map(|last_update| {
+                        let mut data = account_discriminator("OracleState").to_vec();
+                        data.extend([0; 16]);
+                        data.extend(last_update.to_le_bytes());
+                        data
+                    })
+                })
+                .collect())
+        }
+
+        fn vote_performance(&self) -> Result<HashMap<Pubkey, u64>> {
+            Ok(HashMap::new())
+        }
+
+        fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+            let oracles: Vec<Pubkey> = instructions.iter().map(|ix| ix.accounts[1].pubkey).collect();
+            self.sent.borrow_mut().push(oracles.clone());
+            if oracles.iter().any(|oracle| self.broken.contains(oracle)) {
+                anyhow::bail!("simulation failed");
+            }
+            Ok(Signature::default())
+        }
+    }
+
+    fn config(oracles: &[Pubkey]) -> Config {
+        let feeds = oracles
+            .iter()
+            .map(|oracle| {
+                format!(
+                    r#"{{"pool": "{}", "oracle_state": "{oracle}", "pyth_price_account": "{}", "switchboard_feed": "{}"}}"#,
+                    Pubkey::new_unique(),
+                    Pubkey::new_unique(),
+                    Pubkey::new_unique()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        serde_json::from_str(&format!(
+            r#"{{"rpc_url": "", "keypair": "", "lst_program": "{}", "pool_program": "{}",
+                "max_instructions_per_tx": 2, "retry_seconds": 5, "price_feeds": [{feeds}]}}"#,
+            Pubkey::new_unique(),
+            Pubkey::new_unique()
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cooldowns_batching_and_retries() {
+        let oracles: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut chain = MockChain {
+            now: 1_000,
+            // The last oracle updated 3s ago and is still cooling down
+            oracles: oracles.iter().zip([0, 990, 996, 997]).map(|(k, t)| (*k, t)).collect(),
+            broken: vec![oracles[1]],
+            sent: RefCell::new(Vec::new()),
+        };
+        let mut scheduler = Scheduler::new(&config(&oracles), Pubkey::new_unique(), Arc::new(Metrics::new()));
+
+        // The broken oracle sinks the first transaction
+        let tick = scheduler.tick(&chain).unwrap();
+        assert_eq!(tick, Tick { due: 3, sent: 1, failed: 2, transactions: 2 });
+        assert_eq!(chain.sent.take(), vec![vec![oracles[0], oracles[1]], vec![oracles[2]]]);
+
+        // Both are retried alone at once; only the broken one fails and backs off
+        chain.now += 1;
+        chain.oracles.insert(oracles[2], 1_000);
+        let tick = scheduler.tick(&chain).unwrap();
+        assert_eq!(tick, Tick { due: 3, sent: 2, failed: 1, transactions: 3 });
+        assert_eq!(chain.sent.take(), vec![vec![oracles[0]], vec![oracles[1]], vec![oracles[3]]]);
+
+        // Everything is cooling down or backing off until 1006
+        chain.now = 1_005;
+        for oracle in [oracles[0], oracles[2], oracles[3]] {
+            chain.oracles.insert(oracle, 1_002);
+        }
+        assert_eq!(scheduler.tick(&chain).unwrap().due, 0);
+        chain.now = 1_006;
+        let tick = scheduler.tick(&chain).unwrap();
+        assert_eq!(tick, Tick { due: 4, sent: 3, failed: 1, transactions: 3 });
+        assert_eq!(chain.sent.take(), vec![vec![oracles[1]], vec![oracles[0], oracles[2]], vec![oracles[3]]]);
+        let metrics = scheduler.metrics.encode();
+        assert!(metrics.contains(r#"solswap_keeper_cranks_total{crank="update_price",result="failed"} 4"#));
+        assert!(metrics.contains(&format!(
+            r#"solswap_keeper_consecutive_failures{{account="{}",crank="update_price"}} 2"#,
+            oracles[1]
+        )));
+    }
+}
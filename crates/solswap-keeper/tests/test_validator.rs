@@ -0,0 +1,36 @@
+//! Runs a keeper pass against a live cluster, normally a local
+//! `solana-test-validator` with both programs deployed and the configured
+//! accounts initialized:
+//!
+//! ```bash
+//! SOLSWAP_KEEPER_CONFIG=keeper.json cargo test -- --ignored
+//! ```
+
+use std::sync::Arc;
+
+use solana_sdk::signature::{read_keypair_file, Signer};
+use solswap_keeper::config::Config;
+use solswap_keeper::metrics::Metrics;
+use solswap_keeper::rpc::RpcChain;
+use solswap_keeper::scheduler::{Chain, Scheduler};
+
+#[test]
+#[ignore = "needs a running validator; set SOLSWAP_KEEPER_CONFIG"]
+fn test_cranks_against_validator() {
+    let path = std::env::var("SOLSWAP_KEEPER_CONFIG").expect("SOLSWAP_KEEPER_CONFIG is not set");
+    let config = Config::load(path).unwrap();
+    let payer = read_keypair_file(config.keypair_path()).unwrap();
+    let metrics = Arc::new(Metrics::new());
+    let mut scheduler = Scheduler::new(&config, payer.pubkey(), metrics.clone());
+    let chain = RpcChain::new(&config.rpc_url, payer);
+
+    let first = scheduler.tick(&chain).unwrap();
+    assert_eq!(first.failed, 0, "cranks failed:\n{}", metrics.encode());
+
+    // Whatever was just cranked is cooling down again
+    if first.sent > 0 {
+        let now = chain.unix_timestamp().unwrap();
+        let second = scheduler.tick(&chain).unwrap();
+        assert!(second.due < first.due || chain.unix_timestamp().unwrap() > now + 3);
+    }
+}
@@ -0,0 +1,33 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// An intermediate value does not fit its type
+    Overflow,
+    /// The pool cannot pay out the requested amount
+    InsufficientLiquidity,
+    /// Indices, amplification or pool size are out of range
+    InvalidInput,
+    /// An iterative solver did not converge
+    NoConvergence,
+    /// The LST exchange rate has not been verified
+    RateNotVerified,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MathError::Overflow => "math overflow",
+            MathError::InsufficientLiquidity => "insufficient liquidity",
+            MathError::InvalidInput => "invalid input",
+            MathError::NoConvergence => "solver did not converge",
+            MathError::RateNotVerified => "exchange rate not verified",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MathError {}
+
+pub type Result<T> = core::result::Result<T, MathError>;
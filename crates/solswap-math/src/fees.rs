@@ -0,0 +1,114 @@
+//! Utilization, risk and balance driven fees of SOL/LST pools
+
+use crate::BPS_DENOMINATOR;
+
+/// Constants for liquidity protection calculations
+pub const BASE_FEE_RATE: u64 = 30;           // 0.3% base fee in basis points
+pub const MIN_FEE_RATE: u64 = 10;            // 0.1% minimum fee
+pub const MAX_FEE_RATE: u64 = 100;           // 1% maximum fee
+pub const OPTIMAL_UTILIZATION: u64 = 8_000;   // 80% optimal utilization
+pub const SLOPE_1: u64 = 20;                 // Rate of increase below optimal
+pub const SLOPE_2: u64 = 100;                // Rate of increase above optimal
+pub const MAX_FEE_CHANGE: u64 = 5;           // Default max change per update
+pub const BALANCE_FEE_SENSITIVITY: u64 = 10; // Fee change per bp of balance shift, in bps of the fee
+pub const MIN_DIRECTIONAL_MULTIPLIER: u64 = 5_000;  // Rebalancing trades pay at least half
+pub const MAX_DIRECTIONAL_MULTIPLIER: u64 = 20_000; // Unbalancing trades pay at most double
+
+/// Pool utilization in basis points (0-10000)
+pub fn pool_utilization(total_deposits: u64, total_borrows: u64) -> u64 {
+    if total_deposits == 0 {
+        return 0;
+    }
+    ((total_borrows as u128 * BPS_DENOMINATOR) / total_deposits as u128) as u64
+}
+
+/// Pool risk level (0-10000) from utilization and validator performance,
+/// overridden by `reserve_risk` from a verified reserve shortfall
+pub fn risk_level(utilization: u64, validator_performance: u64, reserve_risk: u64) -> u16 {
+    let utilization_risk = if utilization > OPTIMAL_UTILIZATION {
+        // Higher risk when utilization exceeds optimal
+        ((utilization - OPTIMAL_UTILIZATION) * 60) / (10_000 - OPTIMAL_UTILIZATION)
+    } else {
+        // Lower risk below optimal utilization
+        (utilization * 40) / OPTIMAL_UTILIZATION
+    };
+    let lst_risk = (10_000u64.saturating_sub(validator_performance) * 40) / 10_000;
+
+    (utilization_risk + lst_risk).max(reserve_risk).min(10_000) as u16
+}
+
+/// Target fee rate for the pool's utilization and risk level (basis points)
+pub fn dynamic_fee(utilization: u64, risk_level: u16, base_fee: u64) -> u64 {
+    let util_multiplier = if utilization <= OPTIMAL_UTILIZATION {
+        // Below optimal: gradual increase
+        base_fee + (utilization * SLOPE_1) / OPTIMAL_UTILIZATION
+    } else {
+        // Above optimal: steeper increase
+        let excess_util = utilization - OPTIMAL_UTILIZATION;
+        base_fee + SLOPE_1 + (excess_util * SLOPE_2) / (10_000 - OPTIMAL_UTILIZATION)
+    };
+
+    // Apply risk adjustment
+    let risk_multiplier = 10_000 + (risk_level as u64 * 5_000) / 10_000;
+    let fee_rate = (util_multiplier * risk_multiplier) / 10_000;
+
+    fee_rate.clamp(MIN_FEE_RATE, MAX_FEE_RATE)
+}
+
+/// Moves the fee from `current` towards `target` by at most `max_change`
+pub fn step_fee(current: u64, target: u64, max_change: u64) -> u64 {
+    if target > current {
+        current + (target - current).min(max_change)
+    } else {
+        current - (current - target).min(max_change)
+    }
+}
+
+/// Distance of the pool's LST share of value from an even split (basis points)
+pub fn balance_deviation(sol_value: u64, lst_value: u64) -> u64 {
+    let total = sol_value as u128 + lst_value as u128;
+    if total == 0 {
+        return 0;
+    }
+    let lst_share = (lst_value as u128 * BPS_DENOMINATOR / total) as u64;
+    lst_share.abs_diff(5_000)
+}
+
+/// Fee for one trade given the pool's balance before and after it.
+///
+/// Trades that move the pool towards an even split get a discount and trades
+/// that move it away a surcharge, `BALANCE_FEE_SENSITIVITY` bps of the fee per
+/// bp of shift, bounded to [0.5x, 2x] and then to the pool's floor and ceiling.
+pub fn directional_fee(
+    fee_rate: u64,
+    deviation_before: u64,
+    deviation_after: u64,
+    min_fee: u64,
+    max_fee: u64,
+) -> u64 {
+    let shift = deviation_after as i128 - deviation_before as i128;
+    let multiplier = (10_000 + shift * BALANCE_FEE_SENSITIVITY as i128)
+        .clamp(MIN_DIRECTIONAL_MULTIPLIER as i128, MAX_DIRECTIONAL_MULTIPLIER as i128) as u64;
+
+    (fee_rate * multiplier / 10_000).clamp(min_fee, max_fee)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_rises_with_utilization_and_risk() {
+        assert_eq!(pool_utilization(1_000, 800), 8_000);
+        assert_eq!(dynamic_fee(0, 0, 30), 30);
+        assert_eq!(dynamic_fee(8_000, 0, 30), 50);
+        assert_eq!(dynamic_fee(10_000, 0, 30), 100);
+        assert_eq!(dynamic_fee(8_000, 10_000, 30), 75);
+    }
+
+    #[test]
+    fn test_reserve_risk_overrides() {
+        assert_eq!(risk_level(8_000, 10_000, 0), 40);
+        assert_eq!(risk_level(8_000, 10_000, 6_000), 6_000);
+    }
+}
@@ -0,0 +1,22 @@
+//! Pricing math shared by the SolSwap programs and off-chain integrators.
+//!
+//! Everything here is integer arithmetic with no Solana dependencies, so a
+//! quote computed off-chain is bit-identical to the one the programs compute
+//! for the same account state. The crate is `no_std` unless the `std` feature
+//! is enabled.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod error;
+pub mod fees;
+pub mod lst;
+pub mod quoter;
+pub mod risk;
+pub mod stable;
+
+pub use error::{MathError, Result};
+pub use quoter::{Quote, Quoter};
+
+/// Basis point denominator for fees, weights and ratios
+pub const BPS_DENOMINATOR: u128 = 10_000;
@@ -0,0 +1,144 @@
+//! SOL/LST swaps priced at the LST's verified exchange rate
+
+use crate::error::{MathError, Result};
+use crate::fees::{balance_deviation, directional_fee};
+
+/// Direction of a swap between SOL and a pool's LST
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// User pays SOL and receives LST
+    SolToLst,
+    /// User pays LST and receives SOL
+    LstToSol,
+}
+
+/// Result of pricing a swap; fees are always charged in SOL
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SwapQuote {
+    /// Amount the user receives
+    pub amount_out: u64,
+    /// Fee kept by the pool, in lamports
+    pub fee: u64,
+    /// Fee rate applied to this trade (basis points)
+    pub fee_bps: u64,
+}
+
+/// Fee parameters of a pool at the time of a trade (basis points)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeParams {
+    pub fee_rate: u64,
+    pub min_fee: u64,
+    pub max_fee: u64,
+}
+
+/// SOL backing and LST supply of the protocol issuing an LST
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExchangeRate {
+    pub sol_reserves: u64,
+    pub lst_supply: u64,
+}
+
+impl ExchangeRate {
+    fn check(&self) -> Result<()> {
+        if self.sol_reserves == 0 || self.lst_supply == 0 {
+            return Err(MathError::RateNotVerified);
+        }
+        Ok(())
+    }
+
+    /// Lamports backing `tokens` LST, rounded down
+    pub fn lamports_for(&self, tokens: u64) -> Result<u64> {
+        self.check()?;
+        u64::try_from(tokens as u128 * self.sol_reserves as u128 / self.lst_supply as u128)
+            .map_err(|_| MathError::Overflow)
+    }
+
+    /// LST backed by `lamports`, rounded down
+    pub fn tokens_for(&self, lamports: u64) -> Result<u64> {
+        self.check()?;
+        u64::try_from(lamports as u128 * self.lst_supply as u128 / self.sol_reserves as u128)
+            .map_err(|_| MathError::Overflow)
+    }
+}
+
+/// Prices a swap at the LST's verified exchange rate, charging the pool's
+/// dynamic fee adjusted for whether the trade restores the SOL/LST balance
+pub fn quote_swap(
+    direction: SwapDirection,
+    amount_in: u64,
+    sol_reserve: u64,
+    lst_reserve: u64,
+    rate: ExchangeRate,
+    fees: FeeParams,
+) -> Result<SwapQuote> {
+    rate.check()?;
+
+    let lst_value = rate.lamports_for(lst_reserve)?;
+    let deviation_before = balance_deviation(sol_reserve, lst_value);
+
+    // SOL moved by the trade before fees
+    let sol_amount = match direction {
+        SwapDirection::SolToLst => amount_in,
+        SwapDirection::LstToSol => rate.lamports_for(amount_in)?,
+    };
+    let deviation_after = match direction {
+        SwapDirection::SolToLst => balance_deviation(
+            sol_reserve.saturating_add(sol_amount),
+            lst_value.saturating_sub(sol_amount),
+        ),
+        SwapDirection::LstToSol => balance_deviation(
+            sol_reserve.saturating_sub(sol_amount),
+            lst_value.saturating_add(sol_amount),
+        ),
+    };
+
+    let fee_bps = directional_fee(
+        fees.fee_rate,
+        deviation_before,
+        deviation_after,
+        fees.min_fee,
+        fees.max_fee,
+    );
+    // Round the fee up so the pool never undercharges
+    let fee = (sol_amount as u128 * fee_bps as u128).div_ceil(10_000) as u64;
+    let net = sol_amount.checked_sub(fee).ok_or(MathError::Overflow)?;
+
+    let amount_out = match direction {
+        SwapDirection::SolToLst => {
+            let amount_out = rate.tokens_for(net)?;
+            if amount_out > lst_reserve {
+                return Err(MathError::InsufficientLiquidity);
+            }
+            amount_out
+        }
+        SwapDirection::LstToSol => {
+            if sol_amount > sol_reserve {
+                return Err(MathError::InsufficientLiquidity);
+            }
+            net
+        }
+    };
+
+    Ok(SwapQuote { amount_out, fee, fee_bps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 1.1 SOL per LST
+    const RATE: ExchangeRate = ExchangeRate { sol_reserves: 1_100, lst_supply: 1_000 };
+    const FEES: FeeParams = FeeParams { fee_rate: 30, min_fee: 10, max_fee: 100 };
+
+    #[test]
+    fn test_trades_at_rate_less_fee() {
+        let quote = quote_swap(SwapDirection::SolToLst, 1_100_000, 1_000_000_000, 909_090_909, RATE, FEES).unwrap();
+        assert_eq!(quote, SwapQuote { amount_out: 997_000, fee: 3_300, fee_bps: 30 });
+    }
+
+    #[test]
+    fn test_unverified_rate_rejected() {
+        let quote = quote_swap(SwapDirection::LstToSol, 1_000, 100, 0, ExchangeRate::default(), FEES);
+        assert_eq!(quote, Err(MathError::RateNotVerified));
+    }
+}
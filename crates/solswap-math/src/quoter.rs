@@ -0,0 +1,241 @@
+//! Off-chain quotes from deserialized pool and oracle accounts.
+//!
+//! The programs implement the traits below for their own account types
+//! (`Pool`, `PoolState`, `OracleState`), so integrators can quote straight
+//! from account data fetched over RPC with the same math the programs run.
+
+use crate::error::{MathError, Result};
+use crate::lst::{self, ExchangeRate, FeeParams, SwapDirection};
+use crate::risk::{self, RiskMetrics, BASE_RISK_MULTIPLIER};
+use crate::stable;
+use crate::BPS_DENOMINATOR;
+
+/// Precision of oracle prices (SOL per LST)
+pub const ORACLE_PRICE_PRECISION: u128 = 1_000_000_000;
+
+/// A stableswap pool
+pub trait StableSwapPool {
+    /// Pool balance of each token
+    fn balances(&self) -> &[u64];
+    fn amp(&self) -> u64;
+    fn fee_bps(&self) -> u16;
+}
+
+/// A SOL/LST pool priced at the LST's verified exchange rate
+pub trait LstPool {
+    fn exchange_rate(&self) -> ExchangeRate;
+    fn fee_params(&self) -> FeeParams;
+    fn risk_metrics(&self) -> RiskMetrics;
+}
+
+/// A market price feed for an LST
+pub trait PriceOracle {
+    /// SOL per LST, scaled by `ORACLE_PRICE_PRECISION`
+    fn price(&self) -> i64;
+}
+
+/// Quote for a trade
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quote {
+    /// Amount the user receives
+    pub amount_out: u64,
+    /// Fee kept by the pool, in the token the pool charges it in
+    pub fee: u64,
+    /// Fee rate applied to this trade (basis points)
+    pub fee_bps: u64,
+    /// Value lost to the curve and to the gap between the pool's price and the
+    /// reference price, net of the fee (basis points)
+    pub price_impact_bps: u64,
+    /// Risk multiplier for the pool's metrics (basis points, 10000 = 1.0)
+    pub risk_multiplier: u64,
+}
+
+pub struct Quoter;
+
+impl Quoter {
+    /// Quotes `amount_in` of token `index_in` for token `index_out` in a
+    /// stableswap pool. Price impact is measured against the pool's marginal
+    /// price, sampled with a trade of 0.01% of the input balance.
+    pub fn quote_stable<P: StableSwapPool + ?Sized>(
+        pool: &P,
+        index_in: usize,
+        index_out: usize,
+        amount_in: u64,
+    ) -> Result<Quote> {
+        let balances = pool.balances();
+        let amount_out = stable::calc_out_given_in(
+            balances,
+            pool.amp(),
+            index_in,
+            index_out,
+            amount_in,
+            pool.fee_bps(),
+        )?;
+        let net_in = stable::amount_after_fee(amount_in, pool.fee_bps());
+
+        let reference_in = (balances[index_in] / 10_000).max(1);
+        let reference_out = stable::calc_out_given_in(balances, pool.amp(), index_in, index_out, reference_in, 0)?;
+        let ideal_out = net_in as u128 * reference_out as u128 / reference_in as u128;
+
+        Ok(Quote {
+            amount_out,
+            fee: amount_in - net_in,
+            fee_bps: pool.fee_bps() as u64,
+            price_impact_bps: shortfall_bps(ideal_out, amount_out as u128),
+            risk_multiplier: BASE_RISK_MULTIPLIER,
+        })
+    }
+
+    /// Input of token `index_in` needed for exactly `amount_out` of token
+    /// `index_out` in a stableswap pool, as `swap_exact_out` charges it
+    pub fn quote_stable_exact_out<P: StableSwapPool + ?Sized>(
+        pool: &P,
+        index_in: usize,
+        index_out: usize,
+        amount_out: u64,
+    ) -> Result<u64> {
+        stable::calc_in_given_out(
+            pool.balances(),
+            pool.amp(),
+            index_in,
+            index_out,
+            amount_out,
+            pool.fee_bps(),
+        )
+    }
+
+    /// Quotes a SOL/LST swap against the pool's reserves. Price impact is
+    /// measured against `oracle` when given, otherwise against the pool's
+    /// exchange rate.
+    pub fn quote_lst<P: LstPool + ?Sized, O: PriceOracle + ?Sized>(
+        pool: &P,
+        oracle: Option<&O>,
+        direction: SwapDirection,
+        amount_in: u64,
+        sol_reserve: u64,
+        lst_reserve: u64,
+    ) -> Result<Quote> {
+        let rate = pool.exchange_rate();
+        let quote = lst::quote_swap(direction, amount_in, sol_reserve, lst_reserve, rate, pool.fee_params())?;
+
+        // SOL per LST at the reference price
+        let price = match oracle {
+            Some(oracle) => u128::try_from(oracle.price()).map_err(|_| MathError::InvalidInput)?,
+            None => rate.sol_reserves as u128 * ORACLE_PRICE_PRECISION / rate.lst_supply as u128,
+        };
+        let (value_in, value_out) = match direction {
+            SwapDirection::SolToLst => (
+                (amount_in - quote.fee) as u128,
+                quote.amount_out as u128 * price / ORACLE_PRICE_PRECISION,
+            ),
+            SwapDirection::LstToSol => (
+                (amount_in as u128 * price / ORACLE_PRICE_PRECISION).saturating_sub(quote.fee as u128),
+                quote.amount_out as u128,
+            ),
+        };
+
+        Ok(Quote {
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            fee_bps: quote.fee_bps,
+            price_impact_bps: shortfall_bps(value_in, value_out),
+            risk_multiplier: risk::risk_multiplier(pool.risk_metrics()),
+        })
+    }
+}
+
+/// How far `actual` falls short of `expected`, in basis points
+fn shortfall_bps(expected: u128, actual: u128) -> u64 {
+    if expected == 0 {
+        return 0;
+    }
+    (expected.saturating_sub(actual) * BPS_DENOMINATOR / expected) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    struct TestStablePool(Vec<u64>);
+
+    impl StableSwapPool for TestStablePool {
+        fn balances(&self) -> &[u64] {
+            &self.0
+        }
+        fn amp(&self) -> u64 {
+            100
+        }
+        fn fee_bps(&self) -> u16 {
+            4
+        }
+    }
+
+    struct TestLstPool;
+
+    impl LstPool for TestLstPool {
+        fn exchange_rate(&self) -> ExchangeRate {
+            ExchangeRate { sol_reserves: 1_100, lst_supply: 1_000 }
+        }
+        fn fee_params(&self) -> FeeParams {
+            FeeParams { fee_rate: 30, min_fee: 10, max_fee: 100 }
+        }
+        fn risk_metrics(&self) -> RiskMetrics {
+            RiskMetrics { validator_performance: 10_000, reserve_ratio: 10_000, decentralization_score: 5_000 }
+        }
+    }
+
+    struct TestOracle(i64);
+
+    impl PriceOracle for TestOracle {
+        fn price(&self) -> i64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_stable_quote_matches_curve() {
+        let pool = TestStablePool(vec![1_000_000_000, 1_000_000_000]);
+        let quote = Quoter::quote_stable(&pool, 0, 1, 100_000_000).unwrap();
+        assert_eq!(
+            quote.amount_out,
+            stable::calc_out_given_in(&pool.0, 100, 0, 1, 100_000_000, 4).unwrap()
+        );
+        assert_eq!(quote.fee, 40_000);
+        // A 10% trade moves a balanced amp-100 pool only slightly
+        assert!(quote.price_impact_bps > 0 && quote.price_impact_bps < 10);
+
+        let small = Quoter::quote_stable(&pool, 0, 1, 1_000_000).unwrap();
+        assert!(small.price_impact_bps < quote.price_impact_bps);
+    }
+
+    #[test]
+    fn test_lst_quote_reports_impact_against_oracle() {
+        let at_rate = Quoter::quote_lst::<_, TestOracle>(
+            &TestLstPool,
+            None,
+            SwapDirection::SolToLst,
+            1_100_000,
+            1_000_000_000,
+            909_090_909,
+        )
+        .unwrap();
+        assert_eq!(at_rate.amount_out, 997_000);
+        assert_eq!(at_rate.price_impact_bps, 0);
+        assert_eq!(at_rate.risk_multiplier, 14_000);
+
+        // Market values the LST 1% below the rate, so buying it loses 1%
+        let oracle = TestOracle(1_089_000_000);
+        let at_market = Quoter::quote_lst(
+            &TestLstPool,
+            Some(&oracle),
+            SwapDirection::SolToLst,
+            1_100_000,
+            1_000_000_000,
+            909_090_909,
+        )
+        .unwrap();
+        assert_eq!(at_market.price_impact_bps, 100);
+    }
+}
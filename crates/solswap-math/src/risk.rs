@@ -0,0 +1,63 @@
+//! Risk multiplier derived from an LST's validator, reserve and
+//! decentralization metrics
+
+use crate::error::{MathError, Result};
+
+/// Constants for risk adjustment calculations
+pub const BASE_RISK_MULTIPLIER: u64 = 10_000; // 1.0 in basis points
+pub const MIN_RISK_MULTIPLIER: u64 = 5_000;   // 0.5 in basis points
+pub const MAX_RISK_MULTIPLIER: u64 = 15_000;  // 1.5 in basis points
+
+/// Weights for different risk components (must sum to 10000)
+pub const VALIDATOR_PERFORMANCE_WEIGHT: u64 = 4_000;  // 40%
+pub const RESERVE_RATIO_WEIGHT: u64 = 4_000;         // 40%
+pub const DECENTRALIZATION_WEIGHT: u64 = 2_000;      // 20%
+
+/// Metrics behind the risk multiplier, each in basis points
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RiskMetrics {
+    pub validator_performance: u64,
+    pub reserve_ratio: u64,
+    pub decentralization_score: u64,
+}
+
+/// Risk multiplier in basis points (10000 = 1.0), from 0.5 for the worst
+/// metrics to 1.5 for perfect ones
+pub fn risk_multiplier(metrics: RiskMetrics) -> u64 {
+    let performance_component = metrics.validator_performance.min(10_000) * VALIDATOR_PERFORMANCE_WEIGHT / 10_000;
+    let reserve_component = metrics.reserve_ratio.min(10_000) * RESERVE_RATIO_WEIGHT / 10_000;
+    let decentralization_component = metrics.decentralization_score.min(10_000) * DECENTRALIZATION_WEIGHT / 10_000;
+
+    let base_score = performance_component + reserve_component + decentralization_component;
+    MIN_RISK_MULTIPLIER + base_score * (MAX_RISK_MULTIPLIER - MIN_RISK_MULTIPLIER) / 10_000
+}
+
+/// Applies a risk multiplier (in basis points) to an amount
+pub fn apply_risk_multiplier(amount: u64, multiplier: u64) -> Result<u64> {
+    amount
+        .checked_mul(multiplier)
+        .map(|scaled| scaled / BASE_RISK_MULTIPLIER)
+        .ok_or(MathError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(value: u64) -> RiskMetrics {
+        RiskMetrics {
+            validator_performance: value,
+            reserve_ratio: value,
+            decentralization_score: value,
+        }
+    }
+
+    #[test]
+    fn test_multiplier_spans_range() {
+        assert_eq!(risk_multiplier(metrics(10_000)), MAX_RISK_MULTIPLIER);
+        assert_eq!(risk_multiplier(metrics(0)), MIN_RISK_MULTIPLIER);
+        assert_eq!(risk_multiplier(metrics(5_000)), BASE_RISK_MULTIPLIER);
+        // Metrics above 100% count as 100%
+        assert_eq!(risk_multiplier(metrics(20_000)), MAX_RISK_MULTIPLIER);
+    }
+}
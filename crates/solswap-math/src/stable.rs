@@ -0,0 +1,193 @@
+//! StableSwap invariant for pools of like-valued tokens
+
+use alloc::vec::Vec;
+use crate::error::{MathError, Result};
+use crate::BPS_DENOMINATOR;
+
+/// Newton iterations allowed for the invariant and balance solvers
+const MAX_ITERATIONS: usize = 255;
+
+/// StableSwap invariant D for `balances` and amplification `amp`:
+///
+/// A * n^n * sum(x) + D = A * D * n^n + D^(n+1) / (n^n * prod(x))
+pub fn compute_d(balances: &[u128], amp: u64) -> Result<u128> {
+    let n = balances.len() as u128;
+    if n < 2 || amp == 0 {
+        return Err(MathError::InvalidInput);
+    }
+
+    let sum: u128 = balances.iter().sum();
+    if sum == 0 {
+        return Ok(0);
+    }
+    if balances.contains(&0) {
+        return Err(MathError::InsufficientLiquidity);
+    }
+
+    let ann = amp as u128 * n;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for &x in balances {
+            d_p = d_p.checked_mul(d).ok_or(MathError::Overflow)? / (x * n);
+        }
+        let previous = d;
+        let numerator = (ann * sum + d_p * n).checked_mul(d).ok_or(MathError::Overflow)?;
+        let denominator = (ann - 1) * d + (n + 1) * d_p;
+        d = numerator / denominator;
+
+        if d.abs_diff(previous) <= 1 {
+            return Ok(d);
+        }
+    }
+    Err(MathError::NoConvergence)
+}
+
+/// Balance of the token at `index` that keeps the invariant at `d`, given the
+/// other entries of `balances`; the entry at `index` is ignored
+pub fn compute_y(balances: &[u128], amp: u64, index: usize, d: u128) -> Result<u128> {
+    let n = balances.len() as u128;
+    if index >= balances.len() || amp == 0 {
+        return Err(MathError::InvalidInput);
+    }
+    let ann = amp as u128 * n;
+
+    let mut c = d;
+    let mut sum = 0u128;
+    for (k, &x) in balances.iter().enumerate() {
+        if k == index {
+            continue;
+        }
+        if x == 0 {
+            return Err(MathError::InsufficientLiquidity);
+        }
+        sum += x;
+        c = c.checked_mul(d).ok_or(MathError::Overflow)? / (x * n);
+    }
+    c = c.checked_mul(d).ok_or(MathError::Overflow)? / (ann * n);
+    let b = sum + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let previous = y;
+        let numerator = y.checked_mul(y).ok_or(MathError::Overflow)? + c;
+        let denominator = (2 * y + b).checked_sub(d).ok_or(MathError::NoConvergence)?;
+        y = numerator / denominator;
+
+        if y.abs_diff(previous) <= 1 {
+            return Ok(y);
+        }
+    }
+    Err(MathError::NoConvergence)
+}
+
+fn check_indices(balances: &[u64], index_in: usize, index_out: usize) -> Result<()> {
+    if index_in == index_out || index_in >= balances.len() || index_out >= balances.len() {
+        return Err(MathError::InvalidInput);
+    }
+    Ok(())
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| MathError::Overflow)
+}
+
+/// Input left after the fee, rounded down
+pub fn amount_after_fee(amount_in: u64, fee_bps: u16) -> u64 {
+    (amount_in as u128 * (BPS_DENOMINATOR - fee_bps as u128) / BPS_DENOMINATOR) as u64
+}
+
+/// Amount of token `index_out` received for `amount_in` of token `index_in`.
+/// The fee is taken from the input; rounds against the trader.
+pub fn calc_out_given_in(
+    balances: &[u64],
+    amp: u64,
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    check_indices(balances, index_in, index_out)?;
+    let mut balances: Vec<u128> = balances.iter().map(|&x| x as u128).collect();
+    let d = compute_d(&balances, amp)?;
+
+    balances[index_in] += amount_after_fee(amount_in, fee_bps) as u128;
+    let y = compute_y(&balances, amp, index_out, d)?;
+
+    // One unit is held back to cover the solver's rounding
+    to_u64(balances[index_out].saturating_sub(y).saturating_sub(1))
+}
+
+/// Amount of token `index_in` required to receive exactly `amount_out` of
+/// token `index_out`. The fee is charged on the input; rounds against the trader.
+pub fn calc_in_given_out(
+    balances: &[u64],
+    amp: u64,
+    index_in: usize,
+    index_out: usize,
+    amount_out: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    check_indices(balances, index_in, index_out)?;
+    if amount_out >= balances[index_out] {
+        return Err(MathError::InsufficientLiquidity);
+    }
+    let mut balances: Vec<u128> = balances.iter().map(|&x| x as u128).collect();
+    let d = compute_d(&balances, amp)?;
+
+    balances[index_out] -= amount_out as u128;
+    let y = compute_y(&balances, amp, index_in, d)?;
+
+    // One unit is added to cover the solver's rounding
+    let amount_in_before_fee = y.saturating_sub(balances[index_in]) + 1;
+    let fee_complement = BPS_DENOMINATOR - fee_bps as u128;
+    to_u64((amount_in_before_fee * BPS_DENOMINATOR).div_ceil(fee_complement))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balanced_pool_trades_near_par() {
+        let out = calc_out_given_in(&[1_000_000_000, 1_000_000_000], 100, 0, 1, 1_000_000, 0).unwrap();
+        assert_eq!(out, 999_990);
+    }
+
+    #[test]
+    fn test_invariant_holds_after_swap() {
+        let balances = [5_000_000_000u64, 3_000_000_000, 4_000_000_000];
+        let d = compute_d(&balances.map(|x| x as u128), 200).unwrap();
+
+        let amount_in = 250_000_000;
+        let out = calc_out_given_in(&balances, 200, 1, 2, amount_in, 0).unwrap();
+        let after = [balances[0], balances[1] + amount_in, balances[2] - out];
+        let d_after = compute_d(&after.map(|x| x as u128), 200).unwrap();
+        assert!(d_after >= d);
+    }
+
+    #[test]
+    fn test_exact_out_round_trip_never_profits() {
+        let balances = [3_000_000_000u64, 7_000_000_000];
+        for amount_out in [1u64, 1_000, 1_000_000, 2_000_000_000] {
+            let amount_in = calc_in_given_out(&balances, 150, 0, 1, amount_out, 4).unwrap();
+            let received = calc_out_given_in(&balances, 150, 0, 1, amount_in, 4).unwrap();
+            assert!(received >= amount_out);
+        }
+    }
+
+    #[test]
+    fn test_fee_reduces_output() {
+        let balances = [2_000_000_000u64, 2_000_000_000];
+        assert_eq!(calc_out_given_in(&balances, 100, 0, 1, 10_000_000, 0).unwrap(), 9_999_504);
+        assert_eq!(calc_out_given_in(&balances, 100, 0, 1, 10_000_000, 30).unwrap(), 9_969_507);
+    }
+
+    #[test]
+    fn test_invalid_indices_rejected() {
+        let balances = [1_000u64, 1_000];
+        assert_eq!(calc_out_given_in(&balances, 100, 0, 0, 10, 0), Err(MathError::InvalidInput));
+        assert_eq!(calc_out_given_in(&balances, 100, 0, 2, 10, 0), Err(MathError::InvalidInput));
+        assert_eq!(calc_in_given_out(&balances, 100, 0, 1, 1_000, 0), Err(MathError::InsufficientLiquidity));
+    }
+}
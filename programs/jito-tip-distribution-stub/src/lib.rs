@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+// Deployed at the real tip distribution address so SolSwap's CPIs reach it unchanged
+declare_id!("4R3gSG8BpU4t19KYj8CfnbtRpnT8gtk4dvTHxVRwc2r7");
+
+pub const CLAIM_STATUS_SEED: &[u8] = b"CLAIM_STATUS";
+
+/// Mirrors the account layout and `claim` interface of Jito's tip distribution
+/// program closely enough for SolSwap's tests: tips are held as lamports on a
+/// `TipDistributionAccount` and released against a merkle proof.
+#[program]
+pub mod jito_tip_distribution_stub {
+    use super::*;
+
+    /// Creates a funded tip distribution account for a validator's epoch
+    pub fn initialize_tip_distribution_account(
+        ctx: Context<InitializeTipDistributionAccount>,
+        validator_vote_account: Pubkey,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+    ) -> Result<()> {
+        let account = &mut ctx.accounts.tip_distribution_account;
+        account.validator_vote_account = validator_vote_account;
+        account.merkle_root = merkle_root;
+        account.max_total_claim = max_total_claim;
+        account.total_funds_claimed = 0;
+        Ok(())
+    }
+
+    /// Pays `amount` lamports to `claimant` if the proof matches the merkle root
+    pub fn claim(ctx: Context<Claim>, _bump: u8, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let claimant = ctx.accounts.claimant.key();
+        let account = &mut ctx.accounts.tip_distribution_account;
+
+        let node = hashv(&[&claimant.to_bytes(), &amount.to_le_bytes()]);
+        let leaf = hashv(&[&[0u8], &node.to_bytes()]);
+        require!(verify(proof, account.merkle_root, leaf.to_bytes()), StubError::InvalidProof);
+
+        account.total_funds_claimed = account
+            .total_funds_claimed
+            .checked_add(amount)
+            .ok_or(StubError::ExceedsMaxClaim)?;
+        require!(account.total_funds_claimed <= account.max_total_claim, StubError::ExceedsMaxClaim);
+
+        let claim_status = &mut ctx.accounts.claim_status;
+        claim_status.is_claimed = true;
+        claim_status.claimant = claimant;
+        claim_status.amount = amount;
+
+        **account.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.claimant.try_borrow_mut_lamports()? += amount;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeTipDistributionAccount<'info> {
+    #[account(init, payer = payer, space = TipDistributionAccount::LEN)]
+    pub tip_distribution_account: Account<'info, TipDistributionAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    /// CHECK: Unused by the stub; kept for account order compatibility
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub tip_distribution_account: Account<'info, TipDistributionAccount>,
+
+    #[account(
+        init,
+        seeds = [CLAIM_STATUS_SEED, claimant.key().as_ref(), tip_distribution_account.key().as_ref()],
+        bump,
+        payer = payer,
+        space = ClaimStatus::LEN,
+    )]
+    pub claim_status: Account<'info, ClaimStatus>,
+
+    /// CHECK: Any account may receive tips
+    #[account(mut)]
+    pub claimant: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct TipDistributionAccount {
+    /// Validator the tips were earned by; first field, as in the real program
+    pub validator_vote_account: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub max_total_claim: u64,
+    pub total_funds_claimed: u64,
+}
+
+impl TipDistributionAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8;
+}
+
+#[account]
+pub struct ClaimStatus {
+    pub is_claimed: bool,
+    pub claimant: Pubkey,
+    pub amount: u64,
+}
+
+impl ClaimStatus {
+    pub const LEN: usize = 8 + 1 + 32 + 8;
+}
+
+#[error_code]
+pub enum StubError {
+    #[msg("Invalid merkle proof")]
+    InvalidProof,
+    #[msg("Claim exceeds the maximum total claim")]
+    ExceedsMaxClaim,
+}
+
+/// Sorted-pair merkle proof verification, as used by the tip distribution program
+fn verify(proof: Vec<[u8; 32]>, root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof.into_iter() {
+        computed = if computed <= node {
+            hashv(&[&[1u8], &computed, &node]).to_bytes()
+        } else {
+            hashv(&[&[1u8], &node, &computed]).to_bytes()
+        };
+    }
+    computed == root
+}
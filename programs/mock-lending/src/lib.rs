@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+
+declare_id!("Lq2norNryPXxshQH1nj2jeGUhVHGEQHhRMrso1QxSsG");
+
+/// A single-asset lending reserve in the style of Solend and Kamino: deposits
+/// mint collateral tokens redeemable for a growing share of the reserve's
+/// liquidity. Interest is simulated with `accrue_interest`, which pays tokens
+/// into the reserve as if borrowers had repaid them.
+#[program]
+pub mod mock_lending {
+    use super::*;
+
+    pub fn initialize_reserve(ctx: Context<InitializeReserve>) -> Result<()> {
+        let reserve = &mut ctx.accounts.reserve;
+        reserve.mint = ctx.accounts.mint.key();
+        reserve.vault = ctx.accounts.vault.key();
+        reserve.collateral_mint = ctx.accounts.collateral_mint.key();
+        reserve.liquidity = 0;
+        reserve.collateral_supply = 0;
+        reserve.bump = ctx.bumps.reserve;
+        Ok(())
+    }
+
+    /// Deposits `amount` liquidity for collateral, rounded down
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let reserve = &ctx.accounts.reserve;
+        let collateral = if reserve.collateral_supply == 0 {
+            amount
+        } else {
+            (amount as u128 * reserve.collateral_supply as u128 / reserve.liquidity as u128) as u64
+        };
+        require!(collateral > 0, MockLendingError::ZeroAmount);
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let mint = reserve.mint;
+        let seeds = &[b"reserve".as_ref(), mint.as_ref(), &[reserve.bump]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                    to: ctx.accounts.collateral.to_account_info(),
+                    authority: ctx.accounts.reserve.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            collateral,
+        )?;
+
+        let reserve = &mut ctx.accounts.reserve;
+        reserve.liquidity += amount;
+        reserve.collateral_supply += collateral;
+        Ok(())
+    }
+
+    /// Redeems `collateral` for its share of liquidity, rounded down
+    pub fn withdraw(ctx: Context<Withdraw>, collateral: u64) -> Result<()> {
+        let reserve = &ctx.accounts.reserve;
+        require!(
+            collateral > 0 && collateral <= reserve.collateral_supply,
+            MockLendingError::ZeroAmount
+        );
+        let amount = (collateral as u128 * reserve.liquidity as u128 / reserve.collateral_supply as u128) as u64;
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.collateral_mint.to_account_info(),
+                    from: ctx.accounts.collateral.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            collateral,
+        )?;
+
+        let mint = reserve.mint;
+        let seeds = &[b"reserve".as_ref(), mint.as_ref(), &[reserve.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.reserve.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )?;
+
+        let reserve = &mut ctx.accounts.reserve;
+        reserve.liquidity -= amount;
+        reserve.collateral_supply -= collateral;
+        Ok(())
+    }
+
+    /// Pays `amount` into the reserve as interest, raising the collateral rate
+    pub fn accrue_interest(ctx: Context<AccrueInterest>, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        ctx.accounts.reserve.liquidity += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeReserve<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = Reserve::LEN,
+        seeds = [b"reserve", mint.key().as_ref()],
+        bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"vault", reserve.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = reserve,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"collateral", reserve.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = reserve,
+    )]
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, has_one = vault, has_one = collateral_mint)]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub collateral: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = vault, has_one = collateral_mint)]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub collateral: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueInterest<'info> {
+    #[account(mut, has_one = vault)]
+    pub reserve: Account<'info, Reserve>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct Reserve {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub collateral_mint: Pubkey,
+    /// Liquidity owed to depositors, interest included
+    pub liquidity: u64,
+    /// Collateral tokens outstanding
+    pub collateral_supply: u64,
+    pub bump: u8,
+}
+
+impl Reserve {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1;
+}
+
+#[error_code]
+pub enum MockLendingError {
+    #[msg("Amount rounds to zero")]
+    ZeroAmount,
+}
@@ -12,4 +12,36 @@ pub enum SwapError {
     InsufficientLiquidity,
     #[msg("Invalid calculation")]
     InvalidCalculation,
+    #[msg("Math overflow occurred")]
+    MathOverflow,
+    #[msg("Rebalance interval has not elapsed")]
+    TooEarlyToRebalance,
+    #[msg("Missing risk score for pool token")]
+    MissingRiskScore,
+    #[msg("Swap exceeds the maximum in/out ratio of pool balances")]
+    MaxRatioExceeded,
+    #[msg("Invalid stake pool account")]
+    InvalidStakePool,
+    #[msg("Pool reserves already match target weights")]
+    NothingToRebalance,
+    #[msg("Compound interval has not elapsed")]
+    TooEarlyToCompound,
+    #[msg("Pool is not configured for Jito MEV")]
+    JitoNotConfigured,
+    #[msg("Too many validators tracked")]
+    TooManyValidators,
+    #[msg("A flash loan is outstanding on this pool")]
+    FlashLoanActive,
+    #[msg("No flash loan is outstanding on this pool")]
+    NoFlashLoan,
+    #[msg("Flash loan must be repaid by a later instruction in the same transaction")]
+    MissingFlashRepay,
+    #[msg("Flash loans cannot be taken through CPI")]
+    FlashLoanCpiForbidden,
+    #[msg("Flash loan was not repaid with its fee")]
+    FlashLoanNotRepaid,
+    #[msg("Invalid swap route")]
+    InvalidRoute,
+    #[msg("Swap fee must be below 100%")]
+    InvalidFee,
 }
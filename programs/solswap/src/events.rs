@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+/// A swap against one pool, emitted once per leg of a route
+#[event]
+pub struct PoolSwapped {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Fee rate charged on the input (basis points)
+    pub fee_bps: u16,
+    /// Pool balance of each token before and after the swap, in pool order
+    pub reserves_before: Vec<u64>,
+    pub reserves_after: Vec<u64>,
+    /// Risk premium (0-100) of the input token; zero for stableswap pools
+    pub risk_premium: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub amp: u64,
+    pub fee_bps: u16,
+    pub admin_fee_bps: u16,
+}
+
+#[event]
+pub struct FlashLoanTaken {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashLoanRepaid {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    /// Amount transferred by `flash_repay`; zero if the loan was returned directly
+    pub repaid: u64,
+    pub fee: u64,
+    /// Pool balance of the token after the fee is credited
+    pub reserve_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WeightsRebalanced {
+    pub pool: Pubkey,
+    /// Weights the change starts from and moves to (basis points)
+    pub weights_before: Vec<u16>,
+    pub weights_after: Vec<u16>,
+    pub change_end: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReservesRebalanced {
+    pub pool: Pubkey,
+    pub mint_from: Pubkey,
+    pub mint_to: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// SOL value of the redeemed and received LST at stake pool rates
+    pub value_in: u64,
+    pub value_out: u64,
+    pub reserves_before: Vec<u64>,
+    pub reserves_after: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsCompounded {
+    pub pool: Pubkey,
+    pub virtual_price_before: u64,
+    pub virtual_price_after: u64,
+    /// Net staking rewards measured from exchange rates, negative if rates fell
+    pub staking_rewards: i64,
+    pub mev_rewards: u64,
+    pub total_reserves: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MevTipsClaimed {
+    pub pool: Pubkey,
+    pub validator: Pubkey,
+    pub claimed: u64,
+    pub protocol_fee: u64,
+    pub lp_share: u64,
+    pub timestamp: i64,
+}
+
+/// A pool's reserves tracker reached full size and can be compounded into
+#[event]
+pub struct ReservesTrackerInitialized {
+    pub pool: Pubkey,
+    pub reserves_tracker: Pubkey,
+}
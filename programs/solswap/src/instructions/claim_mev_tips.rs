@@ -0,0 +1,253 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    pubkey,
+    system_instruction,
+};
+use anchor_spl::token::{self, spl_token::native_mint, SyncNative, Token, TokenAccount};
+use crate::error::SwapError;
+use crate::events::MevTipsClaimed;
+use crate::state::*;
+
+/// Jito tip distribution program
+pub const JITO_TIP_DISTRIBUTION_PROGRAM_ID: Pubkey = pubkey!("4R3gSG8BpU4t19KYj8CfnbtRpnT8gtk4dvTHxVRwc2r7");
+/// Anchor discriminator of the tip distribution `claim` instruction
+const CLAIM_DISCRIMINATOR: [u8; 8] = [62, 198, 214, 193, 213, 159, 108, 210];
+/// Offset of `validator_vote_account` in a `TipDistributionAccount`
+const TIP_DISTRIBUTION_VOTE_ACCOUNT_OFFSET: usize = 8;
+
+/// Claims MEV tips owed to stake the pool controls from one validator's
+/// tip distribution account for one epoch.
+///
+/// Tips are paid in lamports to `mev_vault`, which must already hold the
+/// rent-exempt minimum. The LPs' share is then wrapped into the pool's wSOL
+/// vault so it is part of the pool's balance when it is compounded.
+#[derive(Accounts)]
+pub struct ClaimMevTips<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, RiskAdjustedPool>>,
+
+    /// CHECK: System-owned PDA that receives the pool's MEV tips
+    #[account(
+        mut,
+        seeds = [b"mev_vault", pool.key().as_ref()],
+        bump,
+    )]
+    pub mev_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = wsol_vault.owner == pool.authority @ SwapError::InvalidTokenAccount,
+        constraint = wsol_vault.mint == native_mint::ID @ SwapError::InvalidTokenAccount,
+    )]
+    pub wsol_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Matched against the pool's Jito settings in the handler
+    #[account(mut)]
+    pub fee_recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by the tip distribution program
+    pub tip_distribution_config: UncheckedAccount<'info>,
+
+    /// CHECK: Owner is checked in the handler; contents by the tip distribution program
+    #[account(mut, owner = JITO_TIP_DISTRIBUTION_PROGRAM_ID @ SwapError::InvalidTokenAccount)]
+    pub tip_distribution_account: UncheckedAccount<'info>,
+
+    /// CHECK: Initialized by the tip distribution program to prevent double claims
+    #[account(mut)]
+    pub claim_status: UncheckedAccount<'info>,
+
+    /// CHECK: Address constraint
+    #[account(address = JITO_TIP_DISTRIBUTION_PROGRAM_ID)]
+    pub tip_distribution_program: UncheckedAccount<'info>,
+
+    /// Pays rent for the claim status account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler(
+    ctx: Context<ClaimMevTips>,
+    claim_status_bump: u8,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    let jito_config = ctx
+        .accounts
+        .pool
+        .lst_config
+        .as_ref()
+        .map(|config| config.jito_config.clone())
+        .ok_or(SwapError::JitoNotConfigured)?;
+    require!(ctx.accounts.pool.jito_reserves.is_some(), SwapError::JitoNotConfigured);
+    let wsol_index = ctx
+        .accounts
+        .pool
+        .token_index(&native_mint::ID)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+    require_keys_eq!(
+        ctx.accounts.fee_recipient.key(),
+        jito_config.fee_recipient,
+        SwapError::InvalidTokenAccount
+    );
+
+    let validator = read_validator_vote_account(&ctx.accounts.tip_distribution_account)?;
+
+    // Claim into the vault and measure what actually arrived
+    let balance_before = ctx.accounts.mev_vault.lamports();
+    invoke(
+        &claim_instruction(
+            ctx.accounts.tip_distribution_config.key(),
+            ctx.accounts.tip_distribution_account.key(),
+            ctx.accounts.claim_status.key(),
+            ctx.accounts.mev_vault.key(),
+            ctx.accounts.payer.key(),
+            claim_status_bump,
+            amount,
+            proof,
+        )?,
+        &[
+            ctx.accounts.tip_distribution_config.to_account_info(),
+            ctx.accounts.tip_distribution_account.to_account_info(),
+            ctx.accounts.claim_status.to_account_info(),
+            ctx.accounts.mev_vault.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+    let claimed = ctx
+        .accounts
+        .mev_vault
+        .lamports()
+        .checked_sub(balance_before)
+        .ok_or(SwapError::MathOverflow)?;
+
+    let (protocol_fee, lp_share) = split_mev_claim(claimed, jito_config.mev_share_fee)?;
+
+    transfer_from_vault(&ctx, ctx.accounts.fee_recipient.to_account_info(), protocol_fee)?;
+
+    // LPs' share joins the pool's wSOL balance, where the next compound
+    // picks it up as new MEV rewards
+    if lp_share > 0 {
+        transfer_from_vault(&ctx, ctx.accounts.wsol_vault.to_account_info(), lp_share)?;
+        token::sync_native(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            SyncNative { account: ctx.accounts.wsol_vault.to_account_info() },
+        ))?;
+        let wsol = &mut ctx.accounts.pool.token_weights[wsol_index];
+        wsol.amount = wsol.amount.checked_add(lp_share).ok_or(SwapError::MathOverflow)?;
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    let jito_reserves = ctx
+        .accounts
+        .pool
+        .jito_reserves
+        .as_mut()
+        .ok_or(SwapError::JitoNotConfigured)?;
+    jito_reserves.mev_rewards_reserves = jito_reserves
+        .mev_rewards_reserves
+        .checked_add(lp_share)
+        .ok_or(SwapError::MathOverflow)?;
+    jito_reserves.last_mev_distribution = now;
+    require!(
+        jito_reserves.record_validator_mev(validator, claimed),
+        SwapError::TooManyValidators
+    );
+
+    emit!(MevTipsClaimed {
+        pool: ctx.accounts.pool.key(),
+        validator,
+        claimed,
+        protocol_fee,
+        lp_share,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Pays `amount` lamports out of the pool's MEV vault
+fn transfer_from_vault<'info>(
+    ctx: &Context<ClaimMevTips<'info>>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let pool_key = ctx.accounts.pool.key();
+    let vault_seeds = &[b"mev_vault".as_ref(), pool_key.as_ref(), &[ctx.bumps.mev_vault]];
+    invoke_signed(
+        &system_instruction::transfer(ctx.accounts.mev_vault.key, to.key, amount),
+        &[
+            ctx.accounts.mev_vault.to_account_info(),
+            to,
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&vault_seeds[..]],
+    )?;
+    Ok(())
+}
+
+/// Splits a claim into the protocol fee (rounded down) and the LP share
+pub fn split_mev_claim(claimed: u64, mev_share_fee_bps: u64) -> Result<(u64, u64)> {
+    require!(mev_share_fee_bps <= 10_000, SwapError::InvalidPoolParameters);
+    let protocol_fee = (claimed as u128 * mev_share_fee_bps as u128 / 10_000) as u64;
+    Ok((protocol_fee, claimed - protocol_fee))
+}
+
+/// Reads the vote account a tip distribution account pays out for
+fn read_validator_vote_account(tip_distribution_account: &AccountInfo) -> Result<Pubkey> {
+    let data = tip_distribution_account.try_borrow_data()?;
+    let bytes = data
+        .get(TIP_DISTRIBUTION_VOTE_ACCOUNT_OFFSET..TIP_DISTRIBUTION_VOTE_ACCOUNT_OFFSET + 32)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+    Ok(Pubkey::try_from(bytes).map_err(|_| SwapError::InvalidTokenAccount)?)
+}
+
+/// Builds the tip distribution program's `claim(bump, amount, proof)` instruction
+#[allow(clippy::too_many_arguments)]
+fn claim_instruction(
+    config: Pubkey,
+    tip_distribution_account: Pubkey,
+    claim_status: Pubkey,
+    claimant: Pubkey,
+    payer: Pubkey,
+    bump: u8,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<Instruction> {
+    let mut data = CLAIM_DISCRIMINATOR.to_vec();
+    (bump, amount, proof).serialize(&mut data)?;
+
+    Ok(Instruction {
+        program_id: JITO_TIP_DISTRIBUTION_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(tip_distribution_account, false),
+            AccountMeta::new(claim_status, false),
+            AccountMeta::new(claimant, false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+        ],
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_mev_claim() {
+        assert_eq!(split_mev_claim(1_000_000, 1_000).unwrap(), (100_000, 900_000));
+        assert_eq!(split_mev_claim(999, 1_000).unwrap(), (99, 900));
+        assert_eq!(split_mev_claim(1_000, 0).unwrap(), (0, 1_000));
+        assert!(split_mev_claim(1_000, 10_001).is_err());
+    }
+}
@@ -1,37 +1,62 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use crate::error::SwapError;
+use crate::events::RewardsCompounded;
+use crate::oracles::stake_pool_rate::load_stake_pool_rate;
+use crate::state::*;
 
+/// Precision of `RiskAdjustedPool::virtual_price` (1.0 SOL per share)
+pub const VIRTUAL_PRICE_PRECISION: u64 = 1_000_000_000;
+
+/// Compounds rewards measured since the last call.
+///
+/// Remaining accounts: the SPL stake pool of every pool token that has one
+/// configured, in `token_weights` order.
 #[derive(Accounts)]
 pub struct CompoundRewards<'info> {
     #[account(mut)]
     pub pool: Account<'info, RiskAdjustedPool>,
-    
-    #[account(mut)]
-    pub reserves_tracker: Account<'info, ReservesTracker>,
-    
+
     #[account(
         mut,
-        constraint = rewards_vault.owner == pool.key()
+        seeds = [ReservesTracker::SEED, pool.key().as_ref()],
+        bump,
+        constraint = reserves_tracker.to_account_info().data_len() == ReservesTracker::LEN
+            @ SwapError::InvalidPoolParameters,
     )]
-    pub rewards_vault: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    pub reserves_tracker: AccountLoader<'info, ReservesTracker>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct RewardsSnapshot {
-    pub timestamp: i64,
-    pub staking_rewards: u64,
-    pub mev_rewards: u64,
+/// Change in SOL value of the pool's holdings between two rate observations
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RateDelta {
+    /// Holdings valued at the previously recorded rates
+    pub value_before: u128,
+    /// Same holdings valued at the current rates
+    pub value_after: u128,
 }
 
-pub fn handler(ctx: Context<CompoundRewards>) -> Result<()> {
+impl RateDelta {
+    pub fn add(&mut self, value_before: u128, value_after: u128) {
+        self.value_before += value_before;
+        self.value_after += value_after;
+    }
+
+    /// Realized staking rewards; zero if rates fell
+    pub fn gain(&self) -> u64 {
+        self.value_after.saturating_sub(self.value_before) as u64
+    }
+
+    /// Net staking rewards, negative if rates fell
+    pub fn net(&self) -> Result<i64> {
+        let net = self.value_after as i128 - self.value_before as i128;
+        Ok(i64::try_from(net).map_err(|_| SwapError::MathOverflow)?)
+    }
+}
+
+pub(crate) fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CompoundRewards<'info>>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
-    let reserves = &mut ctx.accounts.reserves_tracker;
-    
+    let mut reserves = ctx.accounts.reserves_tracker.load_mut()?;
+
     // Verify minimum time between compounds
     let now = Clock::get()?.unix_timestamp;
     require!(
@@ -39,79 +64,179 @@ pub fn handler(ctx: Context<CompoundRewards>) -> Result<()> {
         SwapError::TooEarlyToCompound
     );
 
-    // Calculate new rewards since last compound
-    let new_staking_rewards = calculate_staking_rewards(
-        reserves.total_reserves,
-        reserves.last_update,
-        now,
-    )?;
-    
-    let new_mev_rewards = calculate_mev_rewards(
-        &pool.jito_reserves.as_ref().unwrap(),
-        reserves.last_update,
-        now,
-    )?;
+    // Re-value every holding at its current exchange rate. The delta covers
+    // the whole pool, so LST rate growth is diluted by the holdings that did
+    // not grow. Tokens without a stake pool count at face value, and an LST
+    // without an earlier observation counts at its current value on both sides,
+    // so the first compound after listing it just records its rate.
+    let mut stake_pools = ctx.remaining_accounts.iter();
+    let mut delta = RateDelta::default();
+    let mut token_deltas: Vec<(Pubkey, RateDelta)> = Vec::new();
+    let mut total_value: u128 = 0;
 
-    // Update reserves with compounded amounts
-    reserves.total_reserves = reserves
-        .total_reserves
-        .checked_add(new_staking_rewards)
-        .ok_or(SwapError::MathOverflow)?;
-        
+    for token in pool.token_weights.iter() {
+        if token.stake_pool == Pubkey::default() {
+            delta.add(token.amount as u128, token.amount as u128);
+            total_value += token.amount as u128;
+            continue;
+        }
+
+        let stake_pool = stake_pools.next().ok_or(SwapError::InvalidStakePool)?;
+        require_keys_eq!(stake_pool.key(), token.stake_pool, SwapError::InvalidStakePool);
+
+        let rate = load_stake_pool_rate(stake_pool, &token.mint)?;
+        let current_value = rate.lamports_for(token.amount)? as u128;
+        total_value += current_value;
+
+        match reserves.protocol_reserves_mut(&token.stake_pool) {
+            Some(snapshot) => {
+                let previous_value = snapshot.lamports_for(token.amount).unwrap_or(current_value);
+                let mut token_delta = RateDelta::default();
+                token_delta.add(previous_value, current_value);
+                delta.add(previous_value, current_value);
+                token_deltas.push((token.mint, token_delta));
+                snapshot.sol_reserves = rate.total_lamports;
+                snapshot.lst_supply = rate.pool_token_supply;
+            }
+            None => {
+                delta.add(current_value, current_value);
+                reserves.add_protocol_reserves(ProtocolReserves {
+                    protocol: token.stake_pool,
+                    sol_reserves: rate.total_lamports,
+                    lst_supply: rate.pool_token_supply,
+                    last_verified: 0,
+                })?;
+            }
+        }
+    }
+
+    // MEV is whatever tips were claimed into the pool since the last compound.
+    // Claims already added it to the wSOL balance valued above.
+    let new_mev_rewards = match pool.jito_reserves.as_ref() {
+        Some(jito) => jito.mev_rewards_reserves.saturating_sub(reserves.jito_mev_rewards),
+        None => 0,
+    };
+
+    let virtual_price_before = pool.virtual_price;
+    pool.virtual_price = calculate_virtual_price(pool.virtual_price, &delta, new_mev_rewards)?;
+
+    if delta.value_after < delta.value_before {
+        msg!(
+            "Exchange rates fell: {} lamports lost",
+            delta.value_before - delta.value_after
+        );
+    }
+
+    // Record per-LST history and refresh the statistics the risk model reads.
+    // Claimed MEV comes from stake the pool's LSTs control, so it is
+    // attributed to each LST in proportion to its value.
+    let lst_value: u128 = token_deltas.iter().map(|(_, d)| d.value_after).sum();
+    for (mint, token_delta) in token_deltas.iter() {
+        let mev_share = (new_mev_rewards as u128 * token_delta.value_after)
+            .checked_div(lst_value)
+            .unwrap_or(0) as u64;
+
+        let index = reserves.lst_yield_index(mint)?;
+        let lst_yield = &mut reserves.lst_yields[index];
+        lst_yield.history.record(
+            now,
+            token_delta.net()?,
+            mev_share,
+            token_delta.value_before as u64,
+        );
+        lst_yield.stats = YieldStats::from_history(&lst_yield.history, now);
+
+        if let Some(score) = pool.risk_scores.iter_mut().find(|s| s.mint == *mint) {
+            score.yield_stability_score = lst_yield.stats.yield_stability_score();
+        }
+    }
+
+    // Update reserves with measured amounts
+    reserves.total_reserves = u64::try_from(total_value).map_err(|_| SwapError::MathOverflow)?;
     reserves.jito_mev_rewards = reserves
         .jito_mev_rewards
         .checked_add(new_mev_rewards)
         .ok_or(SwapError::MathOverflow)?;
 
     // Store rewards snapshot
-    reserves.rewards_history.push(RewardsSnapshot {
-        timestamp: now,
-        staking_rewards: new_staking_rewards,
-        mev_rewards: new_mev_rewards,
-    });
+    reserves.rewards_history.record(
+        now,
+        delta.net()?,
+        new_mev_rewards,
+        delta.value_before as u64,
+    );
 
     reserves.last_update = now;
 
+    emit!(RewardsCompounded {
+        pool: pool.key(),
+        virtual_price_before,
+        virtual_price_after: pool.virtual_price,
+        staking_rewards: delta.net()?,
+        mev_rewards: new_mev_rewards,
+        total_reserves: reserves.total_reserves,
+        timestamp: now,
+    });
+
     Ok(())
 }
 
-fn calculate_staking_rewards(
-    total_reserves: u64,
-    last_update: i64,
-    current_time: i64,
-) -> Result<u64> {
-    // Annual staking rate ~6%
-    let annual_rate = 600;
-    let time_elapsed = (current_time - last_update) as u64;
-    
-    let rewards = (total_reserves
-        .checked_mul(annual_rate)
+/// Applies a measured rate delta and newly claimed MEV to the virtual price.
+///
+/// Claimed MEV is already part of the holdings the delta measured, so the
+/// price moves by value_after / (value_before - mev). It only grows by value
+/// that was observed on-chain, and falls if exchange rates fell.
+pub fn calculate_virtual_price(virtual_price: u64, delta: &RateDelta, mev_rewards: u64) -> Result<u64> {
+    let virtual_price = if virtual_price == 0 { VIRTUAL_PRICE_PRECISION } else { virtual_price };
+    if delta.value_before <= mev_rewards as u128 {
+        return Ok(virtual_price);
+    }
+
+    let value_before = delta.value_before - mev_rewards as u128;
+    let new_price = (virtual_price as u128)
+        .checked_mul(delta.value_after)
         .ok_or(SwapError::MathOverflow)?
-        .checked_mul(time_elapsed)
-        .ok_or(SwapError::MathOverflow)?)
-        .checked_div(365 * 24 * 60 * 60 * 10000)
-        .ok_or(SwapError::MathOverflow)?;
-        
-    Ok(rewards)
+        / value_before;
+
+    Ok(u64::try_from(new_price).map_err(|_| SwapError::MathOverflow)?)
 }
 
-fn calculate_mev_rewards(
-    jito_reserves: &JitoReserves,
-    last_update: i64, 
-    current_time: i64,
-) -> Result<u64> {
-    // Calculate MEV rewards based on validator performance
-    let mev_rate = jito_reserves.validator_performance.iter()
-        .map(|v| v.mev_earned)
-        .sum::<u64>();
-        
-    let time_elapsed = (current_time - last_update) as u64;
-    
-    let rewards = (mev_rate
-        .checked_mul(time_elapsed)
-        .ok_or(SwapError::MathOverflow)?)
-        .checked_div(24 * 60 * 60)
-        .ok_or(SwapError::MathOverflow)?;
-        
-    Ok(rewards)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_price_follows_measured_rates() {
+        let mut delta = RateDelta::default();
+        delta.add(1_000_000, 1_001_000);
+        assert_eq!(calculate_virtual_price(0, &delta, 0).unwrap(), 1_001_000_000);
+        assert_eq!(delta.gain(), 1_000);
+
+        // Claimed MEV sits in the SOL balance on both sides of the delta
+        let mut with_mev = delta;
+        with_mev.add(1_000, 1_000);
+        assert_eq!(calculate_virtual_price(0, &with_mev, 1_000).unwrap(), 1_002_000_000);
+
+        // A falling rate is passed through rather than ignored
+        let mut loss = RateDelta::default();
+        loss.add(1_000_000, 990_000);
+        assert_eq!(calculate_virtual_price(1_001_000_000, &loss, 0).unwrap(), 990_990_000);
+        assert_eq!(loss.gain(), 0);
+    }
+
+    #[test]
+    fn test_rate_growth_is_diluted_by_other_holdings() {
+        // Half the pool is an LST whose rate grew 0.1%, half is SOL
+        let mut delta = RateDelta::default();
+        delta.add(1_000_000, 1_001_000);
+        delta.add(1_000_000, 1_000_000);
+        assert_eq!(calculate_virtual_price(0, &delta, 0).unwrap(), 1_000_500_000);
+        assert_eq!(delta.gain(), 1_000);
+    }
+
+    #[test]
+    fn test_no_observation_leaves_price_unchanged() {
+        let delta = RateDelta::default();
+        assert_eq!(calculate_virtual_price(1_050_000_000, &delta, 5_000).unwrap(), 1_050_000_000);
+    }
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::SwapError;
+use crate::events::FlashLoanTaken;
+use crate::state::*;
+
+/// Lends `amount` of one pool token for the rest of the transaction.
+///
+/// A `flash_repay` for the same pool must appear later in the transaction;
+/// it returns the loan plus a fee of `fee_bps`, which stays in the vault for
+/// LPs. Swaps and liquidity changes on the pool are rejected until then.
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// CHECK: PDA that owns the pool vaults
+    #[account(seeds = [b"authority", pool.key().as_ref()], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = vault.owner == pool_authority.key() @ SwapError::InvalidTokenAccount,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = destination.mint == vault.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub borrower: Signer<'info>,
+
+    /// CHECK: Address constraint
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+    require!(amount > 0, SwapError::InvalidPoolParameters);
+    require!(amount <= ctx.accounts.vault.amount, SwapError::InsufficientLiquidity);
+
+    let pool = &ctx.accounts.pool;
+    pool.ensure_unlocked()?;
+    let token_index = pool
+        .token_index(&ctx.accounts.vault.mint)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+
+    // Through CPI the caller could act on the borrowed funds after its own
+    // repayment check, so only top-level loans are allowed
+    require!(
+        get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+        SwapError::FlashLoanCpiForbidden
+    );
+    require_repay_follows(&ctx.accounts.instructions, &pool.key())?;
+
+    let (fee, required_balance) = flash_loan_terms(ctx.accounts.vault.amount, amount, pool.fee_bps)?;
+
+    let pool_key = pool.key();
+    let seeds = &[b"authority".as_ref(), pool_key.as_ref(), &[ctx.bumps.pool_authority]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.pool.active_flash_loan = Some(ActiveFlashLoan {
+        vault: ctx.accounts.vault.key(),
+        token_index: token_index as u8,
+        required_balance,
+        fee,
+    });
+
+    emit!(FlashLoanTaken {
+        pool: pool_key,
+        mint: ctx.accounts.vault.mint,
+        borrower: ctx.accounts.borrower.key(),
+        amount,
+        fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Fee on a loan of `amount` at `fee_bps`, rounded up so small loans are not
+/// free, and the vault balance that repays the loan with its fee
+pub fn flash_loan_terms(vault_balance: u64, amount: u64, fee_bps: u16) -> Result<(u64, u64)> {
+    let fee = u64::try_from((amount as u128 * fee_bps as u128).div_ceil(10_000))
+        .map_err(|_| SwapError::MathOverflow)?;
+    let required_balance = vault_balance.checked_add(fee).ok_or(SwapError::MathOverflow)?;
+    Ok((fee, required_balance))
+}
+
+/// Checks that a later top-level instruction repays a flash loan on `pool`
+fn require_repay_follows(instructions: &AccountInfo, pool: &Pubkey) -> Result<()> {
+    let current = load_current_index_checked(instructions)? as usize;
+    let mut index = current + 1;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == crate::ID
+            && instruction.data.get(..8) == Some(&crate::instruction::FlashRepay::DISCRIMINATOR[..])
+            && instruction.accounts.first().map(|meta| &meta.pubkey) == Some(pool)
+        {
+            return Ok(());
+        }
+        index += 1;
+    }
+    err!(SwapError::MissingFlashRepay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flash_loan_fee_rounds_up() {
+        assert_eq!(flash_loan_terms(5_000_000, 1_000_000, 4).unwrap(), (400, 5_000_400));
+        assert_eq!(flash_loan_terms(5_000_000, 1_000_001, 4).unwrap(), (401, 5_000_401));
+        // Any nonzero fee rate charges at least one unit
+        assert_eq!(flash_loan_terms(100, 1, 4).unwrap(), (1, 101));
+        assert_eq!(flash_loan_terms(100, 1, 0).unwrap(), (0, 100));
+    }
+
+    #[test]
+    fn test_flash_loan_terms_overflow() {
+        // The required balance is the whole vault plus the fee, not the loan
+        assert_eq!(flash_loan_terms(u64::MAX - 400, 1_000_000, 4).unwrap(), (400, u64::MAX));
+        assert!(flash_loan_terms(u64::MAX, 1_000_000, 4).is_err());
+    }
+}
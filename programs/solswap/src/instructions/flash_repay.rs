@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::SwapError;
+use crate::events::FlashLoanRepaid;
+use crate::state::*;
+
+/// Repays the pool's outstanding flash loan, fee included, and unlocks the pool
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, Pool>>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = source.mint == vault.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub source: Account<'info, TokenAccount>,
+
+    pub payer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler(ctx: Context<FlashRepay>) -> Result<()> {
+    let loan = ctx
+        .accounts
+        .pool
+        .active_flash_loan
+        .clone()
+        .ok_or(SwapError::NoFlashLoan)?;
+    require_keys_eq!(ctx.accounts.vault.key(), loan.vault, SwapError::InvalidTokenAccount);
+
+    let owed = loan.required_balance.saturating_sub(ctx.accounts.vault.amount);
+    if owed > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.source.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            owed,
+        )?;
+        ctx.accounts.vault.reload()?;
+    }
+    require!(
+        ctx.accounts.vault.amount >= loan.required_balance,
+        SwapError::FlashLoanNotRepaid
+    );
+
+    // The fee is added to the pool balance, raising the value of every LP token
+    let pool = &mut ctx.accounts.pool;
+    let balance = &mut pool.balances[loan.token_index as usize];
+    *balance = balance.checked_add(loan.fee).ok_or(SwapError::MathOverflow)?;
+    let reserve_after = *balance;
+    pool.active_flash_loan = None;
+
+    emit!(FlashLoanRepaid {
+        pool: pool.key(),
+        mint: ctx.accounts.vault.mint,
+        repaid: owed,
+        fee: loan.fee,
+        reserve_after,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
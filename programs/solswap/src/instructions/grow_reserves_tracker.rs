@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use crate::error::SwapError;
+use crate::events::ReservesTrackerInitialized;
+use crate::state::*;
+
+/// Extends a reserves tracker by up to 10 KiB, the most one instruction may
+/// reallocate. Called until the tracker reaches `ReservesTracker::LEN`.
+#[derive(Accounts)]
+pub struct GrowReservesTracker<'info> {
+    pub pool: Account<'info, RiskAdjustedPool>,
+
+    #[account(
+        mut,
+        seeds = [ReservesTracker::SEED, pool.key().as_ref()],
+        bump,
+        realloc = (reserves_tracker.to_account_info().data_len() + MAX_PERMITTED_DATA_INCREASE)
+            .min(ReservesTracker::LEN),
+        realloc::payer = payer,
+        realloc::zero = false,
+    )]
+    pub reserves_tracker: AccountLoader<'info, ReservesTracker>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn handler(ctx: Context<GrowReservesTracker>) -> Result<()> {
+    let tracker = &ctx.accounts.reserves_tracker;
+    if tracker.to_account_info().data_len() < ReservesTracker::LEN {
+        return Ok(());
+    }
+
+    let mut reserves = tracker.load_mut()?;
+    require!(reserves.pool == Pubkey::default(), SwapError::InvalidPoolParameters);
+    reserves.pool = ctx.accounts.pool.key();
+
+    emit!(ReservesTrackerInitialized {
+        pool: reserves.pool,
+        reserves_tracker: tracker.key(),
+    });
+
+    Ok(())
+}
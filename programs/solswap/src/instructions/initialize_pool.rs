@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::events::PoolInitialized;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -6,7 +7,7 @@ pub struct InitializePool<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    #[account(init, payer = authority, space = 8 + 32 + 1 + 32 * 10 + 8 + 2 + 2 + 8)]
+    #[account(init, payer = authority, space = Pool::LEN)]
     pub pool: Account<'info, Pool>,
     
     pub system_program: Program<'info, System>,
@@ -25,6 +26,14 @@ pub fn handler(
     pool.fee_bps = fee_bps;
     pool.admin_fee_bps = admin_fee_bps;
     pool.lp_supply = 0;
-    
+
+    emit!(PoolInitialized {
+        pool: pool.key(),
+        authority: pool.authority,
+        amp,
+        fee_bps,
+        admin_fee_bps,
+    });
+
     Ok(())
 }
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use crate::state::*;
+
+/// Creates a pool's reserves tracker with as much space as one instruction
+/// may allocate; `grow_reserves_tracker` extends it to full size.
+#[derive(Accounts)]
+pub struct InitializeReservesTracker<'info> {
+    pub pool: Account<'info, RiskAdjustedPool>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = ReservesTracker::LEN.min(MAX_PERMITTED_DATA_INCREASE),
+        seeds = [ReservesTracker::SEED, pool.key().as_ref()],
+        bump,
+    )]
+    pub reserves_tracker: AccountLoader<'info, ReservesTracker>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub(crate) fn handler(_ctx: Context<InitializeReservesTracker>) -> Result<()> {
+    Ok(())
+}
@@ -1,9 +1,25 @@
 pub mod initialize_pool;
 pub mod rebalance_pool;
+pub mod rebalance_reserves;
 pub mod compound_rewards;
-pub mod update_validator_metrics;
+pub mod initialize_reserves_tracker;
+pub mod grow_reserves_tracker;
+pub mod claim_mev_tips;
+pub mod weighted_swap;
+pub mod flash_loan;
+pub mod flash_repay;
+pub mod swap_route;
+pub mod swap_exact_out;
 
-pub use initialize_pool::*;
+pub use initialize_pool::InitializePool;
 pub use rebalance_pool::*;
+pub use rebalance_reserves::*;
 pub use compound_rewards::*;
-pub use update_validator_metrics::*;
+pub use initialize_reserves_tracker::*;
+pub use grow_reserves_tracker::*;
+pub use claim_mev_tips::*;
+pub use weighted_swap::*;
+pub use flash_loan::*;
+pub use flash_repay::*;
+pub use swap_route::*;
+pub use swap_exact_out::*;
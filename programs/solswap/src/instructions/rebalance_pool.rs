@@ -1,78 +1,386 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token::Token;
+use crate::error::SwapError;
+use crate::events::WeightsRebalanced;
 use crate::state::*;
 
+/// Total weight of the pool in basis points
+pub const TOTAL_WEIGHT_BPS: u64 = 10_000;
+/// Share of the blended target taken from risk scores; the rest follows governance targets
+pub const RISK_TARGET_BLEND_BPS: u64 = 5_000;
+/// Scale of `PoolConfig::max_weight_change_per_second` (millionths of a bp)
+pub const WEIGHT_RATE_SCALE: u64 = 1_000_000;
+
 #[derive(Accounts)]
 pub struct RebalancePool<'info> {
     #[account(mut)]
     pub pool: Account<'info, RiskAdjustedPool>,
-    
+
     #[account(mut)]
     pub authority: Signer<'info>,
-    
+
     pub token_program: Program<'info, Token>,
 }
 
-pub fn handler(ctx: Context<RebalancePool>) -> Result<()> {
+pub(crate) fn handler(ctx: Context<RebalancePool>) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     let clock = Clock::get()?;
-    
+
     // Check if enough time has passed since last rebalance
     require!(
         clock.unix_timestamp >= pool.last_rebalance + pool.config.rebalance_interval,
-        ErrorCode::TooEarlyToRebalance
+        SwapError::TooEarlyToRebalance
     );
-    
+
+    // Start the new weight change from wherever the previous one has reached
+    let now = clock.unix_timestamp;
+    pool.checkpoint_weights(now);
+
     // Calculate new weights based on risk scores
     let new_weights = calculate_optimal_weights(
         &pool.token_weights,
         &pool.risk_scores,
         &pool.target_weights,
-        pool.config.max_weight
+        pool.config.max_weight,
+        pool.config.max_weight_deviation,
     )?;
-    
-    // Update pool weights
-    for (i, weight) in new_weights.iter().enumerate() {
-        pool.token_weights[i].weight = *weight;
+
+    let duration = weight_change_duration(
+        &pool.token_weights,
+        &new_weights,
+        pool.config.weight_change_window,
+        pool.config.max_weight_change_per_second,
+    )?;
+
+    // Schedule a linear move from the current weights to the new ones
+    let weights_before: Vec<u16> = pool.token_weights.iter().map(|token| token.weight).collect();
+    for (token, weight) in pool.token_weights.iter_mut().zip(new_weights.iter()) {
+        token.weight = *weight;
     }
-    
+    pool.weight_change_start = now;
+    pool.weight_change_end = now
+        .checked_add(duration)
+        .ok_or(SwapError::MathOverflow)?;
+
     pool.last_rebalance = clock.unix_timestamp;
 
     // Implement validator redistribution for mSOL
-    
+
+    emit!(WeightsRebalanced {
+        pool: pool.key(),
+        weights_before,
+        weights_after: new_weights,
+        change_end: pool.weight_change_end,
+        timestamp: now,
+    });
+
     Ok(())
 }
 
-fn calculate_optimal_weights(
+/// Computes the next set of token weights, in the order of `current_weights`.
+///
+/// Risk-derived targets are blended with governance targets, normalized to
+/// 10_000 bps, capped at `max_weight` (percent) and then approached from the
+/// current weights by at most `max_weight_deviation` (percent) per token.
+pub fn calculate_optimal_weights(
     current_weights: &[TokenWeight],
     risk_scores: &[RiskScore],
     target_weights: &[TargetWeight],
     max_weight: u8,
+    max_weight_deviation: u8,
 ) -> Result<Vec<u16>> {
-    let risk_adjusted_weights: Vec<u16> = risk_scores
+    let token_count = current_weights.len() as u64;
+    let max_weight_bps = (max_weight as u64 * 100).min(TOTAL_WEIGHT_BPS);
+    let max_step_bps = max_weight_deviation as u64 * 100;
+
+    // The cap must leave room for the weights to sum to 100%
+    require!(
+        token_count > 0 && max_weight_bps * token_count >= TOTAL_WEIGHT_BPS,
+        SwapError::InvalidPoolParameters
+    );
+
+    let scores = current_weights
         .iter()
-        .map(|score| {
-            let base_score = (
-                score.validator_score as u16 +
-                score.security_score as u16 +
-                score.decentralization_score as u16 +
-                score.yield_stability_score as u16
-            ) / 4;
-
-            let adjusted_score = if let Some(mev_score) = score.mev_reliability_score {
-                // Adjust for JitoSOL
-                (base_score + mev_score as u16) / 2
-            } else if let Some(diversity_score) = score.validator_diversity_score {
-                // Adjust for mSOL
-                (base_score + diversity_score as u16) / 2
-            } else {
-                base_score
-            };
-
-            // Ensure weight does not exceed max
-            adjusted_score.min(max_weight as u16 * 100)
+        .map(|token| {
+            risk_scores
+                .iter()
+                .find(|score| score.mint == token.mint)
+                .map(RiskScore::composite)
+                .ok_or_else(|| error!(SwapError::MissingRiskScore))
+        })
+        .collect::<Result<Vec<u64>>>()?;
+    let risk_targets = normalize_weights(&scores);
+
+    let governance_raw: Vec<u64> = current_weights
+        .iter()
+        .map(|token| {
+            target_weights
+                .iter()
+                .find(|target| target.mint == token.mint)
+                .map_or(0, |target| target.target as u64)
         })
         .collect();
+    let governance_targets = if governance_raw.iter().all(|w| *w == 0) {
+        risk_targets.clone()
+    } else {
+        normalize_weights(&governance_raw)
+    };
+
+    let blended: Vec<u64> = risk_targets
+        .iter()
+        .zip(governance_targets.iter())
+        .map(|(risk, governance)| {
+            (risk * RISK_TARGET_BLEND_BPS + governance * (TOTAL_WEIGHT_BPS - RISK_TARGET_BLEND_BPS))
+                / TOTAL_WEIGHT_BPS
+        })
+        .collect();
+    let desired = cap_weights(&normalize_weights(&blended), max_weight_bps);
+
+    let current_raw: Vec<u64> = current_weights.iter().map(|w| w.weight as u64).collect();
+    let current = normalize_weights(&current_raw);
+
+    let next = step_towards(&current, &desired, max_step_bps);
+
+    Ok(next.into_iter().map(|w| w as u16).collect())
+}
+
+/// Length of the weight change from `current_weights` to `new_weights`: at
+/// least `window` seconds, stretched so no weight moves faster than
+/// `max_change_per_second` (millionths of a bp per second)
+pub fn weight_change_duration(
+    current_weights: &[TokenWeight],
+    new_weights: &[u16],
+    window: i64,
+    max_change_per_second: u64,
+) -> Result<i64> {
+    require!(max_change_per_second > 0, SwapError::InvalidPoolParameters);
+
+    let max_delta = current_weights
+        .iter()
+        .zip(new_weights.iter())
+        .map(|(current, new)| current.weight.abs_diff(*new) as u64)
+        .max()
+        .unwrap_or(0);
+
+    let rate_limited = (max_delta * WEIGHT_RATE_SCALE).div_ceil(max_change_per_second);
+    let rate_limited = i64::try_from(rate_limited).map_err(|_| SwapError::MathOverflow)?;
+
+    Ok(rate_limited.max(window))
+}
+
+/// Scales values proportionally so they sum to exactly 10_000 bps.
+///
+/// Rounding dust goes to the largest remainders; all-zero input is split evenly.
+fn normalize_weights(values: &[u64]) -> Vec<u64> {
+    let total: u128 = values.iter().map(|v| *v as u128).sum();
+    if total == 0 {
+        let even = vec![1u64; values.len()];
+        return normalize_weights(&even);
+    }
+
+    let mut weights = Vec::with_capacity(values.len());
+    let mut remainders = Vec::with_capacity(values.len());
+    for (i, value) in values.iter().enumerate() {
+        let scaled = *value as u128 * TOTAL_WEIGHT_BPS as u128;
+        weights.push((scaled / total) as u64);
+        remainders.push((scaled % total, i));
+    }
+
+    let assigned: u64 = weights.iter().sum();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders.iter().take((TOTAL_WEIGHT_BPS - assigned) as usize) {
+        weights[*i] += 1;
+    }
+
+    weights
+}
 
-    Ok(risk_adjusted_weights)
-}
\ No newline at end of file
+/// Caps every weight at `cap` and redistributes the excess pro rata to the
+/// uncapped weights until no weight exceeds the cap. The sum is preserved.
+fn cap_weights(weights: &[u64], cap: u64) -> Vec<u64> {
+    let mut weights = weights.to_vec();
+
+    loop {
+        let excess: u64 = weights.iter().map(|w| w.saturating_sub(cap)).sum();
+        if excess == 0 {
+            return weights;
+        }
+
+        for w in weights.iter_mut() {
+            *w = (*w).min(cap);
+        }
+
+        let open: Vec<usize> = (0..weights.len()).filter(|i| weights[*i] < cap).collect();
+        let open_total: u64 = open.iter().map(|i| weights[*i]).sum();
+
+        // Share the excess by current weight, or evenly if the open weights are all zero
+        let shares: Vec<u64> = open
+            .iter()
+            .map(|i| if open_total == 0 { 1 } else { weights[*i] })
+            .collect();
+        let share_total: u128 = shares.iter().map(|s| *s as u128).sum();
+
+        let mut distributed = 0u64;
+        let mut remainders = Vec::with_capacity(open.len());
+        for (share, i) in shares.iter().zip(open.iter()) {
+            let scaled = excess as u128 * *share as u128;
+            let portion = (scaled / share_total) as u64;
+            weights[*i] += portion;
+            distributed += portion;
+            remainders.push((scaled % share_total, *i));
+        }
+
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (_, i) in remainders.iter().take((excess - distributed) as usize) {
+            weights[*i] += 1;
+        }
+    }
+}
+
+/// Moves `current` towards `desired` so that no weight changes by more than
+/// `max_step`. Both inputs must sum to 10_000 bps and so does the result.
+fn step_towards(current: &[u64], desired: &[u64], max_step: u64) -> Vec<u64> {
+    let max_move = current
+        .iter()
+        .zip(desired.iter())
+        .map(|(c, d)| c.abs_diff(*d))
+        .max()
+        .unwrap_or(0);
+
+    if max_move <= max_step {
+        return desired.to_vec();
+    }
+
+    // Scale every move by max_step / max_move, truncating towards the current weight
+    let mut next = Vec::with_capacity(current.len());
+    let mut remainders = Vec::with_capacity(current.len());
+    for (i, (c, d)) in current.iter().zip(desired.iter()).enumerate() {
+        let scaled = (*d as i128 - *c as i128) * max_step as i128;
+        next.push((*c as i128 + scaled / max_move as i128) as u64);
+        remainders.push((scaled % max_move as i128, i));
+    }
+
+    // Truncation leaves a few bps unassigned (or over-assigned); settle them on the
+    // weights whose exact move was cut shortest, which keeps every move within bounds
+    let assigned: i128 = next.iter().map(|w| *w as i128).sum();
+    let residual = TOTAL_WEIGHT_BPS as i128 - assigned;
+    if residual > 0 {
+        remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (_, i) in remainders.iter().take(residual as usize) {
+            next[*i] += 1;
+        }
+    } else if residual < 0 {
+        remainders.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+        for (_, i) in remainders.iter().take((-residual) as usize) {
+            next[*i] -= 1;
+        }
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(seed: u8, weight: u16) -> TokenWeight {
+        TokenWeight {
+            mint: Pubkey::new_from_array([seed; 32]),
+            weight,
+            start_weight: weight,
+            amount: 0,
+            stake_pool: Pubkey::default(),
+        }
+    }
+
+    fn score(seed: u8, value: u8) -> RiskScore {
+        RiskScore {
+            mint: Pubkey::new_from_array([seed; 32]),
+            validator_score: value,
+            security_score: value,
+            decentralization_score: value,
+            yield_stability_score: value,
+            mev_reliability_score: None,
+            validator_diversity_score: None,
+        }
+    }
+
+    fn target(seed: u8, weight: u16) -> TargetWeight {
+        TargetWeight {
+            mint: Pubkey::new_from_array([seed; 32]),
+            target: weight,
+        }
+    }
+
+    #[test]
+    fn test_weights_sum_to_total() {
+        // Deterministic pseudo-random configurations
+        let mut state = 0x2545_f491u64;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            state >> 33
+        };
+
+        for _ in 0..500 {
+            let count = 1 + (next() % 8) as u8;
+            let tokens: Vec<TokenWeight> = (0..count).map(|i| token(i, (next() % 10_000) as u16)).collect();
+            let scores: Vec<RiskScore> = (0..count).map(|i| score(i, (next() % 101) as u8)).collect();
+            let targets: Vec<TargetWeight> = (0..count).map(|i| target(i, (next() % 10_000) as u16)).collect();
+            let max_weight = (100_u64.div_ceil(count as u64) + next() % 100).min(100) as u8;
+            let deviation = (next() % 100) as u8;
+
+            let weights = calculate_optimal_weights(&tokens, &scores, &targets, max_weight, deviation).unwrap();
+            let total: u64 = weights.iter().map(|w| *w as u64).sum();
+            assert_eq!(total, TOTAL_WEIGHT_BPS);
+        }
+    }
+
+    #[test]
+    fn test_max_weight_enforced() {
+        let tokens = vec![token(0, 3_334), token(1, 3_333), token(2, 3_333)];
+        let scores = vec![score(0, 100), score(1, 10), score(2, 10)];
+        let targets = vec![target(0, 8_000), target(1, 1_000), target(2, 1_000)];
+
+        let weights = calculate_optimal_weights(&tokens, &scores, &targets, 40, 100).unwrap();
+        assert_eq!(weights, vec![4_000, 3_000, 3_000]);
+    }
+
+    #[test]
+    fn test_step_bounded_by_max_deviation() {
+        let tokens = vec![token(0, 5_000), token(1, 5_000)];
+        let scores = vec![score(0, 100), score(1, 0)];
+        let targets = vec![target(0, 10_000), target(1, 0)];
+
+        let weights = calculate_optimal_weights(&tokens, &scores, &targets, 100, 5).unwrap();
+        assert_eq!(weights, vec![5_500, 4_500]);
+
+        // Repeated rebalances converge on the target
+        let mut tokens = tokens;
+        for _ in 0..20 {
+            let weights = calculate_optimal_weights(&tokens, &scores, &targets, 100, 5).unwrap();
+            for (token, weight) in tokens.iter_mut().zip(weights.iter()) {
+                assert!(token.weight.abs_diff(*weight) <= 500);
+                token.weight = *weight;
+            }
+        }
+        assert_eq!(tokens[0].weight, 10_000);
+    }
+
+    #[test]
+    fn test_weight_change_duration_respects_rate_limit() {
+        let tokens = vec![token(0, 5_000), token(1, 5_000)];
+
+        // 1_000 bps at 0.01 bps/s needs 100_000s, longer than the 1 day window
+        assert_eq!(weight_change_duration(&tokens, &[6_000, 4_000], 86_400, 10_000).unwrap(), 100_000);
+        // Small moves still take the full window
+        assert_eq!(weight_change_duration(&tokens, &[5_010, 4_990], 86_400, 10_000).unwrap(), 86_400);
+    }
+
+    #[test]
+    fn test_infeasible_max_weight_rejected() {
+        let tokens = vec![token(0, 5_000), token(1, 5_000)];
+        let scores = vec![score(0, 50), score(1, 50)];
+
+        assert!(calculate_optimal_weights(&tokens, &scores, &[], 40, 10).is_err());
+    }
+}
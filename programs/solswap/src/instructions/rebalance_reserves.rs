@@ -0,0 +1,271 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, stake, sysvar};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::error::SwapError;
+use crate::events::ReservesRebalanced;
+use crate::instructions::weighted_swap;
+use crate::oracles::stake_pool_rate::load_stake_pool_rate;
+use crate::state::*;
+
+/// Moves real reserves between two stake-pool LSTs so the pool's composition
+/// follows its weights: `token_from` is redeemed for SOL through its stake
+/// pool's `withdraw_sol` and the SOL is deposited into the other pool.
+///
+/// `pool_authority` must hold at least the rent-exempt minimum, since it
+/// briefly custodies the withdrawn SOL.
+#[derive(Accounts)]
+pub struct RebalanceReserves<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, RiskAdjustedPool>>,
+
+    /// CHECK: PDA that owns the pool vaults and custodies SOL between legs
+    #[account(
+        mut,
+        seeds = [b"authority", pool.key().as_ref()],
+        bump = pool.bump,
+        address = pool.authority,
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_from.owner == pool.authority @ SwapError::InvalidTokenAccount,
+    )]
+    pub vault_from: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = vault_to.owner == pool.authority @ SwapError::InvalidTokenAccount,
+        constraint = vault_to.mint != vault_from.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub vault_to: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Validated against the pool's configured stake pool in the handler
+    #[account(mut)]
+    pub from_stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Derived and checked by the stake pool program
+    pub from_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Checked by the stake pool program
+    #[account(mut)]
+    pub from_reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: Checked by the stake pool program
+    #[account(mut)]
+    pub from_manager_fee_account: UncheckedAccount<'info>,
+    #[account(mut, address = vault_from.mint)]
+    pub from_pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Validated against the pool's configured stake pool in the handler
+    #[account(mut)]
+    pub to_stake_pool: UncheckedAccount<'info>,
+    /// CHECK: Derived and checked by the stake pool program
+    pub to_withdraw_authority: UncheckedAccount<'info>,
+    /// CHECK: Checked by the stake pool program
+    #[account(mut)]
+    pub to_reserve_stake: UncheckedAccount<'info>,
+    /// CHECK: Checked by the stake pool program
+    #[account(mut)]
+    pub to_manager_fee_account: UncheckedAccount<'info>,
+    #[account(mut, address = vault_to.mint)]
+    pub to_pool_mint: Box<Account<'info, Mint>>,
+
+    /// CHECK: Address constraint
+    #[account(address = spl_stake_pool::id())]
+    pub stake_pool_program: UncheckedAccount<'info>,
+    /// CHECK: Address constraint
+    #[account(address = sysvar::clock::id())]
+    pub clock: UncheckedAccount<'info>,
+    /// CHECK: Address constraint
+    #[account(address = sysvar::stake_history::id())]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: Address constraint
+    #[account(address = stake::program::id())]
+    pub stake_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler(ctx: Context<RebalanceReserves>, max_amount_in: u64) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+    let index_from = pool
+        .token_index(&ctx.accounts.vault_from.mint)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+    let index_to = pool
+        .token_index(&ctx.accounts.vault_to.mint)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+
+    require_keys_eq!(
+        ctx.accounts.from_stake_pool.key(),
+        pool.token_weights[index_from].stake_pool,
+        SwapError::InvalidStakePool
+    );
+    require_keys_eq!(
+        ctx.accounts.to_stake_pool.key(),
+        pool.token_weights[index_to].stake_pool,
+        SwapError::InvalidStakePool
+    );
+
+    let rate_from = load_stake_pool_rate(&ctx.accounts.from_stake_pool, &ctx.accounts.vault_from.mint)?;
+    let rate_to = load_stake_pool_rate(&ctx.accounts.to_stake_pool, &ctx.accounts.vault_to.mint)?;
+
+    // Value both reserves in SOL at their stake pool rates and size the trade
+    // that brings their value ratio in line with their current weights
+    let now = Clock::get()?.unix_timestamp;
+    let value_from = rate_from.lamports_for(pool.token_weights[index_from].amount)?;
+    let value_to = rate_to.lamports_for(pool.token_weights[index_to].amount)?;
+    let lamports_to_move = pairwise_rebalance_amount(
+        value_from,
+        pool.effective_weight(index_from, now),
+        value_to,
+        pool.effective_weight(index_to, now),
+    );
+
+    let amount_in = rate_from.tokens_for(lamports_to_move)?.min(max_amount_in);
+    require!(amount_in > 0, SwapError::NothingToRebalance);
+    let value_in = rate_from.lamports_for(amount_in)?;
+
+    let pool_key = pool.key();
+    let authority_seeds = &[b"authority".as_ref(), pool_key.as_ref(), &[pool.bump]];
+    let signer = &[&authority_seeds[..]];
+
+    // Leg 1: redeem the overweight LST for SOL held by the pool authority
+    let lamports_before = ctx.accounts.pool_authority.lamports();
+    invoke_signed(
+        &spl_stake_pool::instruction::withdraw_sol(
+            &spl_stake_pool::id(),
+            ctx.accounts.from_stake_pool.key,
+            ctx.accounts.from_withdraw_authority.key,
+            ctx.accounts.pool_authority.key,
+            &ctx.accounts.vault_from.key(),
+            ctx.accounts.from_reserve_stake.key,
+            ctx.accounts.pool_authority.key,
+            ctx.accounts.from_manager_fee_account.key,
+            &ctx.accounts.from_pool_mint.key(),
+            ctx.accounts.token_program.key,
+            amount_in,
+        ),
+        &[
+            ctx.accounts.from_stake_pool.to_account_info(),
+            ctx.accounts.from_withdraw_authority.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            ctx.accounts.vault_from.to_account_info(),
+            ctx.accounts.from_reserve_stake.to_account_info(),
+            ctx.accounts.from_manager_fee_account.to_account_info(),
+            ctx.accounts.from_pool_mint.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.stake_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        signer,
+    )?;
+    let lamports_received = ctx
+        .accounts
+        .pool_authority
+        .lamports()
+        .checked_sub(lamports_before)
+        .ok_or(SwapError::MathOverflow)?;
+
+    // Leg 2: stake the SOL into the underweight LST's pool
+    let vault_to_before = ctx.accounts.vault_to.amount;
+    invoke_signed(
+        &spl_stake_pool::instruction::deposit_sol(
+            &spl_stake_pool::id(),
+            ctx.accounts.to_stake_pool.key,
+            ctx.accounts.to_withdraw_authority.key,
+            ctx.accounts.to_reserve_stake.key,
+            ctx.accounts.pool_authority.key,
+            &ctx.accounts.vault_to.key(),
+            ctx.accounts.to_manager_fee_account.key,
+            &ctx.accounts.vault_to.key(),
+            &ctx.accounts.to_pool_mint.key(),
+            ctx.accounts.token_program.key,
+            lamports_received,
+        ),
+        &[
+            ctx.accounts.to_stake_pool.to_account_info(),
+            ctx.accounts.to_withdraw_authority.to_account_info(),
+            ctx.accounts.to_reserve_stake.to_account_info(),
+            ctx.accounts.pool_authority.to_account_info(),
+            ctx.accounts.vault_to.to_account_info(),
+            ctx.accounts.to_manager_fee_account.to_account_info(),
+            ctx.accounts.to_pool_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        ],
+        signer,
+    )?;
+    ctx.accounts.vault_to.reload()?;
+    let amount_out = ctx
+        .accounts
+        .vault_to
+        .amount
+        .checked_sub(vault_to_before)
+        .ok_or(SwapError::MathOverflow)?;
+
+    // Stake pool fees are the only value lost; keep them within the budget
+    let value_out = rate_to.lamports_for(amount_out)?;
+    let min_value_out = value_in as u128
+        * (10_000 - ctx.accounts.pool.config.max_rebalance_slippage_bps as u128)
+        / 10_000;
+    require!(value_out as u128 >= min_value_out, SwapError::SlippageExceeded);
+
+    let pool = &mut ctx.accounts.pool;
+    let reserves_before = weighted_swap::reserves(pool);
+    pool.token_weights[index_from].amount = pool.token_weights[index_from]
+        .amount
+        .checked_sub(amount_in)
+        .ok_or(SwapError::InsufficientLiquidity)?;
+    pool.token_weights[index_to].amount = pool.token_weights[index_to]
+        .amount
+        .checked_add(amount_out)
+        .ok_or(SwapError::MathOverflow)?;
+
+    emit!(ReservesRebalanced {
+        pool: pool.key(),
+        mint_from: pool.token_weights[index_from].mint,
+        mint_to: pool.token_weights[index_to].mint,
+        amount_in,
+        amount_out,
+        value_in,
+        value_out,
+        reserves_before,
+        reserves_after: weighted_swap::reserves(pool),
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// SOL value to move from one reserve to another so that
+/// `value_from / value_to == weight_from / weight_to`.
+///
+/// Solves (value_from - x) * weight_to = (value_to + x) * weight_from for x,
+/// returning zero when `from` is not overweight relative to `to`.
+pub fn pairwise_rebalance_amount(value_from: u64, weight_from: u16, value_to: u64, weight_to: u16) -> u64 {
+    let excess = value_from as u128 * weight_to as u128;
+    let deficit = value_to as u128 * weight_from as u128;
+    let total_weight = weight_from as u128 + weight_to as u128;
+
+    if excess <= deficit || total_weight == 0 {
+        return 0;
+    }
+
+    ((excess - deficit) / total_weight) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pairwise_rebalance_amount() {
+        // 60/40 by value against 50/50 weights: move 10% of the total
+        assert_eq!(pairwise_rebalance_amount(600, 5_000, 400, 5_000), 100);
+        // Already on target in this direction
+        assert_eq!(pairwise_rebalance_amount(400, 5_000, 600, 5_000), 0);
+        // 50/50 by value against 80/20 weights moves from the 20% token
+        assert_eq!(pairwise_rebalance_amount(500, 2_000, 500, 8_000), 300);
+        assert_eq!(pairwise_rebalance_amount(500, 8_000, 500, 2_000), 0);
+    }
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::SwapError;
+use crate::instructions::swap_route::RouteLeg;
+
+/// Swaps for exactly `amount_out` of the output token, charging the fee on
+/// the input and paying at most `max_amount_in`.
+///
+/// Remaining accounts: a single leg in the `swap_route` layout, against either
+/// a stableswap `Pool` or a `RiskAdjustedPool`.
+#[derive(Accounts)]
+pub struct SwapExactOut<'info> {
+    #[account(mut)]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapExactOut<'info>>,
+    amount_out: u64,
+    max_amount_in: u64,
+) -> Result<()> {
+    require!(amount_out > 0, SwapError::InvalidPoolParameters);
+
+    let (mut leg, rest) = RouteLeg::parse(ctx.remaining_accounts)?;
+    require!(rest.is_empty(), SwapError::InvalidRoute);
+    require_keys_eq!(leg.mint_in, ctx.accounts.user_token_in.mint, SwapError::InvalidTokenAccount);
+    require_keys_eq!(leg.mint_out, ctx.accounts.user_token_out.mint, SwapError::InvalidTokenAccount);
+
+    let amount_in = leg.swap_exact_out(amount_out)?;
+    require!(amount_in <= max_amount_in, SwapError::SlippageExceeded);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                to: leg.vault_in.clone(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+    leg.pay_out(
+        &ctx.accounts.token_program,
+        ctx.accounts.user_token_out.to_account_info(),
+        amount_out,
+    )?;
+
+    leg.save()?;
+    leg.emit_swap(ctx.accounts.user.key(), amount_in, amount_out)
+}
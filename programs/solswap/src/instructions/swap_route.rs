@@ -0,0 +1,341 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::SwapError;
+use crate::events::PoolSwapped;
+use crate::instructions::weighted_swap;
+use crate::math::stable_math;
+use crate::state::*;
+
+/// Maximum number of legs in a route
+pub const MAX_ROUTE_LEGS: usize = 4;
+
+/// Swaps through an ordered route of pools, enforcing a single end-to-end
+/// `min_amount_out`.
+///
+/// Remaining accounts, for each leg in order: the pool (a stableswap `Pool` or
+/// a `RiskAdjustedPool`), its authority, its vault for the leg's input token
+/// and its vault for the leg's output token, followed for a weighted pool by
+/// its stake pools as in `weighted_swap`. Each leg's output moves straight
+/// from its vault into the next leg's input vault, so no intermediate token
+/// accounts are involved.
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    #[account(mut)]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    require!(amount_in > 0, SwapError::InvalidPoolParameters);
+
+    let mut legs = parse_route(ctx.remaining_accounts)?;
+    require!(
+        !legs.is_empty() && legs.len() <= MAX_ROUTE_LEGS,
+        SwapError::InvalidRoute
+    );
+    require_keys_eq!(
+        legs[0].mint_in,
+        ctx.accounts.user_token_in.mint,
+        SwapError::InvalidRoute
+    );
+    require_keys_eq!(
+        legs[legs.len() - 1].mint_out,
+        ctx.accounts.user_token_out.mint,
+        SwapError::InvalidRoute
+    );
+    for pair in legs.windows(2) {
+        require_keys_eq!(pair[0].mint_out, pair[1].mint_in, SwapError::InvalidRoute);
+    }
+    // Each leg works on its own copy of the pool state, so a pool may appear once
+    for (i, leg) in legs.iter().enumerate() {
+        require!(
+            legs[..i].iter().all(|other| other.pool_info.key != leg.pool_info.key),
+            SwapError::InvalidRoute
+        );
+    }
+
+    // Quote every leg before moving any tokens
+    let mut amounts = Vec::with_capacity(legs.len() + 1);
+    amounts.push(amount_in);
+    for leg in legs.iter_mut() {
+        let amount_out = leg.swap_exact_in(*amounts.last().unwrap())?;
+        require!(amount_out > 0, SwapError::InsufficientLiquidity);
+        amounts.push(amount_out);
+    }
+    let amount_out = *amounts.last().unwrap();
+    require!(amount_out >= min_amount_out, SwapError::SlippageExceeded);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                to: legs[0].vault_in.clone(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    for (i, leg) in legs.iter().enumerate() {
+        let destination = match legs.get(i + 1) {
+            Some(next) => next.vault_in.clone(),
+            None => ctx.accounts.user_token_out.to_account_info(),
+        };
+        leg.pay_out(&ctx.accounts.token_program, destination, amounts[i + 1])?;
+        leg.save()?;
+        leg.emit_swap(ctx.accounts.user.key(), amounts[i], amounts[i + 1])?;
+    }
+
+    Ok(())
+}
+
+/// Pool traded against in one leg of a route
+pub enum RoutePool {
+    Stable(Pool),
+    Weighted(Box<RiskAdjustedPool>),
+}
+
+/// One leg of a route, with the pool state it updates
+pub struct RouteLeg<'a, 'info> {
+    pub pool_info: &'a AccountInfo<'info>,
+    pub pool: RoutePool,
+    pub authority: AccountInfo<'info>,
+    pub authority_bump: u8,
+    pub vault_in: AccountInfo<'info>,
+    pub vault_out: AccountInfo<'info>,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub index_in: usize,
+    pub index_out: usize,
+    pub stake_pools: &'a [AccountInfo<'info>],
+    /// Fee rate charged by the last quote through this leg (basis points)
+    pub fee_bps: u16,
+    /// Pool balances before the last quote through this leg
+    pub reserves_before: Vec<u64>,
+}
+
+/// Splits `accounts` into route legs
+pub fn parse_route<'a, 'info>(accounts: &'a [AccountInfo<'info>]) -> Result<Vec<RouteLeg<'a, 'info>>> {
+    let mut legs = Vec::new();
+    let mut rest = accounts;
+    while !rest.is_empty() {
+        let (leg, remaining) = RouteLeg::parse(rest)?;
+        legs.push(leg);
+        rest = remaining;
+    }
+    Ok(legs)
+}
+
+impl<'a, 'info> RouteLeg<'a, 'info> {
+    /// Parses one leg from the front of `accounts`, returning the accounts after it
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<(Self, &'a [AccountInfo<'info>])> {
+        require!(accounts.len() >= 4, SwapError::InvalidRoute);
+        let pool_info = &accounts[0];
+        let authority = &accounts[1];
+        require!(
+            pool_info.owner == &crate::ID && pool_info.is_writable,
+            SwapError::InvalidRoute
+        );
+
+        let pool = {
+            let data = pool_info.try_borrow_data()?;
+            if data.get(..8) == Some(&Pool::DISCRIMINATOR[..]) {
+                RoutePool::Stable(Pool::try_deserialize(&mut &data[..])?)
+            } else {
+                RoutePool::Weighted(Box::new(RiskAdjustedPool::try_deserialize(&mut &data[..])?))
+            }
+        };
+
+        let (expected_authority, authority_bump) = match &pool {
+            RoutePool::Stable(stable) => {
+                stable.ensure_unlocked()?;
+                require!(stable.balances.len() == stable.tokens.len(), SwapError::InvalidPoolParameters);
+                Pubkey::find_program_address(&[b"authority", pool_info.key.as_ref()], &crate::ID)
+            }
+            RoutePool::Weighted(weighted) => (weighted.authority, weighted.bump),
+        };
+        require_keys_eq!(authority.key(), expected_authority, SwapError::InvalidRoute);
+
+        let mint_in = vault_mint(&accounts[2], &expected_authority)?;
+        let mint_out = vault_mint(&accounts[3], &expected_authority)?;
+        require_keys_neq!(mint_in, mint_out, SwapError::InvalidTokenAccount);
+
+        let (index_in, index_out, stake_pool_count) = match &pool {
+            RoutePool::Stable(stable) => (stable.token_index(&mint_in), stable.token_index(&mint_out), 0),
+            RoutePool::Weighted(weighted) => (
+                weighted.token_index(&mint_in),
+                weighted.token_index(&mint_out),
+                weighted
+                    .token_weights
+                    .iter()
+                    .filter(|token| token.stake_pool != Pubkey::default())
+                    .count(),
+            ),
+        };
+        let index_in = index_in.ok_or(SwapError::InvalidTokenAccount)?;
+        let index_out = index_out.ok_or(SwapError::InvalidTokenAccount)?;
+
+        let rest = &accounts[4..];
+        require!(rest.len() >= stake_pool_count, SwapError::InvalidStakePool);
+        let (stake_pools, rest) = rest.split_at(stake_pool_count);
+
+        let leg = Self {
+            pool_info,
+            pool,
+            authority: authority.clone(),
+            authority_bump,
+            vault_in: accounts[2].clone(),
+            vault_out: accounts[3].clone(),
+            mint_in,
+            mint_out,
+            index_in,
+            index_out,
+            stake_pools,
+            fee_bps: 0,
+            reserves_before: Vec::new(),
+        };
+        Ok((leg, rest))
+    }
+
+    /// Quotes `amount_in` through this leg and records it in the pool state
+    pub fn swap_exact_in(&mut self, amount_in: u64) -> Result<u64> {
+        let (index_in, index_out) = (self.index_in, self.index_out);
+        self.reserves_before = self.reserves();
+        match &mut self.pool {
+            RoutePool::Stable(pool) => {
+                self.fee_bps = pool.fee_bps;
+                let amount_out = stable_math::calc_out_given_in(
+                    &pool.balances,
+                    pool.amp,
+                    index_in,
+                    index_out,
+                    amount_in,
+                    pool.fee_bps,
+                )?;
+                pool.record_swap(index_in, index_out, amount_in, amount_out)?;
+                Ok(amount_out)
+            }
+            RoutePool::Weighted(pool) => {
+                let (amount_out, fee_bps) =
+                    weighted_swap::quote_exact_in(pool, index_in, index_out, amount_in, self.stake_pools)?;
+                self.fee_bps = fee_bps;
+                weighted_swap::record_swap(pool, index_in, index_out, amount_in, amount_out)?;
+                Ok(amount_out)
+            }
+        }
+    }
+
+    /// Quotes the input needed for exactly `amount_out` through this leg and
+    /// records it in the pool state
+    pub fn swap_exact_out(&mut self, amount_out: u64) -> Result<u64> {
+        let (index_in, index_out) = (self.index_in, self.index_out);
+        self.reserves_before = self.reserves();
+        match &mut self.pool {
+            RoutePool::Stable(pool) => {
+                self.fee_bps = pool.fee_bps;
+                let amount_in = stable_math::calc_in_given_out(
+                    &pool.balances,
+                    pool.amp,
+                    index_in,
+                    index_out,
+                    amount_out,
+                    pool.fee_bps,
+                )?;
+                pool.record_swap(index_in, index_out, amount_in, amount_out)?;
+                Ok(amount_in)
+            }
+            RoutePool::Weighted(pool) => {
+                let (amount_in, fee_bps) =
+                    weighted_swap::quote_exact_out(pool, index_in, index_out, amount_out, self.stake_pools)?;
+                self.fee_bps = fee_bps;
+                weighted_swap::record_swap(pool, index_in, index_out, amount_in, amount_out)?;
+                Ok(amount_in)
+            }
+        }
+    }
+
+    /// Pays `amount` out of this leg's output vault, signed by the pool authority
+    pub fn pay_out(
+        &self,
+        token_program: &Program<'info, Token>,
+        destination: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let pool_key = self.pool_info.key();
+        let seeds = &[b"authority".as_ref(), pool_key.as_ref(), &[self.authority_bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: self.vault_out.clone(),
+                    to: destination,
+                    authority: self.authority.clone(),
+                },
+                &[&seeds[..]],
+            ),
+            amount,
+        )
+    }
+
+    /// Pool balance of each token, in pool order
+    pub fn reserves(&self) -> Vec<u64> {
+        match &self.pool {
+            RoutePool::Stable(pool) => pool.balances.clone(),
+            RoutePool::Weighted(pool) => weighted_swap::reserves(pool),
+        }
+    }
+
+    /// Emits the swap recorded through this leg
+    pub fn emit_swap(&self, user: Pubkey, amount_in: u64, amount_out: u64) -> Result<()> {
+        let risk_premium = match &self.pool {
+            RoutePool::Stable(_) => 0,
+            RoutePool::Weighted(pool) => weighted_swap::risk_premium_for(pool, self.index_in),
+        };
+        emit!(PoolSwapped {
+            pool: self.pool_info.key(),
+            user,
+            mint_in: self.mint_in,
+            mint_out: self.mint_out,
+            amount_in,
+            amount_out,
+            fee_bps: self.fee_bps,
+            reserves_before: self.reserves_before.clone(),
+            reserves_after: self.reserves(),
+            risk_premium,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Writes the updated pool state back to its account
+    pub fn save(&self) -> Result<()> {
+        let mut data = self.pool_info.try_borrow_mut_data()?;
+        match &self.pool {
+            RoutePool::Stable(pool) => pool.try_serialize(&mut &mut data[..]),
+            RoutePool::Weighted(pool) => pool.try_serialize(&mut &mut data[..]),
+        }
+    }
+}
+
+/// Mint of a pool vault, checking that `authority` owns it
+fn vault_mint(vault: &AccountInfo, authority: &Pubkey) -> Result<Pubkey> {
+    require!(
+        vault.owner == &token::ID && vault.is_writable,
+        SwapError::InvalidTokenAccount
+    );
+    let account = TokenAccount::try_deserialize(&mut &vault.try_borrow_data()?[..])?;
+    require_keys_eq!(account.owner, *authority, SwapError::InvalidTokenAccount);
+    Ok(account.mint)
+}
@@ -0,0 +1,309 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::SwapError;
+use crate::events::PoolSwapped;
+use crate::math::{directional_fee, weighted_math};
+use crate::oracles::stake_pool_rate::{load_stake_pool_rate, StakePoolRate};
+use crate::state::*;
+
+/// Swaps between two pool tokens.
+///
+/// Remaining accounts: the SPL stake pool of every pool token that has one
+/// configured, in `token_weights` order, used to value the pool's composition
+/// for directional fees.
+#[derive(Accounts)]
+pub struct WeightedSwap<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, RiskAdjustedPool>>,
+
+    /// CHECK: PDA that owns the pool vaults
+    #[account(
+        seeds = [b"authority", pool.key().as_ref()],
+        bump = pool.bump,
+        address = pool.authority,
+    )]
+    pub pool_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = vault_in.owner == pool.authority @ SwapError::InvalidTokenAccount,
+    )]
+    pub vault_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = vault_out.owner == pool.authority @ SwapError::InvalidTokenAccount,
+        constraint = vault_out.mint != vault_in.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub vault_out: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_in.mint == vault_in.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub user_token_in: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = user_token_out.mint == vault_out.mint @ SwapError::InvalidTokenAccount,
+    )]
+    pub user_token_out: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+pub(crate) fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, WeightedSwap<'info>>,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    require!(amount_in > 0, SwapError::InvalidPoolParameters);
+
+    let pool = &ctx.accounts.pool;
+    let index_in = pool
+        .token_index(&ctx.accounts.vault_in.mint)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+    let index_out = pool
+        .token_index(&ctx.accounts.vault_out.mint)
+        .ok_or(SwapError::InvalidTokenAccount)?;
+    let (amount_out, fee_bps) = quote_exact_in(pool, index_in, index_out, amount_in, ctx.remaining_accounts)?;
+
+    require!(amount_out > 0, SwapError::InsufficientLiquidity);
+    require!(amount_out >= min_amount_out, SwapError::SlippageExceeded);
+
+    // Pull the full input, fee included; the fee stays in the pool for LPs
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_in.to_account_info(),
+                to: ctx.accounts.vault_in.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    let pool_key = ctx.accounts.pool.key();
+    let seeds = &[b"authority".as_ref(), pool_key.as_ref(), &[ctx.accounts.pool.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault_out.to_account_info(),
+                to: ctx.accounts.user_token_out.to_account_info(),
+                authority: ctx.accounts.pool_authority.to_account_info(),
+            },
+            &[&seeds[..]],
+        ),
+        amount_out,
+    )?;
+
+    let reserves_before = reserves(&ctx.accounts.pool);
+    record_swap(&mut ctx.accounts.pool, index_in, index_out, amount_in, amount_out)?;
+
+    let pool = &ctx.accounts.pool;
+    emit!(PoolSwapped {
+        pool: pool.key(),
+        user: ctx.accounts.user.key(),
+        mint_in: ctx.accounts.vault_in.mint,
+        mint_out: ctx.accounts.vault_out.mint,
+        amount_in,
+        amount_out,
+        fee_bps,
+        reserves_before,
+        reserves_after: reserves(pool),
+        risk_premium: risk_premium_for(pool, index_in),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Amount of token `index_out` received for `amount_in` of token `index_in`
+/// and the directional fee charged. `stake_pools` are the swap's remaining accounts.
+pub fn quote_exact_in(
+    pool: &RiskAdjustedPool,
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+    stake_pools: &[AccountInfo],
+) -> Result<(u64, u16)> {
+    let values = token_values(pool, &stake_pool_rates(pool, stake_pools)?)?;
+    quote_exact_in_at(pool, index_in, index_out, amount_in, &values, Clock::get()?.unix_timestamp)
+}
+
+/// `quote_exact_in` for a pool whose tokens are worth `values` lamports, at `now`
+pub fn quote_exact_in_at(
+    pool: &RiskAdjustedPool,
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+    values: &[u128],
+    now: i64,
+) -> Result<(u64, u16)> {
+    pool.config.validate_fees()?;
+
+    // Weights move linearly during a rebalance, so price with the current ones
+    let weight_in = pool.effective_weight(index_in, now);
+    let weight_out = pool.effective_weight(index_out, now);
+
+    let quote = |fee_bps: u16| {
+        weighted_math::calc_out_given_in(
+            pool.token_weights[index_in].amount,
+            weight_in,
+            pool.token_weights[index_out].amount,
+            weight_out,
+            amount_in,
+            fee_bps,
+        )
+    };
+
+    // Price risk through the fee: value the composition before and after the
+    // trade at the base fee, then charge the fee for the direction it moves
+    let base_out = quote(pool.config.swap_fee_bps)?;
+    let mut values_after = values.to_vec();
+    values_after[index_in] += values[index_in] * amount_in as u128
+        / pool.token_weights[index_in].amount.max(1) as u128;
+    values_after[index_out] -= values[index_out] * base_out as u128
+        / pool.token_weights[index_out].amount.max(1) as u128;
+
+    let fee_bps = directional_fee_for(pool, index_in, values, &values_after)?;
+    Ok((quote(fee_bps)?, fee_bps))
+}
+
+/// Amount of token `index_in` required to receive exactly `amount_out` of
+/// token `index_out` and the directional fee charged on the input
+pub fn quote_exact_out(
+    pool: &RiskAdjustedPool,
+    index_in: usize,
+    index_out: usize,
+    amount_out: u64,
+    stake_pools: &[AccountInfo],
+) -> Result<(u64, u16)> {
+    let values = token_values(pool, &stake_pool_rates(pool, stake_pools)?)?;
+    quote_exact_out_at(pool, index_in, index_out, amount_out, &values, Clock::get()?.unix_timestamp)
+}
+
+/// `quote_exact_out` for a pool whose tokens are worth `values` lamports, at `now`
+pub fn quote_exact_out_at(
+    pool: &RiskAdjustedPool,
+    index_in: usize,
+    index_out: usize,
+    amount_out: u64,
+    values: &[u128],
+    now: i64,
+) -> Result<(u64, u16)> {
+    pool.config.validate_fees()?;
+
+    let weight_in = pool.effective_weight(index_in, now);
+    let weight_out = pool.effective_weight(index_out, now);
+
+    let quote = |fee_bps: u16| {
+        weighted_math::calc_in_given_out(
+            pool.token_weights[index_in].amount,
+            weight_in,
+            pool.token_weights[index_out].amount,
+            weight_out,
+            amount_out,
+            fee_bps,
+        )
+    };
+
+    let base_in = quote(pool.config.swap_fee_bps)?;
+    let mut values_after = values.to_vec();
+    values_after[index_in] += values[index_in] * base_in as u128
+        / pool.token_weights[index_in].amount.max(1) as u128;
+    values_after[index_out] -= values[index_out] * amount_out as u128
+        / pool.token_weights[index_out].amount.max(1) as u128;
+
+    let fee_bps = directional_fee_for(pool, index_in, values, &values_after)?;
+    Ok((quote(fee_bps)?, fee_bps))
+}
+
+/// Fee for a trade adding token `index_in` that moves the pool's SOL values
+/// from `values` to `values_after`
+fn directional_fee_for(
+    pool: &RiskAdjustedPool,
+    index_in: usize,
+    values: &[u128],
+    values_after: &[u128],
+) -> Result<u16> {
+    let targets = pool.targets();
+    directional_fee::directional_fee_bps(
+        pool.config.swap_fee_bps,
+        directional_fee::total_deviation(values, &targets),
+        directional_fee::total_deviation(values_after, &targets),
+        risk_premium_for(pool, index_in),
+        pool.config.rebalance_fee_sensitivity,
+        pool.config.max_swap_fee_bps.max(pool.config.swap_fee_bps),
+    )
+}
+
+/// Risk premium charged on trades adding token `index`; tokens without a
+/// risk score pay the full premium
+pub fn risk_premium_for(pool: &RiskAdjustedPool, index: usize) -> u8 {
+    pool.risk_scores
+        .iter()
+        .find(|score| score.mint == pool.token_weights[index].mint)
+        .map_or(100, RiskScore::risk_premium)
+}
+
+/// Tracked balance of each pool token, in `token_weights` order
+pub fn reserves(pool: &RiskAdjustedPool) -> Vec<u64> {
+    pool.token_weights.iter().map(|token| token.amount).collect()
+}
+
+/// Updates the pool's tracked balances after a swap
+pub fn record_swap(
+    pool: &mut RiskAdjustedPool,
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+    amount_out: u64,
+) -> Result<()> {
+    pool.token_weights[index_in].amount = pool.token_weights[index_in]
+        .amount
+        .checked_add(amount_in)
+        .ok_or(SwapError::MathOverflow)?;
+    pool.token_weights[index_out].amount = pool.token_weights[index_out]
+        .amount
+        .checked_sub(amount_out)
+        .ok_or(SwapError::InsufficientLiquidity)?;
+
+    Ok(())
+}
+
+/// Rates of the pool's stake pools, in `token_weights` order, read from
+/// `stake_pools` as passed to the swap
+pub fn stake_pool_rates(pool: &RiskAdjustedPool, stake_pools: &[AccountInfo]) -> Result<Vec<StakePoolRate>> {
+    let mut stake_pools = stake_pools.iter();
+    pool.token_weights
+        .iter()
+        .filter(|token| token.stake_pool != Pubkey::default())
+        .map(|token| {
+            let stake_pool = stake_pools.next().ok_or(SwapError::InvalidStakePool)?;
+            require_keys_eq!(stake_pool.key(), token.stake_pool, SwapError::InvalidStakePool);
+            load_stake_pool_rate(stake_pool, &token.mint)
+        })
+        .collect()
+}
+
+/// SOL value of each pool token's balance, in `token_weights` order, given
+/// the `rates` of the tokens with a stake pool. Tokens without a stake pool
+/// are valued one to one.
+pub fn token_values(pool: &RiskAdjustedPool, rates: &[StakePoolRate]) -> Result<Vec<u128>> {
+    let mut rates = rates.iter();
+    pool.token_weights
+        .iter()
+        .map(|token| {
+            if token.stake_pool == Pubkey::default() {
+                return Ok(token.amount as u128);
+            }
+            let rate = rates.next().ok_or(SwapError::InvalidStakePool)?;
+            Ok(rate.lamports_for(token.amount)? as u128)
+        })
+        .collect()
+}
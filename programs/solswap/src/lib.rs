@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
 
+pub mod error;
+pub mod events;
+pub mod instructions;
+pub mod math;
+pub mod oracles;
+pub mod state;
+
+use instructions::*;
+
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 #[program]
@@ -9,6 +18,67 @@ pub mod solswap {
     pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
         Ok(())
     }
+
+    pub fn rebalance_pool(ctx: Context<RebalancePool>) -> Result<()> {
+        instructions::rebalance_pool::handler(ctx)
+    }
+
+    pub fn weighted_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, WeightedSwap<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::weighted_swap::handler(ctx, amount_in, min_amount_out)
+    }
+
+    pub fn rebalance_reserves(ctx: Context<RebalanceReserves>, max_amount_in: u64) -> Result<()> {
+        instructions::rebalance_reserves::handler(ctx, max_amount_in)
+    }
+
+    pub fn initialize_reserves_tracker(ctx: Context<InitializeReservesTracker>) -> Result<()> {
+        instructions::initialize_reserves_tracker::handler(ctx)
+    }
+
+    pub fn grow_reserves_tracker(ctx: Context<GrowReservesTracker>) -> Result<()> {
+        instructions::grow_reserves_tracker::handler(ctx)
+    }
+
+    pub fn compound_rewards<'info>(ctx: Context<'_, '_, '_, 'info, CompoundRewards<'info>>) -> Result<()> {
+        instructions::compound_rewards::handler(ctx)
+    }
+
+    pub fn claim_mev_tips(
+        ctx: Context<ClaimMevTips>,
+        claim_status_bump: u8,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::claim_mev_tips::handler(ctx, claim_status_bump, amount, proof)
+    }
+
+    pub fn flash_loan(ctx: Context<FlashLoan>, amount: u64) -> Result<()> {
+        instructions::flash_loan::handler(ctx, amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashRepay>) -> Result<()> {
+        instructions::flash_repay::handler(ctx)
+    }
+
+    pub fn swap_route<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_route::handler(ctx, amount_in, min_amount_out)
+    }
+
+    pub fn swap_exact_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapExactOut<'info>>,
+        amount_out: u64,
+        max_amount_in: u64,
+    ) -> Result<()> {
+        instructions::swap_exact_out::handler(ctx, amount_out, max_amount_in)
+    }
 }
 
 #[derive(Accounts)]
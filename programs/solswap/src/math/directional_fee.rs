@@ -0,0 +1,83 @@
+// programs/solswap/src/math/directional_fee.rs
+use anchor_lang::prelude::*;
+use crate::error::SwapError;
+
+/// Largest discount a rebalancing trade can earn, as a share of the base fee
+pub const MAX_REBALANCE_DISCOUNT_BPS: u64 = 5_000;
+
+/// Total distance of the pool's composition from its targets: the sum over
+/// tokens of |value share - target| in basis points
+pub fn total_deviation(values: &[u128], targets: &[u16]) -> u64 {
+    let total: u128 = values.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+
+    values
+        .iter()
+        .zip(targets)
+        .map(|(value, target)| {
+            let share = (value * 10_000 / total) as u64;
+            share.abs_diff(*target as u64)
+        })
+        .sum()
+}
+
+/// Fee for a trade that moves total deviation from `deviation_before` to
+/// `deviation_after`.
+///
+/// Each basis point of deviation removed earns `sensitivity` bps of the base
+/// fee as a discount, up to `MAX_REBALANCE_DISCOUNT_BPS`. Each basis point
+/// added costs the same surcharge scaled up by the risk premium (0-100) of the
+/// token being sold to the pool, so a high-risk LST costs up to twice as much
+/// to dump. The result is capped at `max_fee_bps`.
+pub fn directional_fee_bps(
+    base_fee_bps: u16,
+    deviation_before: u64,
+    deviation_after: u64,
+    risk_premium: u8,
+    sensitivity: u16,
+    max_fee_bps: u16,
+) -> Result<u16> {
+    require!(risk_premium <= 100, SwapError::InvalidPoolParameters);
+    let base = base_fee_bps as u128;
+
+    let fee = if deviation_after <= deviation_before {
+        let improvement = (deviation_before - deviation_after) as u128;
+        let discount = (improvement * sensitivity as u128).min(MAX_REBALANCE_DISCOUNT_BPS as u128);
+        base * (10_000 - discount) / 10_000
+    } else {
+        let worsening = (deviation_after - deviation_before) as u128;
+        let surcharge = worsening * sensitivity as u128 * (100 + risk_premium as u128) / 100;
+        base + base * surcharge / 10_000
+    };
+
+    Ok(fee.min(max_fee_bps as u128) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_deviation() {
+        assert_eq!(total_deviation(&[500, 500], &[5_000, 5_000]), 0);
+        assert_eq!(total_deviation(&[600, 400], &[5_000, 5_000]), 2_000);
+        assert_eq!(total_deviation(&[0, 0], &[5_000, 5_000]), 0);
+    }
+
+    #[test]
+    fn test_rebalancing_discount_and_risk_surcharge() {
+        // At 1% of the fee per bp, moving 1% closer to target is capped at half off
+        assert_eq!(directional_fee_bps(30, 2_000, 1_900, 0, 100, 100).unwrap(), 15);
+        assert_eq!(directional_fee_bps(30, 2_000, 1_980, 0, 100, 100).unwrap(), 24);
+        assert_eq!(directional_fee_bps(30, 2_000, 2_000, 50, 100, 100).unwrap(), 30);
+
+        // The same move away from target costs more for a riskier token
+        assert_eq!(directional_fee_bps(30, 1_980, 2_000, 0, 100, 100).unwrap(), 36);
+        assert_eq!(directional_fee_bps(30, 1_980, 2_000, 100, 100, 100).unwrap(), 42);
+        assert_eq!(directional_fee_bps(30, 0, 5_000, 100, 100, 100).unwrap(), 100);
+
+        assert!(directional_fee_bps(30, 0, 0, 101, 100, 100).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use crate::error::SwapError;
+
+/// 1.0 in 18-decimal fixed point
+pub const ONE: u128 = 1_000_000_000_000_000_000;
+/// ln(2) in 18-decimal fixed point
+pub const LN_2: i128 = 693_147_180_559_945_309;
+/// Largest exponent accepted by `exp` (e^41 * ONE still fits in u128)
+pub const MAX_EXP_ARG: i128 = 41 * ONE as i128;
+/// Smallest exponent accepted by `exp`; anything below rounds to zero
+pub const MIN_EXP_ARG: i128 = -41 * ONE as i128;
+/// Relative error bound applied when rounding `pow` results (1e-14)
+pub const MAX_POW_RELATIVE_ERROR: u128 = 10_000;
+
+/// Multiplies two fixed-point values, rounding down
+pub fn mul_down(a: u128, b: u128) -> Result<u128> {
+    Ok(a.checked_mul(b).ok_or(SwapError::MathOverflow)? / ONE)
+}
+
+/// Multiplies two fixed-point values, rounding up
+pub fn mul_up(a: u128, b: u128) -> Result<u128> {
+    let product = a.checked_mul(b).ok_or(SwapError::MathOverflow)?;
+    Ok(if product == 0 { 0 } else { (product - 1) / ONE + 1 })
+}
+
+/// Divides two fixed-point values, rounding down
+pub fn div_down(a: u128, b: u128) -> Result<u128> {
+    require!(b != 0, SwapError::InvalidCalculation);
+    Ok(a.checked_mul(ONE).ok_or(SwapError::MathOverflow)? / b)
+}
+
+/// Divides two fixed-point values, rounding up
+pub fn div_up(a: u128, b: u128) -> Result<u128> {
+    require!(b != 0, SwapError::InvalidCalculation);
+    let scaled = a.checked_mul(ONE).ok_or(SwapError::MathOverflow)?;
+    Ok(if scaled == 0 { 0 } else { (scaled - 1) / b + 1 })
+}
+
+/// Natural logarithm of a positive fixed-point value.
+///
+/// The argument is reduced to m * 2^k with m in [1, 2), and ln(m) is
+/// evaluated with the atanh series 2 * (z + z^3/3 + z^5/5 + ...), z = (m-1)/(m+1).
+pub fn ln(x: u128) -> Result<i128> {
+    require!(x > 0, SwapError::InvalidCalculation);
+
+    let mut m = x;
+    let mut k: i128 = 0;
+    while m >= 2 * ONE {
+        m >>= 1;
+        k += 1;
+    }
+    while m < ONE {
+        m <<= 1;
+        k -= 1;
+    }
+
+    let one = ONE as i128;
+    let m = m as i128;
+    let z = (m - one) * one / (m + one);
+    let z_squared = z * z / one;
+
+    let mut term = z;
+    let mut sum = 0i128;
+    let mut n = 1i128;
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / one;
+        n += 2;
+    }
+
+    Ok(k * LN_2 + 2 * sum)
+}
+
+/// Natural exponential of a fixed-point value.
+///
+/// The argument is reduced to k * ln(2) + r with |r| <= ln(2) / 2 and e^r is
+/// evaluated with its Taylor series before scaling by 2^k.
+pub fn exp(x: i128) -> Result<u128> {
+    require!(x <= MAX_EXP_ARG, SwapError::MathOverflow);
+    if x < MIN_EXP_ARG {
+        return Ok(0);
+    }
+
+    let one = ONE as i128;
+    let k = (x + if x >= 0 { LN_2 / 2 } else { -LN_2 / 2 }) / LN_2;
+    let r = x - k * LN_2;
+
+    let mut term = one;
+    let mut sum = one;
+    let mut n = 1i128;
+    while term != 0 {
+        term = term * r / one / n;
+        sum += term;
+        n += 1;
+    }
+
+    let sum = sum as u128;
+    Ok(if k >= 0 { sum << k } else { sum >> (-k) })
+}
+
+/// Raises a positive fixed-point base to a fixed-point exponent
+pub fn pow(base: u128, exponent: u128) -> Result<u128> {
+    if exponent == 0 {
+        return Ok(ONE);
+    }
+    if base == 0 {
+        return Ok(0);
+    }
+    if exponent == ONE {
+        return Ok(base);
+    }
+
+    let exponent = i128::try_from(exponent).map_err(|_| SwapError::MathOverflow)?;
+    let log = ln(base)?
+        .checked_mul(exponent)
+        .ok_or(SwapError::MathOverflow)?
+        / ONE as i128;
+    exp(log)
+}
+
+/// `pow` rounded down by its error bound
+pub fn pow_down(base: u128, exponent: u128) -> Result<u128> {
+    let raw = pow(base, exponent)?;
+    let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR)? + 1;
+    Ok(raw.saturating_sub(max_error))
+}
+
+/// `pow` rounded up by its error bound
+pub fn pow_up(base: u128, exponent: u128) -> Result<u128> {
+    let raw = pow(base, exponent)?;
+    let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR)? + 1;
+    Ok(raw.checked_add(max_error).ok_or(SwapError::MathOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(x: u128) -> f64 {
+        x as f64 / ONE as f64
+    }
+
+    fn from_f64(x: f64) -> u128 {
+        (x * ONE as f64) as u128
+    }
+
+    #[test]
+    fn test_ln_and_exp_match_float() {
+        for x in [0.001, 0.25, 0.5, 0.999, 1.0, 1.5, 2.0, 7.3, 1_000.0, 1e9] {
+            let result = ln(from_f64(x)).unwrap() as f64 / ONE as f64;
+            assert!((result - x.ln()).abs() < 1e-12, "ln({})", x);
+        }
+        for x in [-40.0, -5.0, -0.3, 0.0, 0.3, 1.0, 5.0, 40.0] {
+            let result = to_f64(exp((x * ONE as f64) as i128).unwrap());
+            assert!((result - x.exp()).abs() < 1e-12 * x.exp().max(1.0), "exp({})", x);
+        }
+    }
+
+    #[test]
+    fn test_pow_bounds_bracket_exact_value() {
+        for (base, exponent) in [(0.5, 0.25), (0.9, 4.0), (1.1, 0.5), (3.0, 1.7)] {
+            let exact: f64 = f64::powf(base, exponent);
+            let down = to_f64(pow_down(from_f64(base), from_f64(exponent)).unwrap());
+            let up = to_f64(pow_up(from_f64(base), from_f64(exponent)).unwrap());
+            assert!(down <= up);
+            assert!(((down - exact) / exact).abs() < 1e-12);
+            assert!(((up - exact) / exact).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod directional_fee;
+pub mod fixed_point;
+pub mod stable_math;
+pub mod weighted_math;
+
+pub use directional_fee::*;
+pub use fixed_point::*;
+pub use weighted_math::*;
@@ -0,0 +1,44 @@
+//! StableSwap curve for `Pool`, backed by `solswap_math::stable` so off-chain
+//! quotes match on-chain results exactly
+
+use anchor_lang::prelude::*;
+use solswap_math::MathError;
+use crate::error::SwapError;
+
+/// Maps shared math errors onto the program's error codes
+pub fn math_error(error: MathError) -> Error {
+    match error {
+        MathError::Overflow => SwapError::MathOverflow.into(),
+        MathError::InsufficientLiquidity => SwapError::InsufficientLiquidity.into(),
+        MathError::InvalidInput => SwapError::InvalidTokenAccount.into(),
+        MathError::NoConvergence | MathError::RateNotVerified => SwapError::InvalidCalculation.into(),
+    }
+}
+
+/// Amount of token `index_out` received for `amount_in` of token `index_in`.
+/// The fee is taken from the input; rounds against the trader.
+pub fn calc_out_given_in(
+    balances: &[u64],
+    amp: u64,
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    solswap_math::stable::calc_out_given_in(balances, amp, index_in, index_out, amount_in, fee_bps)
+        .map_err(math_error)
+}
+
+/// Amount of token `index_in` required to receive exactly `amount_out` of
+/// token `index_out`. The fee is charged on the input; rounds against the trader.
+pub fn calc_in_given_out(
+    balances: &[u64],
+    amp: u64,
+    index_in: usize,
+    index_out: usize,
+    amount_out: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    solswap_math::stable::calc_in_given_out(balances, amp, index_in, index_out, amount_out, fee_bps)
+        .map_err(math_error)
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use crate::error::SwapError;
+use crate::math::fixed_point::*;
+
+/// Basis point denominator for weights and fees
+pub const BPS_DENOMINATOR: u128 = 10_000;
+/// A single swap may add at most 30% of the input balance
+pub const MAX_IN_RATIO: u128 = 300_000_000_000_000_000;
+/// A single swap may remove at most 30% of the output balance
+pub const MAX_OUT_RATIO: u128 = 300_000_000_000_000_000;
+
+/// Converts a basis point weight into an 18-decimal fixed-point fraction
+fn weight_to_fixed(weight_bps: u16) -> Result<u128> {
+    require!(weight_bps > 0, SwapError::InvalidPoolParameters);
+    Ok(weight_bps as u128 * ONE / BPS_DENOMINATOR)
+}
+
+/// Share of an amount left after a fee of `fee_bps`, over `BPS_DENOMINATOR`
+fn fee_complement(fee_bps: u16) -> Result<u128> {
+    require!((fee_bps as u128) < BPS_DENOMINATOR, SwapError::InvalidFee);
+    Ok(BPS_DENOMINATOR - fee_bps as u128)
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    Ok(u64::try_from(value).map_err(|_| SwapError::MathOverflow)?)
+}
+
+/// Amount of `token_out` received for `amount_in` of `token_in`.
+///
+/// out = balance_out * (1 - (balance_in / (balance_in + in_after_fee)) ^ (w_in / w_out))
+///
+/// Rounds against the trader.
+pub fn calc_out_given_in(
+    balance_in: u64,
+    weight_in: u16,
+    balance_out: u64,
+    weight_out: u16,
+    amount_in: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    require!(balance_in > 0 && balance_out > 0, SwapError::InsufficientLiquidity);
+    require!(
+        amount_in as u128 * ONE <= balance_in as u128 * MAX_IN_RATIO,
+        SwapError::MaxRatioExceeded
+    );
+
+    let amount_in_after_fee = amount_in as u128 * fee_complement(fee_bps)? / BPS_DENOMINATOR;
+    let denominator = balance_in as u128 + amount_in_after_fee;
+
+    // Larger base and smaller exponent both round the power up, and the output down
+    let base = div_up(balance_in as u128, denominator)?;
+    let exponent = div_down(weight_to_fixed(weight_in)?, weight_to_fixed(weight_out)?)?;
+    let power = pow_up(base, exponent)?;
+
+    if power >= ONE {
+        return Ok(0);
+    }
+
+    to_u64(mul_down(balance_out as u128, ONE - power)?)
+}
+
+/// Amount of `token_in` required to receive exactly `amount_out` of `token_out`.
+///
+/// in = balance_in * ((balance_out / (balance_out - amount_out)) ^ (w_out / w_in) - 1) / (1 - fee)
+///
+/// Rounds against the trader.
+pub fn calc_in_given_out(
+    balance_in: u64,
+    weight_in: u16,
+    balance_out: u64,
+    weight_out: u16,
+    amount_out: u64,
+    fee_bps: u16,
+) -> Result<u64> {
+    require!(balance_in > 0 && balance_out > 0, SwapError::InsufficientLiquidity);
+    require!(
+        amount_out as u128 * ONE <= balance_out as u128 * MAX_OUT_RATIO,
+        SwapError::MaxRatioExceeded
+    );
+    let fee_complement = fee_complement(fee_bps)?;
+
+    let base = div_up(balance_out as u128, balance_out as u128 - amount_out as u128)?;
+    let exponent = div_up(weight_to_fixed(weight_out)?, weight_to_fixed(weight_in)?)?;
+    let power = pow_up(base, exponent)?;
+
+    let amount_in_before_fee = mul_up(balance_in as u128, power - ONE)?;
+    let amount_in = (amount_in_before_fee * BPS_DENOMINATOR).div_ceil(fee_complement);
+
+    to_u64(amount_in)
+}
+
+/// Spot price of `token_out` denominated in `token_in`, including the swap fee,
+/// as an 18-decimal fixed-point value.
+///
+/// price = (balance_in / w_in) / (balance_out / w_out) / (1 - fee)
+pub fn calc_spot_price(
+    balance_in: u64,
+    weight_in: u16,
+    balance_out: u64,
+    weight_out: u16,
+    fee_bps: u16,
+) -> Result<u128> {
+    require!(balance_in > 0 && balance_out > 0, SwapError::InsufficientLiquidity);
+    let fee_complement = fee_complement(fee_bps)?;
+
+    let numerator = div_down(balance_in as u128, weight_to_fixed(weight_in)?)?;
+    let denominator = div_down(balance_out as u128, weight_to_fixed(weight_out)?)?;
+    let price = div_down(numerator, denominator)?;
+
+    Ok(price * BPS_DENOMINATOR / fee_complement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float_out_given_in(bi: f64, wi: f64, bo: f64, wo: f64, ai: f64, fee: f64) -> f64 {
+        bo * (1.0 - (bi / (bi + ai * (1.0 - fee))).powf(wi / wo))
+    }
+
+    #[test]
+    fn test_out_given_in_matches_float() {
+        let out = calc_out_given_in(1_000_000_000, 8_000, 2_000_000_000, 2_000, 10_000_000, 30).unwrap();
+        let expected = float_out_given_in(1e9, 0.8, 2e9, 0.2, 1e7, 0.003);
+        assert!(out as f64 <= expected);
+        assert!(expected - out as f64 <= 2.0);
+    }
+
+    #[test]
+    fn test_equal_weights_reduce_to_constant_product() {
+        let out = calc_out_given_in(1_000_000, 5_000, 1_000_000, 5_000, 100_000, 0).unwrap();
+        // x * y = k gives 1_000_000 - 1e12 / 1_100_000 = 90_909.09
+        assert_eq!(out, 90_909);
+    }
+
+    #[test]
+    fn test_round_trip_never_profits() {
+        let (bi, wi, bo, wo, fee) = (5_000_000_000u64, 3_000u16, 9_000_000_000u64, 7_000u16, 10u16);
+        for amount_out in [1u64, 1_000, 1_000_000, 500_000_000] {
+            let amount_in = calc_in_given_out(bi, wi, bo, wo, amount_out, fee).unwrap();
+            let received = calc_out_given_in(bi, wi, bo, wo, amount_in, fee).unwrap();
+            assert!(received >= amount_out);
+            let short = calc_out_given_in(bi, wi, bo, wo, amount_in - 1, fee).unwrap();
+            assert!(short <= amount_out);
+        }
+    }
+
+    #[test]
+    fn test_weight_shift_moves_spot_price() {
+        let balanced = calc_spot_price(1_000_000, 5_000, 1_000_000, 5_000, 0).unwrap();
+        assert_eq!(balanced, ONE);
+
+        // Raising the output token's weight makes it more expensive
+        let heavier_out = calc_spot_price(1_000_000, 4_000, 1_000_000, 6_000, 0).unwrap();
+        assert!(heavier_out > balanced);
+    }
+
+    #[test]
+    fn test_max_ratio_enforced() {
+        assert!(calc_out_given_in(1_000, 5_000, 1_000, 5_000, 301, 0).is_err());
+        assert!(calc_in_given_out(1_000, 5_000, 1_000, 5_000, 301, 0).is_err());
+    }
+
+    #[test]
+    fn test_fees_of_100_percent_rejected() {
+        for fee_bps in [10_000, 10_001, u16::MAX] {
+            assert!(calc_out_given_in(1_000, 5_000, 1_000, 5_000, 100, fee_bps).is_err());
+            assert!(calc_in_given_out(1_000, 5_000, 1_000, 5_000, 100, fee_bps).is_err());
+            assert!(calc_spot_price(1_000, 5_000, 1_000, 5_000, fee_bps).is_err());
+        }
+        assert!(calc_in_given_out(1_000, 5_000, 1_000, 5_000, 100, 9_999).is_ok());
+    }
+}
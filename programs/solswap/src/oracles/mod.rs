@@ -0,0 +1,3 @@
+pub mod stake_pool_rate;
+
+pub use stake_pool_rate::*;
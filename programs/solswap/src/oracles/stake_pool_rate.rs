@@ -0,0 +1,50 @@
+// programs/solswap/src/oracles/stake_pool_rate.rs
+use anchor_lang::prelude::*;
+use spl_stake_pool::state::StakePool;
+use crate::error::SwapError;
+
+/// SOL exchange rate of an SPL stake pool token
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StakePoolRate {
+    /// Total lamports under management
+    pub total_lamports: u64,
+    /// Pool tokens in circulation
+    pub pool_token_supply: u64,
+}
+
+impl StakePoolRate {
+    /// Lamports backing `amount` pool tokens, rounded down
+    pub fn lamports_for(&self, amount: u64) -> Result<u64> {
+        require!(self.pool_token_supply > 0, SwapError::InvalidStakePool);
+        let lamports = amount as u128 * self.total_lamports as u128 / self.pool_token_supply as u128;
+        Ok(u64::try_from(lamports).map_err(|_| SwapError::MathOverflow)?)
+    }
+
+    /// Pool tokens worth `lamports`, rounded down
+    pub fn tokens_for(&self, lamports: u64) -> Result<u64> {
+        require!(self.total_lamports > 0, SwapError::InvalidStakePool);
+        let tokens = lamports as u128 * self.pool_token_supply as u128 / self.total_lamports as u128;
+        Ok(u64::try_from(tokens).map_err(|_| SwapError::MathOverflow)?)
+    }
+}
+
+/// Reads the exchange rate from an SPL stake pool account, checking that it is
+/// owned by the stake pool program and mints `expected_mint`
+pub fn load_stake_pool_rate(stake_pool: &AccountInfo, expected_mint: &Pubkey) -> Result<StakePoolRate> {
+    parse_stake_pool_rate(stake_pool.owner, &stake_pool.try_borrow_data()?, expected_mint)
+}
+
+/// `load_stake_pool_rate` for an account's owner and data, as fetched off chain
+pub fn parse_stake_pool_rate(owner: &Pubkey, data: &[u8], expected_mint: &Pubkey) -> Result<StakePoolRate> {
+    require!(*owner == spl_stake_pool::id(), SwapError::InvalidStakePool);
+
+    let state = StakePool::deserialize(&mut &data[..])
+        .map_err(|_| SwapError::InvalidStakePool)?;
+    require!(state.is_valid(), SwapError::InvalidStakePool);
+    require!(state.pool_mint == *expected_mint, SwapError::InvalidStakePool);
+
+    Ok(StakePoolRate {
+        total_lamports: state.total_lamports,
+        pool_token_supply: state.pool_token_supply,
+    })
+}
@@ -1,4 +1,19 @@
 use anchor_lang::prelude::*;
+use crate::error::SwapError;
+
+pub mod jito_reserves;
+pub mod liquid_stake_pool;
+pub mod lst_pool_config;
+pub mod rewards_history;
+pub mod risk_adjusted_pool;
+pub mod validator_tracking;
+
+pub use jito_reserves::*;
+pub use liquid_stake_pool::*;
+pub use lst_pool_config::*;
+pub use rewards_history::*;
+pub use risk_adjusted_pool::*;
+pub use validator_tracking::*;
 
 #[account]
 pub struct Pool {
@@ -8,6 +23,8 @@ pub struct Pool {
     pub bump: u8,
     /// LST tokens supported in this pool
     pub tokens: Vec<Pubkey>,
+    /// Pool balance of each token, in `tokens` order
+    pub balances: Vec<u64>,
     /// amplification coefficient (A)
     pub amp: u64,
     /// fee percentage in basis points
@@ -16,6 +33,68 @@ pub struct Pool {
     pub admin_fee_bps: u16,
     /// total LP token supply
     pub lp_supply: u64,
+    /// Flash loan awaiting repayment within the current transaction
+    pub active_flash_loan: Option<ActiveFlashLoan>,
+}
+
+impl Pool {
+    /// Maximum number of tokens in a pool
+    pub const MAX_TOKENS: usize = 10;
+    pub const LEN: usize = 8 + 32 + 1 + (4 + 32 * Self::MAX_TOKENS) + (4 + 8 * Self::MAX_TOKENS)
+        + 8 + 2 + 2 + 8 + (1 + ActiveFlashLoan::LEN);
+
+    /// Returns the index of `mint` in `tokens`
+    pub fn token_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.tokens.iter().position(|t| t == mint)
+    }
+
+    /// Updates the pool's balances after a swap
+    pub fn record_swap(&mut self, index_in: usize, index_out: usize, amount_in: u64, amount_out: u64) -> Result<()> {
+        self.balances[index_in] = self.balances[index_in]
+            .checked_add(amount_in)
+            .ok_or(SwapError::MathOverflow)?;
+        self.balances[index_out] = self.balances[index_out]
+            .checked_sub(amount_out)
+            .ok_or(SwapError::InsufficientLiquidity)?;
+        Ok(())
+    }
+
+    /// Rejects swaps and liquidity changes while a flash loan is outstanding,
+    /// so borrowed reserves cannot be priced or withdrawn against
+    pub fn ensure_unlocked(&self) -> Result<()> {
+        require!(self.active_flash_loan.is_none(), SwapError::FlashLoanActive);
+        Ok(())
+    }
+}
+
+impl solswap_math::quoter::StableSwapPool for Pool {
+    fn balances(&self) -> &[u64] {
+        &self.balances
+    }
+
+    fn amp(&self) -> u64 {
+        self.amp
+    }
+
+    fn fee_bps(&self) -> u16 {
+        self.fee_bps
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ActiveFlashLoan {
+    /// Vault the loan was drawn from
+    pub vault: Pubkey,
+    /// Index of the borrowed token in `tokens`
+    pub token_index: u8,
+    /// Vault balance required at repayment: the balance before the loan plus the fee
+    pub required_balance: u64,
+    /// Fee owed to LPs
+    pub fee: u64,
+}
+
+impl ActiveFlashLoan {
+    pub const LEN: usize = 32 + 1 + 8 + 8;
 }
 
 #[account]
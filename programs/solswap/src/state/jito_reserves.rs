@@ -1,6 +1,9 @@
 // programs/solswap/src/state/jito_reserves.rs
 use anchor_lang::prelude::*;
 
+/// Maximum number of validators tracked in `validator_performance`
+pub const MAX_JITO_VALIDATORS: usize = 16;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct JitoReserves {
     pub base_staking_reserves: u64,
@@ -14,4 +17,28 @@ pub struct JitoValidatorMetrics {
     pub validator_identity: Pubkey,
     pub mev_earned: u64,
     pub performance_score: u8,
-}
\ No newline at end of file
+}
+
+impl JitoReserves {
+    /// Credits claimed tips to the validator whose tip distribution account paid them
+    pub fn record_validator_mev(&mut self, validator: Pubkey, amount: u64) -> bool {
+        if let Some(metrics) = self
+            .validator_performance
+            .iter_mut()
+            .find(|m| m.validator_identity == validator)
+        {
+            metrics.mev_earned = metrics.mev_earned.saturating_add(amount);
+            return true;
+        }
+
+        if self.validator_performance.len() >= MAX_JITO_VALIDATORS {
+            return false;
+        }
+        self.validator_performance.push(JitoValidatorMetrics {
+            validator_identity: validator,
+            mev_earned: amount,
+            performance_score: 0,
+        });
+        true
+    }
+}
@@ -1,4 +1,6 @@
 // programs/solswap/src/state/lst_pool_config.rs
+use anchor_lang::prelude::*;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct LstPoolConfig {
     pub jito_config: JitoPoolSettings,
@@ -9,7 +11,10 @@ pub struct LstPoolConfig {
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct JitoPoolSettings {
+    /// Share of claimed MEV tips taken by the protocol (basis points)
     pub mev_share_fee: u64,
+    /// Account receiving the protocol's share of MEV tips
+    pub fee_recipient: Pubkey,
     // Other settings...
 }
 
@@ -0,0 +1,299 @@
+// programs/solswap/src/state/rewards_history.rs
+use anchor_lang::prelude::*;
+
+/// Number of snapshots kept per history (a little over 90 daily periods)
+pub const REWARDS_HISTORY_CAPACITY: usize = 92;
+/// Compounds within one period are merged into the same snapshot
+pub const SNAPSHOT_PERIOD: i64 = 86_400;
+pub const SECONDS_PER_YEAR: i64 = 365 * 86_400;
+/// Maximum number of LSTs with their own yield history
+pub const MAX_TRACKED_LSTS: usize = 8;
+
+pub const WINDOW_7D: i64 = 7 * 86_400;
+pub const WINDOW_30D: i64 = 30 * 86_400;
+pub const WINDOW_90D: i64 = 90 * 86_400;
+
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct RewardsSnapshot {
+    /// Start of the period covered by this snapshot
+    pub timestamp: i64,
+    /// Net staking rewards in lamports, negative when exchange rates fell
+    pub staking_rewards: i64,
+    /// MEV rewards in lamports
+    pub mev_rewards: u64,
+    /// SOL value the rewards accrued on, at the start of the period
+    pub value: u64,
+}
+
+impl RewardsSnapshot {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+}
+
+/// Fixed-capacity ring buffer of rewards snapshots
+#[zero_copy]
+pub struct RewardsHistory {
+    pub snapshots: [RewardsSnapshot; REWARDS_HISTORY_CAPACITY],
+    /// Index of the most recent snapshot
+    pub head: u16,
+    /// Number of valid snapshots
+    pub len: u16,
+    pub _padding: [u8; 4],
+}
+
+impl Default for RewardsHistory {
+    fn default() -> Self {
+        Self {
+            snapshots: [RewardsSnapshot::default(); REWARDS_HISTORY_CAPACITY],
+            head: 0,
+            len: 0,
+            _padding: [0; 4],
+        }
+    }
+}
+
+impl RewardsHistory {
+    pub const LEN: usize = RewardsSnapshot::LEN * REWARDS_HISTORY_CAPACITY +
+        2 +    // head
+        2 +    // len
+        4;     // padding
+
+    /// Most recent snapshot, if any
+    pub fn latest(&self) -> Option<&RewardsSnapshot> {
+        if self.len == 0 {
+            return None;
+        }
+        Some(&self.snapshots[self.head as usize])
+    }
+
+    /// Snapshots from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &RewardsSnapshot> {
+        let len = self.len as usize;
+        let start = (self.head as usize + REWARDS_HISTORY_CAPACITY + 1 - len) % REWARDS_HISTORY_CAPACITY;
+        (0..len).map(move |i| &self.snapshots[(start + i) % REWARDS_HISTORY_CAPACITY])
+    }
+
+    /// Records rewards observed at `timestamp`, merging them into the latest
+    /// snapshot if it is still within its period and overwriting the oldest
+    /// snapshot once the buffer is full
+    pub fn record(&mut self, timestamp: i64, staking_rewards: i64, mev_rewards: u64, value: u64) {
+        if let Some(latest) = self.latest() {
+            if timestamp < latest.timestamp + SNAPSHOT_PERIOD {
+                let latest = &mut self.snapshots[self.head as usize];
+                latest.staking_rewards = latest.staking_rewards.saturating_add(staking_rewards);
+                latest.mev_rewards = latest.mev_rewards.saturating_add(mev_rewards);
+                return;
+            }
+            self.head = ((self.head as usize + 1) % REWARDS_HISTORY_CAPACITY) as u16;
+        }
+
+        self.snapshots[self.head as usize] = RewardsSnapshot {
+            timestamp,
+            staking_rewards,
+            mev_rewards,
+            value,
+        };
+        self.len = (self.len + 1).min(REWARDS_HISTORY_CAPACITY as u16);
+    }
+
+    /// Realized yield statistics for snapshots starting within `window` of `now`
+    pub fn window_stats(&self, now: i64, window: i64) -> WindowStats {
+        let snapshots: Vec<&RewardsSnapshot> = self
+            .iter()
+            .filter(|s| s.timestamp >= now - window && s.timestamp < now)
+            .collect();
+
+        let mut staking: i128 = 0;
+        let mut mev: i128 = 0;
+        let mut value_time: i128 = 0;
+        let mut period_yields: Vec<i128> = Vec::with_capacity(snapshots.len());
+
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            let end = snapshots.get(i + 1).map_or(now, |next| next.timestamp);
+            let exposure = snapshot.value as i128 * (end - snapshot.timestamp) as i128;
+            if exposure == 0 {
+                continue;
+            }
+
+            staking += snapshot.staking_rewards as i128;
+            mev += snapshot.mev_rewards as i128;
+            value_time += exposure;
+            period_yields.push(annualize(snapshot.staking_rewards as i128, exposure));
+        }
+
+        if value_time == 0 {
+            return WindowStats::default();
+        }
+
+        WindowStats {
+            apy_bps: annualize(staking, value_time) as i64,
+            mev_apy_bps: annualize(mev, value_time) as u64,
+            volatility_bps: standard_deviation(&period_yields) as u64,
+        }
+    }
+}
+
+/// Annualized yield in basis points for `rewards` earned on `exposure`
+/// (lamports multiplied by seconds held)
+fn annualize(rewards: i128, exposure: i128) -> i128 {
+    rewards * SECONDS_PER_YEAR as i128 * 10_000 / exposure
+}
+
+/// Population standard deviation, rounded down
+fn standard_deviation(values: &[i128]) -> u128 {
+    if values.len() < 2 {
+        return 0;
+    }
+
+    let count = values.len() as i128;
+    let mean = values.iter().sum::<i128>() / count;
+    let variance = values
+        .iter()
+        .map(|v| ((v - mean) * (v - mean)) as u128)
+        .sum::<u128>()
+        / count as u128;
+
+    integer_sqrt(variance)
+}
+
+fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+/// Realized yield over a trailing window, annualized and in basis points
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct WindowStats {
+    /// Staking yield; negative if exchange rates fell over the window
+    pub apy_bps: i64,
+    /// Yield from claimed MEV
+    pub mev_apy_bps: u64,
+    /// Standard deviation of the per-period staking yield
+    pub volatility_bps: u64,
+}
+
+impl WindowStats {
+    pub const LEN: usize = 8 + 8 + 8;
+}
+
+#[zero_copy]
+#[derive(Debug, Default, PartialEq)]
+pub struct YieldStats {
+    pub window_7d: WindowStats,
+    pub window_30d: WindowStats,
+    pub window_90d: WindowStats,
+}
+
+impl YieldStats {
+    pub const LEN: usize = WindowStats::LEN * 3;
+
+    pub fn from_history(history: &RewardsHistory, now: i64) -> Self {
+        Self {
+            window_7d: history.window_stats(now, WINDOW_7D),
+            window_30d: history.window_stats(now, WINDOW_30D),
+            window_90d: history.window_stats(now, WINDOW_90D),
+        }
+    }
+
+    /// Score (0-100) for `RiskScore::yield_stability_score`: halves for every
+    /// 10% of 30-day yield volatility and is zero if the 30-day yield is negative
+    pub fn yield_stability_score(&self) -> u8 {
+        if self.window_30d.apy_bps < 0 {
+            return 0;
+        }
+        (100 * 1_000 / (1_000 + self.window_30d.volatility_bps as u128)) as u8
+    }
+}
+
+/// Rewards history and derived statistics for one LST
+#[zero_copy]
+pub struct LstYield {
+    pub mint: Pubkey,
+    pub history: RewardsHistory,
+    pub stats: YieldStats,
+}
+
+impl LstYield {
+    pub const LEN: usize = 32 + RewardsHistory::LEN + YieldStats::LEN;
+
+    pub fn new(mint: Pubkey) -> Self {
+        Self {
+            mint,
+            history: RewardsHistory::default(),
+            stats: YieldStats::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_ring_buffer_overwrites_oldest() {
+        let mut history = RewardsHistory::default();
+        for day in 0..(REWARDS_HISTORY_CAPACITY as i64 + 10) {
+            history.record(day * DAY, day, 0, 1);
+        }
+
+        assert_eq!(history.len as usize, REWARDS_HISTORY_CAPACITY);
+        let timestamps: Vec<i64> = history.iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps.first(), Some(&(10 * DAY)));
+        assert_eq!(timestamps.last(), Some(&((REWARDS_HISTORY_CAPACITY as i64 + 9) * DAY)));
+        assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_compounds_within_a_period_are_merged() {
+        let mut history = RewardsHistory::default();
+        history.record(0, 100, 5, 1_000_000);
+        history.record(DAY / 2, 50, 5, 1_000_100);
+        history.record(DAY, 70, 0, 1_000_150);
+
+        assert_eq!(history.len, 2);
+        let first = history.iter().next().unwrap();
+        assert_eq!((first.staking_rewards, first.mev_rewards, first.value), (150, 10, 1_000_000));
+    }
+
+    #[test]
+    fn test_steady_yield_has_no_volatility() {
+        // 1 SOL earning 0.0002 SOL per day is a 7.3% simple annual yield
+        let mut history = RewardsHistory::default();
+        for day in 0..30 {
+            history.record(day * DAY, 200_000, 20_000, 1_000_000_000);
+        }
+
+        let stats = YieldStats::from_history(&history, 30 * DAY);
+        assert_eq!(stats.window_30d.apy_bps, 730);
+        assert_eq!(stats.window_30d.mev_apy_bps, 73);
+        assert_eq!(stats.window_30d.volatility_bps, 0);
+        assert_eq!(stats.window_7d.apy_bps, 730);
+        assert_eq!(stats.yield_stability_score(), 100);
+    }
+
+    #[test]
+    fn test_uneven_yield_lowers_stability() {
+        // Rewards landing every other day, as with two-day epochs
+        let mut history = RewardsHistory::default();
+        for day in 0..30 {
+            let rewards = if day % 2 == 0 { 400_000 } else { 0 };
+            history.record(day * DAY, rewards, 0, 1_000_000_000);
+        }
+
+        let stats = YieldStats::from_history(&history, 30 * DAY);
+        assert_eq!(stats.window_30d.apy_bps, 730);
+        assert_eq!(stats.window_30d.volatility_bps, 730);
+        assert_eq!(stats.yield_stability_score(), 57);
+    }
+}
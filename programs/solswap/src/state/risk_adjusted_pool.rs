@@ -1,22 +1,34 @@
 use anchor_lang::prelude::*;
+use super::{jito_reserves::JitoReserves, liquid_stake_pool::LiquidStakePool, lst_pool_config::LstPoolConfig};
+use crate::error::SwapError;
+use crate::math::weighted_math::BPS_DENOMINATOR;
 
 #[account]
 pub struct RiskAdjustedPool {
     /// Pool authority PDA
     pub authority: Pubkey,
+    /// Bump seed for authority PDA
+    pub bump: u8,
     /// Pool configuration
     pub config: PoolConfig,
     /// LST tokens and their weights
     pub token_weights: Vec<TokenWeight>,
     /// Last rebalance timestamp
     pub last_rebalance: i64,
+    /// Timestamp at which the current weight change starts
+    pub weight_change_start: i64,
+    /// Timestamp at which the current weight change completes
+    pub weight_change_end: i64,
     /// Target weights for each LST
     pub target_weights: Vec<TargetWeight>,
     /// Risk scores for each LST
     pub risk_scores: Vec<RiskScore>,
+    /// SOL value of one LP share (9 decimals), grown only by measured rewards
+    pub virtual_price: u64,
     // Include new fields
     pub jito_reserves: Option<JitoReserves>,
     pub msol_pool: Option<LiquidStakePool>,
+    pub lst_config: Option<LstPoolConfig>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -27,16 +39,98 @@ pub struct PoolConfig {
     pub rebalance_interval: i64,
     /// Maximum deviation from target weights
     pub max_weight_deviation: u8,
+    /// Base swap fee in basis points, retained in the pool for LPs
+    pub swap_fee_bps: u16,
+    /// Minimum duration over which a rebalance moves weights (seconds)
+    pub weight_change_window: i64,
+    /// Maximum change in any weight per second, in millionths of a basis point
+    pub max_weight_change_per_second: u64,
+    /// Maximum value lost to stake pool fees per reserve rebalance (basis points)
+    pub max_rebalance_slippage_bps: u16,
+    /// Minimum time between reward compounds
+    pub min_compound_interval: i64,
+    /// Share of the swap fee, in basis points, added or discounted per basis
+    /// point a trade moves the pool away from or towards its targets
+    pub rebalance_fee_sensitivity: u16,
+    /// Ceiling on the fee charged on any swap (basis points)
+    pub max_swap_fee_bps: u16,
+}
+
+impl PoolConfig {
+    /// Checks the swap fee and its ceiling leave a nonzero share of each trade
+    pub fn validate_fees(&self) -> Result<()> {
+        require!(
+            (self.swap_fee_bps as u128) < BPS_DENOMINATOR && (self.max_swap_fee_bps as u128) < BPS_DENOMINATOR,
+            SwapError::InvalidFee
+        );
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct TokenWeight {
     /// Token mint address
     pub mint: Pubkey,
-    /// Current weight (basis points)
+    /// Weight at the end of the current weight change (basis points)
     pub weight: u16,
+    /// Weight at the start of the current weight change (basis points)
+    pub start_weight: u16,
     /// Amount of tokens in pool
     pub amount: u64,
+    /// SPL stake pool that mints this LST, or default if it is not stake-pool backed
+    pub stake_pool: Pubkey,
+}
+
+impl RiskAdjustedPool {
+    /// Target weight of each token in `token_weights` order, falling back to
+    /// its current end weight where governance has set none
+    pub fn targets(&self) -> Vec<u16> {
+        self.token_weights
+            .iter()
+            .map(|token| {
+                self.target_weights
+                    .iter()
+                    .find(|target| target.mint == token.mint)
+                    .map_or(token.weight, |target| target.target)
+            })
+            .collect()
+    }
+
+    /// Returns the index of `mint` in `token_weights`
+    pub fn token_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.token_weights.iter().position(|t| t.mint == *mint)
+    }
+
+    /// Weight of the token at `index` at time `now`, interpolated linearly
+    /// between its start and end weights over the weight change window
+    pub fn effective_weight(&self, index: usize, now: i64) -> u16 {
+        let token = &self.token_weights[index];
+        if now >= self.weight_change_end || self.weight_change_end <= self.weight_change_start {
+            return token.weight;
+        }
+        if now <= self.weight_change_start {
+            return token.start_weight;
+        }
+
+        let elapsed = (now - self.weight_change_start) as i128;
+        let duration = (self.weight_change_end - self.weight_change_start) as i128;
+        let delta = token.weight as i128 - token.start_weight as i128;
+        (token.start_weight as i128 + delta * elapsed / duration) as u16
+    }
+
+    /// Freezes every token at its effective weight at `now`, ending any
+    /// weight change in progress
+    pub fn checkpoint_weights(&mut self, now: i64) {
+        let weights: Vec<u16> = (0..self.token_weights.len())
+            .map(|i| self.effective_weight(i, now))
+            .collect();
+        for (token, weight) in self.token_weights.iter_mut().zip(weights) {
+            token.start_weight = weight;
+            token.weight = weight;
+        }
+        self.weight_change_start = now;
+        self.weight_change_end = now;
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -62,4 +156,32 @@ pub struct RiskScore {
     // New fields
     pub mev_reliability_score: Option<u8>,      // For JitoSOL
     pub validator_diversity_score: Option<u8>,  // For mSOL
-}
\ No newline at end of file
+}
+
+impl RiskScore {
+    /// Combines the risk components into a single 0-100 score; higher is safer
+    pub fn composite(&self) -> u64 {
+        let base_score = (
+            self.validator_score as u64 +
+            self.security_score as u64 +
+            self.decentralization_score as u64 +
+            self.yield_stability_score as u64
+        ) / 4;
+
+        if let Some(mev_score) = self.mev_reliability_score {
+            // Adjust for JitoSOL
+            (base_score + mev_score as u64) / 2
+        } else if let Some(diversity_score) = self.validator_diversity_score {
+            // Adjust for mSOL
+            (base_score + diversity_score as u64) / 2
+        } else {
+            base_score
+        }
+    }
+
+    /// Risk premium (0-100) charged on trades that add this token to an
+    /// overweight pool
+    pub fn risk_premium(&self) -> u8 {
+        (100 - self.composite().min(100)) as u8
+    }
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use super::rewards_history::{LstYield, RewardsHistory, MAX_TRACKED_LSTS};
+use crate::error::SwapError;
 
 #[account]
 pub struct ValidatorInfo {
@@ -34,23 +36,73 @@ pub struct LstProtocolInfo {
     pub risk_score: u8,
 }
 
-#[account]
+/// Rewards accounting for a `RiskAdjustedPool`.
+///
+/// Zero-copy because the per-LST histories make it larger than an account
+/// can be created with in one instruction: `initialize_reserves_tracker`
+/// creates the first 10 KiB and `grow_reserves_tracker` extends it to `LEN`.
+#[account(zero_copy)]
 pub struct ReservesTracker {
-    /// Authority for updating reserves
-    pub authority: Pubkey,
+    /// Pool whose holdings are tracked, set once the account is full size
+    pub pool: Pubkey,
     /// Last update timestamp
     pub last_update: i64,
     /// Total SOL in reserves
     pub total_reserves: u64,
-    /// Breakdown by LST protocol
-    pub protocol_reserves: Vec<ProtocolReserves>,
     /// Add JitoSOL-specific reserves
     pub jito_mev_rewards: u64,
+    /// Breakdown by LST protocol; the first `protocol_count` entries are used
+    pub protocol_reserves: [ProtocolReserves; MAX_TRACKED_LSTS],
+    /// Pool-wide rewards measured at each compound
+    pub rewards_history: RewardsHistory,
+    /// Per-LST rewards history and rolling yield statistics; the first
+    /// `lst_count` entries are used
+    pub lst_yields: [LstYield; MAX_TRACKED_LSTS],
+    pub protocol_count: u8,
+    pub lst_count: u8,
+    pub _padding: [u8; 6],
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl ReservesTracker {
+    /// PDA seed prefix; the tracker is derived from its pool
+    pub const SEED: &'static [u8] = b"reserves_tracker";
+
+    pub const LEN: usize = 8 + std::mem::size_of::<ReservesTracker>();
+
+    /// Snapshot of `protocol`'s exchange rate, if it has been observed
+    pub fn protocol_reserves_mut(&mut self, protocol: &Pubkey) -> Option<&mut ProtocolReserves> {
+        self.protocol_reserves[..self.protocol_count as usize]
+            .iter_mut()
+            .find(|p| p.protocol == *protocol)
+    }
+
+    /// Starts tracking a protocol's exchange rate
+    pub fn add_protocol_reserves(&mut self, reserves: ProtocolReserves) -> Result<()> {
+        let count = self.protocol_count as usize;
+        require!(count < MAX_TRACKED_LSTS, SwapError::InvalidPoolParameters);
+        self.protocol_reserves[count] = reserves;
+        self.protocol_count += 1;
+        Ok(())
+    }
+
+    /// Index into `lst_yields` of `mint`'s history, starting one if the LST is
+    /// not tracked yet
+    pub fn lst_yield_index(&mut self, mint: &Pubkey) -> Result<usize> {
+        let count = self.lst_count as usize;
+        if let Some(index) = self.lst_yields[..count].iter().position(|y| y.mint == *mint) {
+            return Ok(index);
+        }
+        require!(count < MAX_TRACKED_LSTS, SwapError::InvalidPoolParameters);
+        self.lst_yields[count] = LstYield::new(*mint);
+        self.lst_count += 1;
+        Ok(count)
+    }
+}
+
+#[zero_copy]
+#[derive(Default)]
 pub struct ProtocolReserves {
-    /// Protocol identifier
+    /// Protocol identifier (stake pool address for SPL stake pools)
     pub protocol: Pubkey,
     /// Total SOL backing LSTs
     pub sol_reserves: u64,
@@ -59,3 +111,45 @@ pub struct ProtocolReserves {
     /// Last verification timestamp
     pub last_verified: i64,
 }
+
+impl ProtocolReserves {
+    pub const LEN: usize = 32 + 8 + 8 + 8;
+
+    /// Lamports backing `amount` LST at the recorded rate, or None before the
+    /// first observation
+    pub fn lamports_for(&self, amount: u64) -> Option<u128> {
+        if self.lst_supply == 0 {
+            return None;
+        }
+        Some(amount as u128 * self.sol_reserves as u128 / self.lst_supply as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+
+    #[test]
+    fn test_tracker_needs_growing() {
+        // One `initialize_reserves_tracker` and two `grow_reserves_tracker` calls
+        assert_eq!(ReservesTracker::LEN, 27_920);
+        assert_eq!(ReservesTracker::LEN.div_ceil(MAX_PERMITTED_DATA_INCREASE), 3);
+    }
+
+    #[test]
+    fn test_tracked_lsts_are_bounded() {
+        let mut tracker: ReservesTracker = bytemuck::Zeroable::zeroed();
+        let mints: Vec<Pubkey> = (0..MAX_TRACKED_LSTS).map(|_| Pubkey::new_unique()).collect();
+        for (i, mint) in mints.iter().enumerate() {
+            assert_eq!(tracker.lst_yield_index(mint).unwrap(), i);
+            tracker
+                .add_protocol_reserves(ProtocolReserves { protocol: *mint, ..Default::default() })
+                .unwrap();
+        }
+        assert_eq!(tracker.lst_yield_index(&mints[3]).unwrap(), 3);
+        assert!(tracker.lst_yield_index(&Pubkey::new_unique()).is_err());
+        assert!(tracker.add_protocol_reserves(ProtocolReserves::default()).is_err());
+        assert!(tracker.protocol_reserves_mut(&mints[5]).is_some());
+    }
+}
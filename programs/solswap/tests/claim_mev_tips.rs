@@ -0,0 +1,220 @@
+//! Claims MEV tips through the tip distribution stub and checks how the claim
+//! is split between the protocol fee recipient and LPs, whose share is
+//! wrapped into the pool's wSOL vault.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::token::spl_token;
+use jito_tip_distribution_stub::{TipDistributionAccount, CLAIM_STATUS_SEED};
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Signer;
+use solana_sdk::transaction::Transaction;
+use solswap::instructions::claim_mev_tips::JITO_TIP_DISTRIBUTION_PROGRAM_ID;
+use solswap::state::*;
+
+const TIPS: u64 = 2_000_000;
+const MEV_SHARE_FEE_BPS: u64 = 1_000;
+const WSOL_BALANCE: u64 = 5_000_000;
+
+// Anchor 0.29 entrypoints tie the account slice and infos to one lifetime,
+// which `processor!` cannot take directly
+fn solswap_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solswap::entry(program_id, accounts, data)
+}
+
+fn stub_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    jito_tip_distribution_stub::entry(program_id, accounts, data)
+}
+
+/// Serializes `account` with `spare` bytes of room to grow
+fn anchor_account<T: AccountSerialize>(account: &T, owner: Pubkey, spare: usize) -> SolanaAccount {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data.resize(data.len() + spare, 0);
+    SolanaAccount {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A native (wSOL) token account holding `amount` on top of its rent
+fn wsol_account(owner: Pubkey, amount: u64) -> SolanaAccount {
+    let rent_exempt_reserve = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: spl_token::native_mint::ID,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(rent_exempt_reserve),
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    SolanaAccount {
+        lamports: rent_exempt_reserve + amount,
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn jito_pool(authority: Pubkey, fee_recipient: Pubkey) -> RiskAdjustedPool {
+    RiskAdjustedPool {
+        authority,
+        bump: 255,
+        config: PoolConfig {
+            max_weight: 60,
+            rebalance_interval: 3_600,
+            max_weight_deviation: 10,
+            swap_fee_bps: 4,
+            weight_change_window: 3_600,
+            max_weight_change_per_second: 1,
+            max_rebalance_slippage_bps: 10,
+            min_compound_interval: 86_400,
+            rebalance_fee_sensitivity: 0,
+            max_swap_fee_bps: 100,
+        },
+        token_weights: vec![TokenWeight {
+            mint: spl_token::native_mint::ID,
+            weight: 10_000,
+            start_weight: 10_000,
+            amount: WSOL_BALANCE,
+            stake_pool: Pubkey::default(),
+        }],
+        last_rebalance: 0,
+        weight_change_start: 0,
+        weight_change_end: 0,
+        target_weights: Vec::new(),
+        risk_scores: Vec::new(),
+        virtual_price: 1_000_000_000,
+        jito_reserves: Some(JitoReserves {
+            base_staking_reserves: 0,
+            mev_rewards_reserves: 0,
+            last_mev_distribution: 0,
+            validator_performance: Vec::new(),
+        }),
+        msol_pool: None,
+        lst_config: Some(LstPoolConfig {
+            jito_config: JitoPoolSettings { mev_share_fee: MEV_SHARE_FEE_BPS, fee_recipient },
+            msol_config: MsolPoolSettings { directed_stake_enabled: false },
+            rebalance_threshold: 0,
+            fee_structure: FeeStructure { swap_fee: 0, admin_fee: 0, mev_share_fee: MEV_SHARE_FEE_BPS },
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_claim_splits_tips_between_protocol_and_lps() {
+    let mut program_test = ProgramTest::new("solswap", solswap::ID, processor!(solswap_entry));
+    program_test.add_program(
+        "jito_tip_distribution_stub",
+        JITO_TIP_DISTRIBUTION_PROGRAM_ID,
+        processor!(stub_entry),
+    );
+    program_test.prefer_bpf(false);
+
+    let pool = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let wsol_vault = Pubkey::new_unique();
+    let fee_recipient = Pubkey::new_unique();
+    let validator = Pubkey::new_unique();
+    let (mev_vault, _) = Pubkey::find_program_address(&[b"mev_vault", pool.as_ref()], &solswap::ID);
+    let rent = Rent::default();
+
+    program_test.add_account(pool, anchor_account(&jito_pool(authority, fee_recipient), solswap::ID, 256));
+    program_test.add_account(wsol_vault, wsol_account(authority, WSOL_BALANCE));
+    program_test.add_account(
+        mev_vault,
+        SolanaAccount::new(rent.minimum_balance(0), 0, &system_program::ID),
+    );
+    program_test.add_account(
+        fee_recipient,
+        SolanaAccount::new(rent.minimum_balance(0), 0, &system_program::ID),
+    );
+
+    // A one-leaf tree paying TIPS to the pool's vault
+    let node = hashv(&[&mev_vault.to_bytes(), &TIPS.to_le_bytes()]);
+    let merkle_root = hashv(&[&[0u8], &node.to_bytes()]).to_bytes();
+    let tip_distribution_account = Pubkey::new_unique();
+    let mut tda = anchor_account(
+        &TipDistributionAccount {
+            validator_vote_account: validator,
+            merkle_root,
+            max_total_claim: TIPS,
+            total_funds_claimed: 0,
+        },
+        JITO_TIP_DISTRIBUTION_PROGRAM_ID,
+        0,
+    );
+    tda.lamports += TIPS;
+    program_test.add_account(tip_distribution_account, tda);
+
+    let (claim_status, claim_status_bump) = Pubkey::find_program_address(
+        &[CLAIM_STATUS_SEED, mev_vault.as_ref(), tip_distribution_account.as_ref()],
+        &JITO_TIP_DISTRIBUTION_PROGRAM_ID,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let claim = Instruction {
+        program_id: solswap::ID,
+        accounts: solswap::accounts::ClaimMevTips {
+            pool,
+            mev_vault,
+            wsol_vault,
+            fee_recipient,
+            tip_distribution_config: Pubkey::new_unique(),
+            tip_distribution_account,
+            claim_status,
+            tip_distribution_program: JITO_TIP_DISTRIBUTION_PROGRAM_ID,
+            payer: payer.pubkey(),
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: solswap::instruction::ClaimMevTips { claim_status_bump, amount: TIPS, proof: Vec::new() }.data(),
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&claim),
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let protocol_fee = TIPS * MEV_SHARE_FEE_BPS / 10_000;
+    let lp_share = TIPS - protocol_fee;
+    assert_eq!(banks_client.get_balance(fee_recipient).await.unwrap(), rent.minimum_balance(0) + protocol_fee);
+    assert_eq!(banks_client.get_balance(mev_vault).await.unwrap(), rent.minimum_balance(0));
+
+    // The LPs' share is wrapped into the vault and counted in the pool's balance
+    let vault = banks_client.get_account(wsol_vault).await.unwrap().unwrap();
+    let vault = spl_token::state::Account::unpack(&vault.data).unwrap();
+    assert_eq!(vault.amount, WSOL_BALANCE + lp_share);
+
+    let data = banks_client.get_account(pool).await.unwrap().unwrap().data;
+    let pool_state = RiskAdjustedPool::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(pool_state.token_weights[0].amount, WSOL_BALANCE + lp_share);
+    let jito = pool_state.jito_reserves.unwrap();
+    assert_eq!(jito.mev_rewards_reserves, lp_share);
+    assert_eq!(jito.validator_performance.len(), 1);
+    assert_eq!(jito.validator_performance[0].validator_identity, validator);
+    assert_eq!(jito.validator_performance[0].mev_earned, TIPS);
+
+    // The claim status account makes a second claim of the same tips fail
+    let blockhash = banks_client.get_new_latest_blockhash(&recent_blockhash).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[claim], Some(&payer.pubkey()), &[&payer], blockhash);
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+}
@@ -0,0 +1,307 @@
+//! Takes flash loans from a stableswap pool and checks that they are repaid
+//! with their fee in the same transaction, that the pool is locked until
+//! then, and that loans cannot be taken through CPI.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::InstructionError;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::InstructionData;
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest};
+use solana_sdk::account::Account as SolanaAccount;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solswap::error::SwapError;
+use solswap::state::*;
+
+const BALANCES: [u64; 2] = [5_000_000_000, 3_000_000_000];
+const FEE_BPS: u16 = 4;
+const LOAN: u64 = 1_000_000_000;
+const LOAN_FEE: u64 = LOAN * FEE_BPS as u64 / 10_000;
+const BORROWER_BALANCE: u64 = 10_000_000;
+
+// Anchor 0.29 entrypoints tie the account slice and infos to one lifetime,
+// which `processor!` cannot take directly
+fn solswap_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    solswap::entry(program_id, accounts, data)
+}
+
+/// A borrower program that forwards its instruction to SolSwap through CPI
+fn cpi_borrower_entry(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let metas = accounts[..accounts.len() - 1]
+        .iter()
+        .map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        })
+        .collect();
+    invoke(&Instruction { program_id: solswap::ID, accounts: metas, data: data.to_vec() }, accounts)
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> SolanaAccount {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    SolanaAccount {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+struct Fixture {
+    banks_client: BanksClient,
+    payer: Keypair,
+    borrower: Keypair,
+    cpi_borrower: Pubkey,
+    pool: Pubkey,
+    authority: Pubkey,
+    vaults: [Pubkey; 2],
+    /// Borrower's token accounts, one per pool token
+    accounts: [Pubkey; 2],
+    /// Someone else's account for token 0, where borrowed funds are spent
+    sink: Pubkey,
+}
+
+impl Fixture {
+    async fn new() -> Self {
+        let mut program_test = ProgramTest::new("solswap", solswap::ID, processor!(solswap_entry));
+        let cpi_borrower = Pubkey::new_unique();
+        program_test.add_program("cpi_borrower", cpi_borrower, processor!(cpi_borrower_entry));
+        program_test.prefer_bpf(false);
+
+        let pool = Pubkey::new_unique();
+        let (authority, bump) = Pubkey::find_program_address(&[b"authority", pool.as_ref()], &solswap::ID);
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let state = Pool {
+            authority,
+            bump,
+            tokens: mints.to_vec(),
+            balances: BALANCES.to_vec(),
+            amp: 200,
+            fee_bps: FEE_BPS,
+            admin_fee_bps: 0,
+            lp_supply: 8_000_000_000,
+            active_flash_loan: None,
+        };
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        data.resize(Pool::LEN, 0);
+        program_test.add_account(
+            pool,
+            SolanaAccount {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: solswap::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+
+        let borrower = Keypair::new();
+        let vaults = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for i in 0..2 {
+            program_test.add_account(vaults[i], token_account(mints[i], authority, BALANCES[i]));
+            program_test.add_account(accounts[i], token_account(mints[i], borrower.pubkey(), BORROWER_BALANCE));
+        }
+        let sink = Pubkey::new_unique();
+        program_test.add_account(sink, token_account(mints[0], Pubkey::new_unique(), 0));
+
+        let (banks_client, payer, _) = program_test.start().await;
+        Self { banks_client, payer, borrower, cpi_borrower, pool, authority, vaults, accounts, sink }
+    }
+
+    fn flash_loan(&self, amount: u64) -> Instruction {
+        Instruction {
+            program_id: solswap::ID,
+            accounts: solswap::accounts::FlashLoan {
+                pool: self.pool,
+                pool_authority: self.authority,
+                vault: self.vaults[0],
+                destination: self.accounts[0],
+                borrower: self.borrower.pubkey(),
+                instructions: sysvar::instructions::ID,
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: solswap::instruction::FlashLoan { amount }.data(),
+        }
+    }
+
+    fn flash_repay(&self) -> Instruction {
+        Instruction {
+            program_id: solswap::ID,
+            accounts: solswap::accounts::FlashRepay {
+                pool: self.pool,
+                vault: self.vaults[0],
+                source: self.accounts[0],
+                payer: self.borrower.pubkey(),
+                token_program: spl_token::ID,
+            }
+            .to_account_metas(None),
+            data: solswap::instruction::FlashRepay {}.data(),
+        }
+    }
+
+    /// Swaps token 1 for token 0 against the pool, exact in or exact out
+    fn swap(&self, exact_out: bool) -> Instruction {
+        let mut accounts = solswap::accounts::SwapRoute {
+            user_token_in: self.accounts[1],
+            user_token_out: self.accounts[0],
+            user: self.borrower.pubkey(),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend([
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new_readonly(self.authority, false),
+            AccountMeta::new(self.vaults[1], false),
+            AccountMeta::new(self.vaults[0], false),
+        ]);
+        let data = if exact_out {
+            solswap::instruction::SwapExactOut { amount_out: 1_000_000, max_amount_in: u64::MAX }.data()
+        } else {
+            solswap::instruction::SwapRoute { amount_in: 1_000_000, min_amount_out: 1 }.data()
+        };
+        Instruction { program_id: solswap::ID, accounts, data }
+    }
+
+    /// Moves `amount` of token 0 out of the borrower's account, as if spent
+    fn spend(&self, amount: u64) -> Instruction {
+        spl_token::instruction::transfer(
+            &spl_token::ID,
+            &self.accounts[0],
+            &self.sink,
+            &self.borrower.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap()
+    }
+
+    async fn process(&mut self, instructions: &[Instruction]) -> std::result::Result<(), TransactionError> {
+        let blockhash = self.banks_client.get_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &[&self.payer, &self.borrower],
+            blockhash,
+        );
+        self.banks_client.process_transaction(transaction).await.map_err(|err| err.unwrap())
+    }
+
+    async fn pool(&mut self) -> Pool {
+        let data = self.banks_client.get_account(self.pool).await.unwrap().unwrap().data;
+        Pool::try_deserialize(&mut &data[..]).unwrap()
+    }
+
+    async fn balance(&mut self, account: Pubkey) -> u64 {
+        let account = self.banks_client.get_account(account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+}
+
+fn custom_error(index: u8, error: SwapError) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(error.into()))
+}
+
+#[tokio::test]
+async fn test_loan_repaid_with_fee_in_same_transaction() {
+    let mut fixture = Fixture::new().await;
+    let (loan, repay) = (fixture.flash_loan(LOAN), fixture.flash_repay());
+    fixture.process(&[loan, repay]).await.unwrap();
+
+    // The fee stays in the vault and is added to the pool's balance
+    assert_eq!(fixture.balance(fixture.vaults[0]).await, BALANCES[0] + LOAN_FEE);
+    assert_eq!(fixture.balance(fixture.accounts[0]).await, BORROWER_BALANCE - LOAN_FEE);
+    let pool = fixture.pool().await;
+    assert_eq!(pool.balances, vec![BALANCES[0] + LOAN_FEE, BALANCES[1]]);
+    assert!(pool.active_flash_loan.is_none());
+
+    // The pool is unlocked again for later transactions
+    let swap = fixture.swap(false);
+    fixture.process(&[swap]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_loan_without_repay_is_rejected() {
+    let mut fixture = Fixture::new().await;
+    let loan = fixture.flash_loan(LOAN);
+    assert_eq!(
+        fixture.process(std::slice::from_ref(&loan)).await.unwrap_err(),
+        custom_error(0, SwapError::MissingFlashRepay)
+    );
+
+    // A repay placed before the loan does not cover it
+    let repay = fixture.flash_repay();
+    assert_eq!(
+        fixture.process(&[repay, loan]).await.unwrap_err(),
+        custom_error(0, SwapError::NoFlashLoan)
+    );
+    assert_eq!(fixture.balance(fixture.vaults[0]).await, BALANCES[0]);
+}
+
+#[tokio::test]
+async fn test_short_repay_reverts_loan() {
+    let mut fixture = Fixture::new().await;
+    // The borrower spends more than its own balance can cover
+    let instructions = [
+        fixture.flash_loan(LOAN),
+        fixture.spend(BORROWER_BALANCE + 1),
+        fixture.flash_repay(),
+    ];
+    let err = fixture.process(&instructions).await.unwrap_err();
+    assert!(matches!(err, TransactionError::InstructionError(2, _)), "{err:?}");
+
+    assert_eq!(fixture.balance(fixture.vaults[0]).await, BALANCES[0]);
+    assert_eq!(fixture.balance(fixture.accounts[0]).await, BORROWER_BALANCE);
+    assert!(fixture.pool().await.active_flash_loan.is_none());
+}
+
+#[tokio::test]
+async fn test_swaps_rejected_during_loan() {
+    let mut fixture = Fixture::new().await;
+    for exact_out in [false, true] {
+        let instructions = [fixture.flash_loan(LOAN), fixture.swap(exact_out), fixture.flash_repay()];
+        assert_eq!(
+            fixture.process(&instructions).await.unwrap_err(),
+            custom_error(1, SwapError::FlashLoanActive)
+        );
+    }
+
+    // A second loan cannot be stacked on the first either
+    let instructions = [fixture.flash_loan(LOAN), fixture.flash_loan(LOAN), fixture.flash_repay()];
+    assert_eq!(
+        fixture.process(&instructions).await.unwrap_err(),
+        custom_error(1, SwapError::FlashLoanActive)
+    );
+}
+
+#[tokio::test]
+async fn test_loan_through_cpi_is_rejected() {
+    let mut fixture = Fixture::new().await;
+    let mut loan = fixture.flash_loan(LOAN);
+    loan.program_id = fixture.cpi_borrower;
+    loan.accounts.push(AccountMeta::new_readonly(solswap::ID, false));
+    let repay = fixture.flash_repay();
+    assert_eq!(
+        fixture.process(&[loan, repay]).await.unwrap_err(),
+        custom_error(0, SwapError::FlashLoanCpiForbidden)
+    );
+    assert_eq!(fixture.balance(fixture.vaults[0]).await, BALANCES[0]);
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::error::ErrorCode;
+use crate::events::{DepegConfigUpdated, StatusChangeReason, TradingStatusChanged};
+use crate::state::depeg::*;
+use crate::state::oracle::OracleState;
+use crate::state::pool::{PoolState, TradingStatus};
+use crate::state::reserves::read_reported_reserves;
+
+/// Context for the permissionless depeg check of a pool's LST
+#[derive(Accounts)]
+pub struct CheckDepeg<'info> {
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// EMA of the LST's market price
+    #[account(address = pool_state.oracle)]
+    pub oracle_state: Box<Account<'info, OracleState>>,
+
+    #[account(address = pool_state.lst_mint @ ErrorCode::InvalidLSTMint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK: Parsed as an SPL stake pool or Marinade state depending on owner
+    #[account(address = pool_state.protocol_reserves.protocol @ ErrorCode::InvalidProtocolState)]
+    pub protocol_state: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> CheckDepeg<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        let now = self.clock.unix_timestamp;
+        require!(
+            now - self.oracle_state.last_update_ts <= MAX_ORACLE_AGE,
+            ErrorCode::StaleOracle
+        );
+
+        let reported = read_reported_reserves(&self.protocol_state, &self.lst_mint.key())?;
+        let fair_price = fair_price(&reported)?;
+        let discount = depeg_discount(self.oracle_state.ema_price, fair_price);
+
+        let pool_state = &mut self.pool_state;
+        let status = pool_state.depeg_config.status_for(discount);
+
+        // Recovery does not lift restrictions automatically; that is left to governance
+        if let Some(previous) = pool_state.escalate_trading_status(status) {
+            msg!("LST trading at {} bps below fair value", discount);
+            emit!(TradingStatusChanged {
+                pool: pool_state.key(),
+                previous,
+                status,
+                reason: StatusChangeReason::Depeg,
+                metric: discount,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Context for governance control of a pool's trading status and depeg
+/// thresholds
+#[derive(Accounts)]
+pub struct GovernTrading<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub authority: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> GovernTrading<'info> {
+    /// Sets the trading status directly, including lifting a restriction
+    pub fn set_trading_status(&mut self, status: TradingStatus) -> Result<()> {
+        let previous = self.pool_state.trading_status;
+        if previous == status {
+            return Ok(());
+        }
+
+        self.pool_state.trading_status = status;
+        emit!(TradingStatusChanged {
+            pool: self.pool_state.key(),
+            previous,
+            status,
+            reason: StatusChangeReason::Governance,
+            metric: 0,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn set_depeg_config(&mut self, config: DepegConfig) -> Result<()> {
+        config.validate()?;
+        self.pool_state.depeg_config = config;
+        emit!(DepegConfigUpdated {
+            pool: self.pool_state.key(),
+            config,
+            timestamp: self.clock.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use crate::error::ErrorCode;
+use crate::events::PoolInitialized;
+use crate::program::Solswap;
+use crate::state::pool::PoolState;
+
+/// Context for creating the pool for an LST and its token accounts. Signed by
+/// the program's upgrade authority, which names the pool's governance
+/// authority, so a pool cannot be claimed by whoever creates it first.
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = PoolState::LEN,
+        seeds = [PoolState::SEED, lst_mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub lst_mint: Account<'info, Mint>,
+
+    #[account(address = token::spl_token::native_mint::ID @ ErrorCode::InvalidPoolAccount)]
+    pub native_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"sol_reserve", pool_state.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool_state,
+    )]
+    pub sol_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"lst_reserve", pool_state.key().as_ref()],
+        bump,
+        token::mint = lst_mint,
+        token::authority = pool_state,
+    )]
+    pub lst_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"fee_account", pool_state.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool_state,
+    )]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidAuthority)]
+    pub program: Program<'info, Solswap>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ ErrorCode::InvalidAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitializePool<'info> {
+    pub fn process(&mut self, authority: Pubkey, protocol: Pubkey, oracle: Pubkey, bump: u8) -> Result<()> {
+        let lst_mint = self.lst_mint.key();
+        let mut pool_state = PoolState::new(bump, authority, lst_mint, protocol, oracle);
+        pool_state.set_token_accounts(self.sol_reserve.key(), self.lst_reserve.key(), self.fee_account.key());
+        self.pool_state.set_inner(pool_state);
+
+        emit!(PoolInitialized {
+            pool: self.pool_state.key(),
+            lst_mint,
+            authority,
+            protocol,
+            oracle,
+        });
+        Ok(())
+    }
+}
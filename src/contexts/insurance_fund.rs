@@ -0,0 +1,567 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
+use crate::error::ErrorCode;
+use crate::events::{
+    FeesRouted, InsuranceDeposited, InsuranceEventTriggered, InsuranceFundInitialized,
+    InsuranceParamsUpdated, InsuranceWithdrawalRequested, InsuranceWithdrawn,
+};
+use crate::state::{insurance_fund::*, pool::PoolState};
+
+/// Context for creating a pool's insurance fund, its vault and share mint
+#[derive(Accounts)]
+pub struct InitializeInsuranceFund<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = InsuranceFund::LEN,
+        seeds = [b"insurance_fund", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(has_one = authority @ ErrorCode::InvalidAuthority)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub asset_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"insurance_vault", insurance_fund.key().as_ref()],
+        bump,
+        token::mint = asset_mint,
+        token::authority = insurance_fund,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"insurance_shares", insurance_fund.key().as_ref()],
+        bump,
+        mint::decimals = asset_mint.decimals,
+        mint::authority = insurance_fund,
+    )]
+    pub share_mint: Account<'info, Mint>,
+
+    /// Holds shares of pending withdrawals
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"insurance_escrow", insurance_fund.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = insurance_fund,
+    )]
+    pub share_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitializeInsuranceFund<'info> {
+    pub fn process(&mut self, withdrawal_cooldown: i64, bump: u8) -> Result<()> {
+        require!(withdrawal_cooldown >= 0, ErrorCode::InvalidInsuranceParams);
+
+        self.insurance_fund.set_inner(InsuranceFund::new(
+            self.authority.key(),
+            bump,
+            self.pool_state.key(),
+            self.asset_mint.key(),
+            self.vault.key(),
+            self.share_mint.key(),
+            withdrawal_cooldown,
+        ));
+
+        emit!(InsuranceFundInitialized {
+            insurance_fund: self.insurance_fund.key(),
+            pool: self.pool_state.key(),
+            authority: self.authority.key(),
+            asset_mint: self.asset_mint.key(),
+            vault: self.vault.key(),
+            share_mint: self.share_mint.key(),
+            withdrawal_cooldown,
+        });
+        Ok(())
+    }
+}
+
+/// Context for underwriting the pool by depositing into its insurance fund
+#[derive(Accounts)]
+pub struct DepositInsurance<'info> {
+    #[account(
+        mut,
+        has_one = vault,
+        has_one = share_mint,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = depositor_assets.owner == depositor.key())]
+    pub depositor_assets: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = depositor_shares.mint == share_mint.key())]
+    pub depositor_shares: Account<'info, TokenAccount>,
+
+    pub depositor: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> DepositInsurance<'info> {
+    pub fn process(&mut self, amount: u64) -> Result<()> {
+        let shares = self.insurance_fund.deposit(amount)?;
+
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.depositor_assets.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.depositor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let pool = self.insurance_fund.pool;
+        let seeds = &[b"insurance_fund".as_ref(), pool.as_ref(), &[self.insurance_fund.bump]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.share_mint.to_account_info(),
+                    to: self.depositor_shares.to_account_info(),
+                    authority: self.insurance_fund.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            shares,
+        )?;
+
+        emit!(InsuranceDeposited {
+            insurance_fund: self.insurance_fund.key(),
+            depositor: self.depositor.key(),
+            amount,
+            shares,
+            total_assets: self.insurance_fund.total_assets,
+            total_shares: self.insurance_fund.total_shares,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for starting a withdrawal; shares move into escrow for the cooldown
+#[derive(Accounts)]
+pub struct RequestInsuranceWithdrawal<'info> {
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = InsuranceWithdrawal::LEN,
+        seeds = [InsuranceWithdrawal::SEED, insurance_fund.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub withdrawal: Account<'info, InsuranceWithdrawal>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_escrow", insurance_fund.key().as_ref()],
+        bump,
+    )]
+    pub share_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = owner_shares.owner == owner.key())]
+    pub owner_shares: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> RequestInsuranceWithdrawal<'info> {
+    pub fn process(&mut self, shares: u64, bump: u8) -> Result<()> {
+        require!(shares > 0, ErrorCode::InvalidInsuranceParams);
+
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.owner_shares.to_account_info(),
+                    to: self.share_escrow.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        *self.withdrawal = InsuranceWithdrawal {
+            fund: self.insurance_fund.key(),
+            owner: self.owner.key(),
+            shares,
+            unlock_ts: self
+                .clock
+                .unix_timestamp
+                .checked_add(self.insurance_fund.withdrawal_cooldown)
+                .ok_or(ErrorCode::MathOverflow)?,
+            bump,
+        };
+
+        emit!(InsuranceWithdrawalRequested {
+            insurance_fund: self.insurance_fund.key(),
+            owner: self.owner.key(),
+            shares,
+            unlock_ts: self.withdrawal.unlock_ts,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for completing a withdrawal once its cooldown has passed. Shares are
+/// redeemed at the price at completion, so losses during the cooldown are shared.
+#[derive(Accounts)]
+pub struct WithdrawInsurance<'info> {
+    #[account(
+        mut,
+        has_one = vault,
+        has_one = share_mint,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(
+        mut,
+        seeds = [InsuranceWithdrawal::SEED, insurance_fund.key().as_ref(), owner.key().as_ref()],
+        bump = withdrawal.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub withdrawal: Account<'info, InsuranceWithdrawal>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub share_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"insurance_escrow", insurance_fund.key().as_ref()],
+        bump,
+    )]
+    pub share_escrow: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = owner_assets.mint == insurance_fund.asset_mint)]
+    pub owner_assets: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> WithdrawInsurance<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        require!(
+            self.clock.unix_timestamp >= self.withdrawal.unlock_ts,
+            ErrorCode::WithdrawalCooldown
+        );
+
+        let shares = self.withdrawal.shares;
+        let assets = self.insurance_fund.withdraw(shares)?;
+
+        let pool = self.insurance_fund.pool;
+        let seeds = &[b"insurance_fund".as_ref(), pool.as_ref(), &[self.insurance_fund.bump]];
+        token::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.share_mint.to_account_info(),
+                    from: self.share_escrow.to_account_info(),
+                    authority: self.insurance_fund.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            shares,
+        )?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.owner_assets.to_account_info(),
+                    authority: self.insurance_fund.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            assets,
+        )?;
+
+        emit!(InsuranceWithdrawn {
+            insurance_fund: self.insurance_fund.key(),
+            owner: self.owner.key(),
+            shares,
+            assets,
+            total_assets: self.insurance_fund.total_assets,
+            total_shares: self.insurance_fund.total_shares,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for governance paying out the insurance fund after a verified
+/// reserve shortfall or slashing of the pool's LST
+#[derive(Accounts)]
+pub struct TriggerInsuranceEvent<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+        has_one = vault,
+        constraint = insurance_fund.pool == pool_state.key() @ ErrorCode::InvalidInsuranceParams,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// The pool's LST holdings, whose value is insured
+    #[account(address = pool_state.lst_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub pool_lst_reserve: Account<'info, TokenAccount>,
+
+    /// LP liquidity receiving the payout
+    #[account(
+        mut,
+        address = pool_state.sol_reserve @ ErrorCode::InvalidPoolAccount,
+        constraint = pool_recovery_account.mint == insurance_fund.asset_mint,
+    )]
+    pub pool_recovery_account: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> TriggerInsuranceEvent<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        let now = self.clock.unix_timestamp;
+        let pool_state = &mut self.pool_state;
+
+        // Claims are only made against a fresh on-chain verification
+        require!(pool_state.protocol_reserves.is_fresh(now), ErrorCode::ReservesNotVerified);
+
+        let reserve_ratio = pool_state.lst_performance_metrics.reserve_ratio;
+        let insured_value = pool_state
+            .protocol_reserves
+            .lamports_for(self.pool_lst_reserve.amount)
+            .ok_or(ErrorCode::ReservesNotVerified)?;
+        let claim = calculate_insurance_payout(
+            u64::try_from(insured_value).map_err(|_| ErrorCode::MathOverflow)?,
+            pool_state.compensated_reserve_ratio,
+            reserve_ratio,
+            self.insurance_fund.total_assets,
+            self.insurance_fund.max_claim,
+        )?;
+        require!(claim.loss > 0, ErrorCode::NoInsuredLoss);
+
+        pool_state.compensated_reserve_ratio = reserve_ratio;
+        pool_state.record_deposit(claim.payout)?;
+        self.insurance_fund.deploy(claim.payout)?;
+
+        let pool = self.insurance_fund.pool;
+        let seeds = &[b"insurance_fund".as_ref(), pool.as_ref(), &[self.insurance_fund.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.pool_recovery_account.to_account_info(),
+                    authority: self.insurance_fund.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            claim.payout,
+        )?;
+
+        emit!(InsuranceEventTriggered {
+            insurance_fund: self.insurance_fund.key(),
+            pool,
+            reserve_ratio,
+            loss: claim.loss,
+            payout: claim.payout,
+            total_assets: self.insurance_fund.total_assets,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for the permissionless split of collected swap fees between LPs and
+/// the insurance fund
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(
+        mut,
+        seeds = [PoolState::SEED, pool_state.lst_mint.as_ref()],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        has_one = vault,
+        constraint = insurance_fund.pool == pool_state.key() @ ErrorCode::InvalidInsuranceParams,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Swap fees collected since the last sweep
+    #[account(
+        mut,
+        address = pool_state.fee_account @ ErrorCode::InvalidPoolAccount,
+        constraint = fee_account.mint == insurance_fund.asset_mint,
+    )]
+    pub fee_account: Account<'info, TokenAccount>,
+
+    /// LP liquidity that receives the LPs' share
+    #[account(mut, address = pool_state.sol_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub lp_reserve: Account<'info, TokenAccount>,
+
+    /// The pool's LST holdings, valued to size the allocation
+    #[account(address = pool_state.lst_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub pool_lst_reserve: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> SweepFees<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        // Pool liquidity is the LP reserve plus LST holdings at the verified rate
+        let lst_value = self
+            .pool_state
+            .protocol_reserves
+            .lamports_for(self.pool_lst_reserve.amount)
+            .ok_or(ErrorCode::ReservesNotVerified)?;
+        let pool_liquidity = u64::try_from(lst_value + self.lp_reserve.amount as u128)
+            .map_err(|_| ErrorCode::MathOverflow)?;
+
+        let fees = self.fee_account.amount;
+        let route = route_fees(&self.insurance_fund, fees, pool_liquidity)?;
+
+        let lst_mint = self.pool_state.lst_mint;
+        let pool_seeds = &[PoolState::SEED, lst_mint.as_ref(), &[self.pool_state.bump]];
+        self.transfer_fees(&self.vault, route.to_insurance, pool_seeds)?;
+        self.transfer_fees(&self.lp_reserve, route.to_lps, pool_seeds)?;
+
+        if route.released > 0 {
+            let pool = self.insurance_fund.pool;
+            let fund_seeds = &[b"insurance_fund".as_ref(), pool.as_ref(), &[self.insurance_fund.bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.vault.to_account_info(),
+                        to: self.lp_reserve.to_account_info(),
+                        authority: self.insurance_fund.to_account_info(),
+                    },
+                    &[&fund_seeds[..]],
+                ),
+                route.released,
+            )?;
+        }
+
+        self.insurance_fund.contribute_fees(route.to_insurance)?;
+        self.insurance_fund.release_fees(route.released)?;
+        self.pool_state.record_deposit(route.to_lps + route.released)?;
+
+        emit!(FeesRouted {
+            pool: self.pool_state.key(),
+            fees,
+            to_insurance: route.to_insurance,
+            to_lps: route.to_lps,
+            released: route.released,
+            total_assets: self.insurance_fund.total_assets,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Moves collected fees out of the pool's fee account
+    fn transfer_fees(
+        &self,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        pool_seeds: &[&[u8]],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.fee_account.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.pool_state.to_account_info(),
+                },
+                &[pool_seeds],
+            ),
+            amount,
+        )
+    }
+}
+
+/// Context for governance changes to insurance claim and fee parameters
+#[derive(Accounts)]
+pub struct SetInsuranceParams<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub insurance_fund: Box<Account<'info, InsuranceFund>>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> SetInsuranceParams<'info> {
+    pub fn process(&mut self, fee_share: u16, max_claim: u16) -> Result<()> {
+        require!(fee_share <= 10_000, ErrorCode::InvalidFeeConfig);
+        require!(max_claim <= 10_000, ErrorCode::InvalidInsuranceParams);
+
+        self.insurance_fund.fee_share = fee_share;
+        self.insurance_fund.max_claim = max_claim;
+
+        emit!(InsuranceParamsUpdated {
+            insurance_fund: self.insurance_fund.key(),
+            fee_share,
+            max_claim,
+        });
+        Ok(())
+    }
+}
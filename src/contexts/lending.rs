@@ -0,0 +1,369 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::error::ErrorCode;
+use crate::events::{LendingRebalanced, LendingStrategyInitialized};
+use crate::state::lending::*;
+use crate::state::pool::PoolState;
+
+/// Anchor discriminator of the lending market's `deposit` instruction
+const DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// Anchor discriminator of the lending market's `withdraw` instruction
+const WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+
+/// Context for attaching a lending strategy to a pool
+#[derive(Accounts)]
+pub struct InitializeLendingStrategy<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = LendingStrategy::LEN,
+        seeds = [LendingStrategy::SEED, pool_state.key().as_ref()],
+        bump,
+    )]
+    pub lending_strategy: Box<Account<'info, LendingStrategy>>,
+
+    #[account(has_one = authority @ ErrorCode::InvalidAuthority)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// CHECK: Only invoked through the strategy's CPIs
+    #[account(executable)]
+    pub lending_program: UncheckedAccount<'info>,
+
+    /// CHECK: Balances are read at fixed offsets; ownership ties it to the program
+    #[account(owner = lending_program.key() @ ErrorCode::InvalidLendingReserve)]
+    pub reserve: UncheckedAccount<'info>,
+
+    pub collateral_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"lending_collateral", lending_strategy.key().as_ref()],
+        bump,
+        token::mint = collateral_mint,
+        token::authority = pool_state,
+    )]
+    pub collateral_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitializeLendingStrategy<'info> {
+    pub fn process(&mut self, max_allocation: u16, liquidity_buffer: u16, bump: u8) -> Result<()> {
+        LendingStrategy::validate_params(max_allocation, liquidity_buffer)?;
+
+        self.lending_strategy.set_inner(LendingStrategy {
+            pool: self.pool_state.key(),
+            lending_program: self.lending_program.key(),
+            reserve: self.reserve.key(),
+            collateral_account: self.collateral_account.key(),
+            max_allocation,
+            liquidity_buffer,
+            principal: 0,
+            borrowed_value: 0,
+            total_yield: 0,
+            bump,
+        });
+
+        emit!(LendingStrategyInitialized {
+            lending_strategy: self.lending_strategy.key(),
+            pool: self.pool_state.key(),
+            lending_program: self.lending_program.key(),
+            reserve: self.reserve.key(),
+            collateral_account: self.collateral_account.key(),
+            max_allocation,
+            liquidity_buffer,
+        });
+        Ok(())
+    }
+}
+
+/// Context for the permissionless rebalance of a pool's lending position
+#[derive(Accounts)]
+pub struct RebalanceLending<'info> {
+    #[account(
+        mut,
+        seeds = [PoolState::SEED, pool_state.lst_mint.as_ref()],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [LendingStrategy::SEED, pool_state.key().as_ref()],
+        bump = lending_strategy.bump,
+    )]
+    pub lending_strategy: Box<Account<'info, LendingStrategy>>,
+
+    #[account(mut, address = pool_state.lst_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub lst_reserve: Account<'info, TokenAccount>,
+
+    /// CHECK: Checked against the strategy
+    #[account(address = lending_strategy.lending_program)]
+    pub lending_program: UncheckedAccount<'info>,
+
+    /// CHECK: Checked against the strategy
+    #[account(mut, address = lending_strategy.reserve @ ErrorCode::InvalidLendingReserve)]
+    pub reserve: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lending program
+    #[account(mut)]
+    pub reserve_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lending program
+    #[account(mut)]
+    pub collateral_mint: UncheckedAccount<'info>,
+
+    #[account(mut, address = lending_strategy.collateral_account)]
+    pub collateral_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> RebalanceLending<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        let market = LendingMarket {
+            program: self.lending_program.to_account_info(),
+            reserve: self.reserve.to_account_info(),
+            reserve_vault: self.reserve_vault.to_account_info(),
+            collateral_mint: self.collateral_mint.to_account_info(),
+            collateral_account: self.collateral_account.to_account_info(),
+        };
+        let lst_reserve = self.lst_reserve.to_account_info();
+        let token_program = self.token_program.to_account_info();
+
+        let harvested = harvest_yield(
+            &mut self.pool_state,
+            &mut self.lending_strategy,
+            &market,
+            &lst_reserve,
+            &token_program,
+        )?;
+        self.lst_reserve.reload()?;
+        self.collateral_account.reload()?;
+
+        let (deposited, withdrawn) = match self.lending_strategy.rebalance_action(self.lst_reserve.amount) {
+            LendingAction::Deposit(amount) => {
+                let amount = self.lendable(amount)?;
+                if amount > 0 {
+                    let value = lamport_value(&self.pool_state, amount)?;
+                    self.pool_state.record_borrow(value)?;
+                    self.lending_strategy.record_deposit(amount, value)?;
+                    market.deposit(&self.pool_state, &lst_reserve, &token_program, amount)?;
+                }
+                (amount, 0)
+            }
+            LendingAction::Withdraw(amount) => {
+                let withdrawn = withdraw_principal(
+                    &mut self.pool_state,
+                    &mut self.lending_strategy,
+                    &market,
+                    &lst_reserve,
+                    &token_program,
+                    amount,
+                )?;
+                (0, withdrawn)
+            }
+            LendingAction::Hold => (0, 0),
+        };
+
+        emit!(LendingRebalanced {
+            pool: self.pool_state.key(),
+            deposited,
+            withdrawn,
+            harvested,
+            principal: self.lending_strategy.principal,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Caps a deposit at the pool's unlent liquidity and at the LST the
+    /// reserve can spare above its liquidity buffer
+    fn lendable(&self, amount: u64) -> Result<u64> {
+        let rate = &self.pool_state.protocol_reserves;
+        require!(rate.sol_reserves > 0, ErrorCode::ReservesNotVerified);
+        let available = self.pool_state.available_liquidity() as u128 * rate.lst_supply as u128
+            / rate.sol_reserves as u128;
+        let spare = self.lending_strategy.deposit_limit(self.lst_reserve.amount);
+        Ok(amount.min(available as u64).min(spare))
+    }
+}
+
+/// Lending market accounts used by the strategy's CPIs
+pub struct LendingMarket<'info> {
+    pub program: AccountInfo<'info>,
+    pub reserve: AccountInfo<'info>,
+    pub reserve_vault: AccountInfo<'info>,
+    pub collateral_mint: AccountInfo<'info>,
+    pub collateral_account: AccountInfo<'info>,
+}
+
+impl<'info> LendingMarket<'info> {
+    /// Reads the market accounts from `accounts` in the order of the fields,
+    /// checking them against `strategy`
+    pub fn from_accounts(strategy: &LendingStrategy, accounts: &[AccountInfo<'info>]) -> Result<Self> {
+        require!(accounts.len() >= 5, ErrorCode::InvalidLendingReserve);
+        require!(
+            accounts[0].key() == strategy.lending_program
+                && accounts[1].key() == strategy.reserve
+                && accounts[4].key() == strategy.collateral_account,
+            ErrorCode::InvalidLendingReserve
+        );
+        Ok(Self {
+            program: accounts[0].clone(),
+            reserve: accounts[1].clone(),
+            reserve_vault: accounts[2].clone(),
+            collateral_mint: accounts[3].clone(),
+            collateral_account: accounts[4].clone(),
+        })
+    }
+
+    pub fn balances(&self) -> Result<ReserveBalances> {
+        ReserveBalances::read(&self.reserve)
+    }
+
+    pub fn collateral_held(&self) -> Result<u64> {
+        let data = self.collateral_account.try_borrow_data()?;
+        Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+    }
+
+    pub fn deposit(
+        &self,
+        pool_state: &Account<'info, PoolState>,
+        source: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        let accounts = [
+            self.reserve.clone(),
+            self.reserve_vault.clone(),
+            self.collateral_mint.clone(),
+            source.clone(),
+            self.collateral_account.clone(),
+            pool_state.to_account_info(),
+            token_program.clone(),
+        ];
+        self.invoke(DEPOSIT_DISCRIMINATOR, amount, &accounts, pool_state)
+    }
+
+    pub fn withdraw(
+        &self,
+        pool_state: &Account<'info, PoolState>,
+        destination: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+        collateral: u64,
+    ) -> Result<()> {
+        let accounts = [
+            self.reserve.clone(),
+            self.reserve_vault.clone(),
+            self.collateral_mint.clone(),
+            self.collateral_account.clone(),
+            destination.clone(),
+            pool_state.to_account_info(),
+            token_program.clone(),
+        ];
+        self.invoke(WITHDRAW_DISCRIMINATOR, collateral, &accounts, pool_state)
+    }
+
+    fn invoke(
+        &self,
+        discriminator: [u8; 8],
+        amount: u64,
+        accounts: &[AccountInfo<'info>],
+        pool_state: &Account<'info, PoolState>,
+    ) -> Result<()> {
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+
+        let pool_key = pool_state.key();
+        let metas = accounts
+            .iter()
+            .map(|account| match account.key() {
+                key if key == pool_key => AccountMeta::new_readonly(key, true),
+                key if key == anchor_spl::token::ID => AccountMeta::new_readonly(key, false),
+                key => AccountMeta::new(key, false),
+            })
+            .collect();
+        let instruction = Instruction {
+            program_id: self.program.key(),
+            accounts: metas,
+            data,
+        };
+
+        let lst_mint = pool_state.lst_mint;
+        let seeds = &[PoolState::SEED, lst_mint.as_ref(), &[pool_state.bump]];
+        invoke_signed(&instruction, accounts, &[&seeds[..]])?;
+        Ok(())
+    }
+}
+
+/// Lamports backing `amount` LST at the pool's verified rate
+fn lamport_value(pool_state: &PoolState, amount: u64) -> Result<u64> {
+    let value = pool_state
+        .protocol_reserves
+        .lamports_for(amount)
+        .ok_or(ErrorCode::ReservesNotVerified)?;
+    u64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Redeems accrued interest into the pool reserve and credits it to LPs,
+/// returning the LST harvested
+pub fn harvest_yield<'info>(
+    pool_state: &mut Account<'info, PoolState>,
+    strategy: &mut LendingStrategy,
+    market: &LendingMarket<'info>,
+    lst_reserve: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<u64> {
+    let balances = market.balances()?;
+    let held = market.collateral_held()?;
+    let interest = balances.position_value(held).saturating_sub(strategy.principal);
+    let collateral = balances.collateral_within(interest);
+    if collateral == 0 {
+        return Ok(0);
+    }
+
+    let harvested = balances.position_value(collateral);
+    market.withdraw(pool_state, lst_reserve, token_program, collateral)?;
+    strategy.total_yield = strategy.total_yield.saturating_add(harvested);
+    let value = lamport_value(pool_state, harvested)?;
+    pool_state.record_repayment(0, value)?;
+
+    Ok(harvested)
+}
+
+/// Withdraws at least `amount` principal into the pool reserve, crediting any
+/// interest redeemed alongside it to LPs, and returns the LST received
+pub fn withdraw_principal<'info>(
+    pool_state: &mut Account<'info, PoolState>,
+    strategy: &mut LendingStrategy,
+    market: &LendingMarket<'info>,
+    lst_reserve: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<u64> {
+    let balances = market.balances()?;
+    let collateral = balances.collateral_for(amount, market.collateral_held()?);
+    if collateral == 0 {
+        return Ok(0);
+    }
+
+    let received = balances.position_value(collateral);
+    market.withdraw(pool_state, lst_reserve, token_program, collateral)?;
+
+    let (released, interest) = strategy.record_redemption(received);
+    let interest = lamport_value(pool_state, interest)?;
+    pool_state.record_repayment(released, interest)?;
+
+    Ok(received)
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::events::{FeeConfigUpdated, LiquidityParametersUpdated};
 use crate::state::{pool::PoolState, insurance_fund::InsuranceFund};
 use crate::state::liquidity_protection::*;
 
@@ -7,7 +9,10 @@ pub struct LiquidityProtection<'info> {
     #[account(mut)]
     pub pool_state: Account<'info, PoolState>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = insurance_fund.pool == pool_state.key() @ ErrorCode::InvalidInsuranceParams,
+    )]
     pub insurance_fund: Account<'info, InsuranceFund>,
 
     pub clock: Sysvar<'info, Clock>,
@@ -28,12 +33,20 @@ impl<'info> LiquidityProtection<'info> {
             utilization,
         )?;
 
-        // Update dynamic fee rate
-        self.pool_state.fee_rate = calculate_dynamic_fee(
+        // Move the dynamic fee rate towards its target within the pool's
+        // bounds, a limited step at a time
+        let target_fee = calculate_dynamic_fee(
             utilization,
             risk_level,
             self.pool_state.base_fee,
-        )?;
+        )?
+        .clamp(self.pool_state.min_fee, self.pool_state.max_fee);
+        let previous_fee_rate = self.pool_state.fee_rate;
+        self.pool_state.fee_rate = step_fee(
+            self.pool_state.fee_rate,
+            target_fee,
+            self.pool_state.max_fee_change,
+        );
 
         // Update insurance fund allocation
         self.insurance_fund.update_allocation(risk_level)?;
@@ -41,6 +54,66 @@ impl<'info> LiquidityProtection<'info> {
         // Update last update timestamp
         self.insurance_fund.last_rebalance = self.clock.unix_timestamp;
 
+        emit!(LiquidityParametersUpdated {
+            pool: self.pool_state.key(),
+            utilization,
+            risk_level,
+            previous_fee_rate,
+            fee_rate: self.pool_state.fee_rate,
+            insurance_allocation: self.insurance_fund.target_allocation,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for governance control of a pool's fee bounds
+#[derive(Accounts)]
+pub struct SetFeeConfig<'info> {
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub authority: Signer<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> SetFeeConfig<'info> {
+    /// Sets the base fee and the pool's fee floor, ceiling and maximum step,
+    /// all in basis points and within the global fee bounds
+    pub fn process(
+        &mut self,
+        base_fee: u64,
+        min_fee: u64,
+        max_fee: u64,
+        max_fee_change: u64,
+    ) -> Result<()> {
+        require!(
+            MIN_FEE_RATE <= min_fee && min_fee <= max_fee && max_fee <= MAX_FEE_RATE,
+            ErrorCode::InvalidFeeConfig
+        );
+        require!(base_fee <= max_fee && max_fee_change > 0, ErrorCode::InvalidFeeConfig);
+
+        let pool_state = &mut self.pool_state;
+        pool_state.base_fee = base_fee;
+        pool_state.min_fee = min_fee;
+        pool_state.max_fee = max_fee;
+        pool_state.max_fee_change = max_fee_change;
+        pool_state.fee_rate = pool_state.fee_rate.clamp(min_fee, max_fee);
+
+        emit!(FeeConfigUpdated {
+            pool: pool_state.key(),
+            base_fee,
+            min_fee,
+            max_fee,
+            max_fee_change,
+            fee_rate: pool_state.fee_rate,
+            timestamp: self.clock.unix_timestamp,
+        });
         Ok(())
     }
 }
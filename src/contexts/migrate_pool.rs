@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use crate::error::ErrorCode;
+use crate::events::PoolMigrated;
+use crate::program::Solswap;
+use crate::state::pool::PoolState;
+
+/// Context for moving a version 1 `PoolState`, the original layout, to a
+/// current-layout pool at its LST's pool address. Version 1 pools were not
+/// derived from their LST and have no authority of their own, so migration is
+/// signed by the program's upgrade authority, which names the pool's LST and
+/// governance authority. Older accounts cannot be deserialized as
+/// `PoolState`, so the old pool is taken unchecked, recognised by its owner
+/// and discriminator, and closed once its state has moved. Version 1 pools
+/// held no tokens, so the pool's token accounts are created here.
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    /// CHECK: Discriminator and size are checked in `process`
+    #[account(mut, owner = crate::ID)]
+    pub legacy_pool: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = PoolState::LEN,
+        seeds = [PoolState::SEED, lst_mint.key().as_ref()],
+        bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    pub lst_mint: Account<'info, Mint>,
+
+    #[account(address = token::spl_token::native_mint::ID @ ErrorCode::InvalidPoolAccount)]
+    pub native_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"sol_reserve", pool_state.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool_state,
+    )]
+    pub sol_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"lst_reserve", pool_state.key().as_ref()],
+        bump,
+        token::mint = lst_mint,
+        token::authority = pool_state,
+    )]
+    pub lst_reserve: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"fee_account", pool_state.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool_state,
+    )]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidAuthority)]
+    pub program: Program<'info, Solswap>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ ErrorCode::InvalidAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+
+    /// Pays rent for the new pool and receives the old pool's
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> MigratePoolState<'info> {
+    pub fn process(&mut self, authority: Pubkey, protocol: Pubkey, oracle: Pubkey, bump: u8) -> Result<()> {
+        let legacy_info = self.legacy_pool.to_account_info();
+        let v1 = PoolState::decode_v1(&legacy_info.try_borrow_data()?)?;
+
+        let lst_mint = self.lst_mint.key();
+        let mut pool_state = PoolState::from_v1(v1, bump, authority, lst_mint, protocol, oracle);
+        pool_state.set_token_accounts(self.sol_reserve.key(), self.lst_reserve.key(), self.fee_account.key());
+        self.pool_state.set_inner(pool_state);
+
+        // Close the old pool so it cannot be migrated again
+        let payer_info = self.payer.to_account_info();
+        **payer_info.try_borrow_mut_lamports()? = payer_info
+            .lamports()
+            .checked_add(legacy_info.lamports())
+            .ok_or(ErrorCode::MathOverflow)?;
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        legacy_info.assign(&System::id());
+        legacy_info.realloc(0, false)?;
+
+        emit!(PoolMigrated {
+            pool: self.pool_state.key(),
+            legacy_pool: legacy_info.key(),
+            lst_mint,
+            authority,
+            protocol,
+            oracle,
+            version: self.pool_state.version,
+            lst_risk_score: self.pool_state.lst_risk_score,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+pub mod depeg_monitor;
+pub mod initialize_pool;
+pub mod insurance_fund;
+pub mod lending;
+pub mod liquidity_protection;
+pub mod lst_pool;
+pub mod migrate_pool;
+pub mod swap;
+pub mod update_price;
+pub mod validator_registry;
+pub mod validator_update;
+pub mod verify_reserves;
+
+pub use depeg_monitor::*;
+pub use initialize_pool::*;
+pub use insurance_fund::*;
+pub use lending::*;
+pub use liquidity_protection::*;
+pub use lst_pool::*;
+pub use migrate_pool::*;
+pub use swap::*;
+pub use update_price::*;
+pub use validator_registry::*;
+pub use validator_update::*;
+pub use verify_reserves::*;
@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::error::ErrorCode;
+use crate::contexts::lending::{withdraw_principal, LendingMarket};
+use crate::events::SwapExecuted;
+use crate::math::lst_swap::*;
+use crate::math::risk_adjusted_curve::calculate_risk_multiplier;
+use crate::state::lending::LendingStrategy;
+use crate::state::pool::PoolState;
+
+/// Context for swapping between SOL and the pool's LST at the verified rate.
+/// Pools with a lending strategy may pass it and its market accounts as
+/// remaining accounts, in `LendingMarket` order after the strategy, so LST lent
+/// out can be withdrawn when the reserve runs short.
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(
+        mut,
+        seeds = [PoolState::SEED, pool_state.lst_mint.as_ref()],
+        bump = pool_state.bump,
+    )]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// LP SOL liquidity
+    #[account(mut, address = pool_state.sol_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub sol_reserve: Account<'info, TokenAccount>,
+
+    /// The pool's LST holdings
+    #[account(mut, address = pool_state.lst_reserve @ ErrorCode::InvalidPoolAccount)]
+    pub lst_reserve: Account<'info, TokenAccount>,
+
+    /// Collects swap fees until they are swept
+    #[account(mut, address = pool_state.fee_account @ ErrorCode::InvalidPoolAccount)]
+    pub fee_account: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = user_sol.mint == sol_reserve.mint)]
+    pub user_sol: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = user_lst.mint == pool_state.lst_mint @ ErrorCode::InvalidLSTMint)]
+    pub user_lst: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> Swap<'info> {
+    pub fn process(
+        &mut self,
+        direction: SwapDirection,
+        amount_in: u64,
+        min_amount_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(amount_in > 0, ErrorCode::InvalidSwapAmount);
+
+        let status = self.pool_state.trading_status;
+        match direction {
+            SwapDirection::SolToLst => require!(status.allows_sell(), ErrorCode::TradingRestricted),
+            SwapDirection::LstToSol => require!(status.allows_buy(), ErrorCode::TradingRestricted),
+        }
+
+        // Quotes are priced off the verified rate, which must be current
+        require!(
+            self.pool_state.protocol_reserves.is_fresh(self.clock.unix_timestamp),
+            ErrorCode::ReservesNotVerified
+        );
+
+        let mut lending = self.load_lending(remaining_accounts)?;
+        let lst_lent = lending.as_ref().map_or(0, |(strategy, _, _)| strategy.principal);
+
+        let sol_reserve_before = self.sol_reserve.amount;
+        let lst_reserve_before = self.lst_reserve.amount;
+        let quote = quote_swap(
+            direction,
+            amount_in,
+            sol_reserve_before,
+            lst_reserve_before.saturating_add(lst_lent),
+            &self.pool_state.protocol_reserves,
+            FeeParams {
+                fee_rate: self.pool_state.fee_rate,
+                min_fee: self.pool_state.min_fee,
+                max_fee: self.pool_state.max_fee,
+            },
+        )?;
+        require!(quote.amount_out > 0, ErrorCode::InsufficientLiquidity);
+        require!(quote.amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
+
+        if direction == SwapDirection::SolToLst {
+            if let Some((strategy, market, strategy_info)) = lending.as_mut() {
+                self.withdraw_lent(strategy, market, quote.amount_out)?;
+                let mut data = strategy_info.try_borrow_mut_data()?;
+                strategy.try_serialize(&mut &mut data[..])?;
+            }
+        }
+
+        let lst_mint = self.pool_state.lst_mint;
+        let seeds = &[PoolState::SEED, lst_mint.as_ref(), &[self.pool_state.bump]];
+        match direction {
+            SwapDirection::SolToLst => {
+                self.transfer_from_user(&self.user_sol, &self.sol_reserve, amount_in - quote.fee)?;
+                self.transfer_from_user(&self.user_sol, &self.fee_account, quote.fee)?;
+                self.transfer_from_pool(&self.lst_reserve, &self.user_lst, quote.amount_out, seeds)?;
+            }
+            SwapDirection::LstToSol => {
+                self.transfer_from_user(&self.user_lst, &self.lst_reserve, amount_in)?;
+                self.transfer_from_pool(&self.sol_reserve, &self.user_sol, quote.amount_out, seeds)?;
+                self.transfer_from_pool(&self.sol_reserve, &self.fee_account, quote.fee, seeds)?;
+            }
+        }
+
+        self.sol_reserve.reload()?;
+        self.lst_reserve.reload()?;
+
+        // LP liquidity is whatever the reserves and lent LST are now worth
+        let lst_lent = lending.as_ref().map_or(0, |(strategy, _, _)| strategy.principal);
+        let lst_held = self.lst_reserve.amount.checked_add(lst_lent).ok_or(ErrorCode::MathOverflow)?;
+        self.pool_state.sync_deposits(self.sol_reserve.amount, lst_held)?;
+
+        let metrics = &self.pool_state.lst_performance_metrics;
+        let risk_multiplier = calculate_risk_multiplier(
+            metrics.validator_performance,
+            metrics.reserve_ratio,
+            metrics.decentralization_score,
+        )?;
+
+        emit!(SwapExecuted {
+            pool: self.pool_state.key(),
+            user: self.user.key(),
+            direction,
+            amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+            fee_bps: quote.fee_bps,
+            sol_reserve_before,
+            lst_reserve_before,
+            sol_reserve_after: self.sol_reserve.amount,
+            lst_reserve_after: self.lst_reserve.amount,
+            risk_multiplier,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Loads the pool's lending strategy and market from the remaining
+    /// accounts, if they were passed
+    fn load_lending(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<Option<(LendingStrategy, LendingMarket<'info>, AccountInfo<'info>)>> {
+        let Some((strategy_info, market_accounts)) = remaining_accounts.split_first() else {
+            return Ok(None);
+        };
+
+        let (expected, _) = Pubkey::find_program_address(
+            &[LendingStrategy::SEED, self.pool_state.key().as_ref()],
+            &crate::ID,
+        );
+        require!(
+            strategy_info.key() == expected && strategy_info.owner == &crate::ID && strategy_info.is_writable,
+            ErrorCode::InvalidLendingReserve
+        );
+
+        let strategy = LendingStrategy::try_deserialize(&mut &strategy_info.try_borrow_data()?[..])?;
+        let market = LendingMarket::from_accounts(&strategy, market_accounts)?;
+        Ok(Some((strategy, market, strategy_info.clone())))
+    }
+
+    /// Withdraws lent LST into the reserve if it holds less than `amount_out`
+    fn withdraw_lent(
+        &mut self,
+        strategy: &mut LendingStrategy,
+        market: &LendingMarket<'info>,
+        amount_out: u64,
+    ) -> Result<()> {
+        let Some(amount) = strategy.shortfall_withdrawal(self.lst_reserve.amount, amount_out) else {
+            return Ok(());
+        };
+
+        let lst_reserve = self.lst_reserve.to_account_info();
+        let token_program = self.token_program.to_account_info();
+        withdraw_principal(&mut self.pool_state, strategy, market, &lst_reserve, &token_program, amount)?;
+        self.lst_reserve.reload()
+    }
+
+    fn transfer_from_user(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+
+    fn transfer_from_pool(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        pool_seeds: &[&[u8]],
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.pool_state.to_account_info(),
+                },
+                &[pool_seeds],
+            ),
+            amount,
+        )
+    }
+}
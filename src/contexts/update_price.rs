@@ -1,5 +1,54 @@
 use anchor_lang::prelude::*;
-use crate::state::{pool::PoolState, oracle::OracleState};
+use crate::error::ErrorCode;
+use crate::events::{OracleInitialized, PriceUpdated};
+use crate::state::{pool::PoolState, oracle::*};
+
+/// Context for creating a pool's oracle over its Pyth and Switchboard feeds.
+/// The feeds are fixed here so price updates cannot be fed from other accounts.
+#[derive(Accounts)]
+pub struct InitializeOracle<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = OracleState::LEN,
+        seeds = [b"oracle", pool_state.key().as_ref()],
+        bump,
+    )]
+    pub oracle_state: Box<Account<'info, OracleState>>,
+
+    #[account(mut, has_one = authority @ ErrorCode::InvalidAuthority)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// CHECK: Price data is validated on each update
+    #[account(owner = pyth_program::ID @ ErrorCode::InvalidOracleFeed)]
+    pub pyth_price_account: UncheckedAccount<'info>,
+
+    /// CHECK: Price data is validated on each update
+    #[account(owner = SWITCHBOARD_PROGRAM_ID @ ErrorCode::InvalidOracleFeed)]
+    pub switchboard_feed: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeOracle<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        let pyth_feed = self.pyth_price_account.key();
+        let switchboard_feed = self.switchboard_feed.key();
+        self.oracle_state.set_inner(OracleState::new(pyth_feed, switchboard_feed));
+        self.pool_state.oracle = self.oracle_state.key();
+
+        emit!(OracleInitialized {
+            oracle: self.oracle_state.key(),
+            pool: self.pool_state.key(),
+            pyth_feed,
+            switchboard_feed,
+        });
+        Ok(())
+    }
+}
 
 #[derive(Accounts)]
 pub struct UpdatePrice<'info> {
@@ -8,15 +57,49 @@ pub struct UpdatePrice<'info> {
 
     #[account(
         mut,
+        address = pool.oracle,
         constraint = oracle_state.last_update_ts < Clock::get()?.unix_timestamp - 3 @ ErrorCode::TooFrequentUpdate
     )]
     pub oracle_state: Account<'info, OracleState>,
 
     /// CHECK: Verified in price calculation logic
+    #[account(address = oracle_state.pyth_feed @ ErrorCode::InvalidOracleFeed)]
     pub pyth_price_account: AccountInfo<'info>,
 
     /// CHECK: Verified in price calculation logic
+    #[account(address = oracle_state.switchboard_feed @ ErrorCode::InvalidOracleFeed)]
     pub switchboard_feed: AccountInfo<'info>,
 
     pub clock: Sysvar<'info, Clock>,
 }
+
+impl<'info> UpdatePrice<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        let price = get_weighted_median_price(
+            &self.pyth_price_account,
+            &self.switchboard_feed,
+            &self.oracle_state,
+            self.clock.unix_timestamp,
+        )?;
+
+        let oracle_state = &mut self.oracle_state;
+        // The first observation seeds the EMA rather than averaging against zero
+        oracle_state.ema_price = if oracle_state.last_update_ts == 0 {
+            price
+        } else {
+            oracle_state.calculate_ema(price)?
+        };
+        oracle_state.last_price = price;
+        oracle_state.last_update_ts = self.clock.unix_timestamp;
+        oracle_state.update_price_history(price);
+
+        emit!(PriceUpdated {
+            oracle: oracle_state.key(),
+            price,
+            ema_price: oracle_state.ema_price,
+            timestamp: self.clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::vote;
+use crate::error::ErrorCode;
+use crate::program::Solswap;
+use crate::events::{
+    AllowlistEnabledChanged, ValidatorApprovalChanged, ValidatorDeregistered, ValidatorRegistered,
+    ValidatorRegistryInitialized,
+};
+use crate::state::{validator::ValidatorState, validator_registry::ValidatorRegistry};
+
+/// Context for creating the global validator registry
+#[derive(Accounts)]
+pub struct InitializeValidatorRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ValidatorRegistry::LEN,
+        seeds = [b"validator_registry"],
+        bump,
+    )]
+    pub registry: Account<'info, ValidatorRegistry>,
+
+    /// Governance authority for the registry; must be the program's upgrade
+    /// authority so the singleton cannot be claimed by whoever initializes first
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::InvalidAuthority)]
+    pub program: Program<'info, Solswap>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::InvalidAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeValidatorRegistry<'info> {
+    pub fn process(&mut self, allowlist_enabled: bool, bump: u8) -> Result<()> {
+        self.registry.set_inner(ValidatorRegistry::new(self.authority.key(), allowlist_enabled, bump));
+
+        emit!(ValidatorRegistryInitialized {
+            registry: self.registry.key(),
+            authority: self.authority.key(),
+            allowlist_enabled,
+        });
+        Ok(())
+    }
+}
+
+/// Context for governance changes to the validator allowlist
+#[derive(Accounts)]
+pub struct UpdateValidatorAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"validator_registry"],
+        bump = registry.bump,
+        has_one = authority @ ErrorCode::InvalidAuthority,
+    )]
+    pub registry: Account<'info, ValidatorRegistry>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> UpdateValidatorAllowlist<'info> {
+    /// Approves or revokes a vote account
+    pub fn set_approval(&mut self, vote_account: Pubkey, approved: bool) -> Result<()> {
+        if approved {
+            self.registry.approve(vote_account)?;
+        } else {
+            self.registry.revoke(&vote_account);
+        }
+
+        emit!(ValidatorApprovalChanged { vote_account, approved });
+        Ok(())
+    }
+
+    /// Toggles whether registration requires prior approval
+    pub fn set_allowlist_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.registry.allowlist_enabled = enabled;
+        emit!(AllowlistEnabledChanged { enabled });
+        Ok(())
+    }
+}
+
+/// Context for permissionless validator registration, keyed on the vote account
+#[derive(Accounts)]
+pub struct RegisterValidator<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = ValidatorState::LEN,
+        seeds = [ValidatorState::SEED, vote_account.key().as_ref()],
+        bump,
+    )]
+    pub validator_state: Account<'info, ValidatorState>,
+
+    #[account(
+        mut,
+        seeds = [b"validator_registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, ValidatorRegistry>,
+
+    /// CHECK: Ownership by the vote program is enforced by the constraint
+    #[account(owner = vote::program::ID @ ErrorCode::InvalidVoteAccount)]
+    pub vote_account: UncheckedAccount<'info>,
+
+    /// Anyone may pay rent to register a validator
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterValidator<'info> {
+    pub fn process(&mut self, bump: u8) -> Result<()> {
+        let vote_account = self.vote_account.key();
+        self.registry.register(&vote_account)?;
+
+        *self.validator_state = ValidatorState::new(
+            vote_account,
+            self.registry.authority,
+            self.payer.key(),
+            bump,
+        );
+
+        emit!(ValidatorRegistered {
+            vote_account,
+            validator_state: self.validator_state.key(),
+            registered_count: self.registry.registered_count,
+        });
+
+        Ok(())
+    }
+}
+
+/// Context for removing a validator and returning its rent to the original payer
+#[derive(Accounts)]
+pub struct DeregisterValidator<'info> {
+    #[account(
+        mut,
+        seeds = [ValidatorState::SEED, validator_state.validator_address.as_ref()],
+        bump = validator_state.bump,
+        has_one = rent_payer @ ErrorCode::InvalidValidatorState,
+        close = rent_payer,
+    )]
+    pub validator_state: Account<'info, ValidatorState>,
+
+    #[account(
+        mut,
+        seeds = [b"validator_registry"],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, ValidatorRegistry>,
+
+    /// CHECK: Matched against `validator_state.rent_payer`
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// Governance authority or the account that registered the validator
+    pub signer: Signer<'info>,
+}
+
+impl<'info> DeregisterValidator<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        self.registry
+            .deregister(&self.signer.key(), &self.validator_state.rent_payer)?;
+
+        emit!(ValidatorDeregistered {
+            vote_account: self.validator_state.validator_address,
+            validator_state: self.validator_state.key(),
+            registered_count: self.registry.registered_count,
+        });
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::events::ValidatorMetricsUpdated;
 use crate::state::{pool::PoolState, validator::ValidatorState};
 
 /// Context for updating validator metrics
@@ -7,7 +9,7 @@ pub struct UpdateValidatorMetrics<'info> {
     /// The validator state account to update
     #[account(
         mut,
-        seeds = [b"validator", validator_state.validator_address.as_ref()],
+        seeds = [ValidatorState::SEED, validator_state.validator_address.as_ref()],
         bump = validator_state.bump,
     )]
     pub validator_state: Account<'info, ValidatorState>,
@@ -45,6 +47,16 @@ impl<'info> UpdateValidatorMetrics<'info> {
         // Update pool's aggregate validator metrics
         self.update_pool_metrics(new_risk_score)?;
 
+        emit!(ValidatorMetricsUpdated {
+            validator: self.validator_state.key(),
+            pool: self.pool_state.key(),
+            performance: new_performance,
+            mev_reward: new_mev_reward,
+            total_mev_rewards: self.validator_state.mev_rewards,
+            risk_score: new_risk_score,
+            timestamp: self.clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
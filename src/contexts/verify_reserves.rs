@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::error::ErrorCode;
+use crate::events::{StatusChangeReason, TradingStatusChanged};
+use crate::state::pool::{PoolState, TradingStatus};
+use crate::state::reserves::*;
+
+/// Context for permissionless proof-of-reserves verification of a pool's LST
+#[derive(Accounts)]
+pub struct VerifyReserves<'info> {
+    #[account(mut)]
+    pub pool_state: Box<Account<'info, PoolState>>,
+
+    /// Mint of the pool's LST, for the circulating supply
+    #[account(address = pool_state.lst_mint @ ErrorCode::InvalidLSTMint)]
+    pub lst_mint: Account<'info, Mint>,
+
+    /// CHECK: Parsed as an SPL stake pool or Marinade state depending on owner
+    #[account(address = pool_state.protocol_reserves.protocol @ ErrorCode::InvalidProtocolState)]
+    pub protocol_state: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+impl<'info> VerifyReserves<'info> {
+    pub fn process(&mut self) -> Result<()> {
+        require!(self.pool_state.is_initialized, ErrorCode::PoolNotInitialized);
+
+        let reported = read_reported_reserves(&self.protocol_state, &self.lst_mint.key())?;
+        let reserve_ratio = calculate_backing_ratio(
+            &reported,
+            self.lst_mint.supply,
+            &self.pool_state.protocol_reserves,
+        );
+
+        let pool_state = &mut self.pool_state;
+        pool_state.lst_performance_metrics.update_reserve_ratio(reserve_ratio);
+
+        // Verification only ever raises the risk score; it is lowered by the
+        // regular validator metric updates
+        let reserve_risk = reserve_risk_level(reserve_ratio) as u64;
+        pool_state.lst_risk_score = pool_state.lst_risk_score.max(reserve_risk);
+
+        if reserve_ratio < MIN_RESERVE_RATIO {
+            if let Some(previous) = pool_state.escalate_trading_status(TradingStatus::Quarantined) {
+                emit!(TradingStatusChanged {
+                    pool: pool_state.key(),
+                    previous,
+                    status: TradingStatus::Quarantined,
+                    reason: StatusChangeReason::ReserveShortfall,
+                    metric: reserve_ratio,
+                    timestamp: self.clock.unix_timestamp,
+                });
+            }
+        }
+
+        // A shortfall is not recorded as the new baseline, so the rate drop keeps
+        // showing until the protocol recovers
+        if reserve_ratio >= FULL_RESERVE_RATIO {
+            pool_state.protocol_reserves.sol_reserves = reported.sol_reserves;
+            pool_state.protocol_reserves.lst_supply = reported.lst_supply;
+            pool_state.compensated_reserve_ratio = FULL_RESERVE_RATIO;
+        }
+        pool_state.protocol_reserves.last_verified = self.clock.unix_timestamp;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use solswap_math::MathError;
 
 #[error_code]
 pub enum ErrorCode {
@@ -24,4 +25,49 @@ pub enum ErrorCode {
     InvalidAuthority,
     #[msg("Invalid validator state")]
     InvalidValidatorState,
-}
\ No newline at end of file
+    #[msg("Account is not a vote account")]
+    InvalidVoteAccount,
+    #[msg("Validator is not on the governance allowlist")]
+    ValidatorNotApproved,
+    #[msg("Validator allowlist is full")]
+    AllowlistFull,
+    #[msg("Account is not a supported stake pool or Marinade state for this LST")]
+    InvalidProtocolState,
+    #[msg("Oracle price is stale")]
+    StaleOracle,
+    #[msg("Account is not one of the oracle's price feeds")]
+    InvalidOracleFeed,
+    #[msg("Oracle price confidence interval is too wide")]
+    LowConfidencePrice,
+    #[msg("Insurance withdrawal is still in its cooldown")]
+    WithdrawalCooldown,
+    #[msg("Reserves have not been verified recently")]
+    ReservesNotVerified,
+    #[msg("No uncompensated loss to claim for")]
+    NoInsuredLoss,
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+    #[msg("Pool account is already at the current version")]
+    AlreadyMigrated,
+    #[msg("Swap amount must be greater than zero")]
+    InvalidSwapAmount,
+    #[msg("Trading in this direction is restricted for the LST")]
+    TradingRestricted,
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+    #[msg("Lending allocation and liquidity buffer exceed 100%")]
+    InvalidLendingParams,
+    #[msg("Account does not match the pool's lending market")]
+    InvalidLendingReserve,
+    #[msg("Account is not one of the pool's token accounts")]
+    InvalidPoolAccount,
+}
+
+/// Maps errors from the shared math crate onto the program's error codes
+pub fn math_error(error: MathError) -> Error {
+    match error {
+        MathError::Overflow | MathError::NoConvergence | MathError::InvalidInput => ErrorCode::MathOverflow.into(),
+        MathError::InsufficientLiquidity => ErrorCode::InsufficientLiquidity.into(),
+        MathError::RateNotVerified => ErrorCode::ReservesNotVerified.into(),
+    }
+}
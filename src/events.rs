@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use crate::math::lst_swap::SwapDirection;
+use crate::state::depeg::DepegConfig;
+use crate::state::pool::TradingStatus;
+
+/// Why a pool's trading status changed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusChangeReason {
+    /// Market price fell below the fair stake-pool rate
+    Depeg,
+    /// Reserve verification found the LST under-backed
+    ReserveShortfall,
+    /// Set directly by the pool authority
+    Governance,
+}
+
+#[event]
+pub struct TradingStatusChanged {
+    pub pool: Pubkey,
+    pub previous: TradingStatus,
+    pub status: TradingStatus,
+    pub reason: StatusChangeReason,
+    /// Depeg discount or reserve ratio behind the change (basis points)
+    pub metric: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceEventTriggered {
+    pub insurance_fund: Pubkey,
+    pub pool: Pubkey,
+    /// Verified reserve ratio the claim was made against (basis points)
+    pub reserve_ratio: u64,
+    pub loss: u64,
+    pub payout: u64,
+    /// Fund assets remaining after the payout
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesRouted {
+    pub pool: Pubkey,
+    pub fees: u64,
+    pub to_insurance: u64,
+    pub to_lps: u64,
+    /// Earlier fee contributions returned from the insurance vault to LPs
+    pub released: u64,
+    /// Insurance fund assets after routing
+    pub total_assets: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapExecuted {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub direction: SwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Fee charged, in lamports
+    pub fee: u64,
+    /// Fee rate applied to this trade (basis points)
+    pub fee_bps: u64,
+    pub sol_reserve_before: u64,
+    pub lst_reserve_before: u64,
+    pub sol_reserve_after: u64,
+    pub lst_reserve_after: u64,
+    /// Risk multiplier of the pool's LST metrics (basis points, 10000 = 1.0)
+    pub risk_multiplier: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LendingRebalanced {
+    pub pool: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    /// Interest redeemed into the pool reserve and credited to LPs, in LST
+    pub harvested: u64,
+    /// LST principal lent out after the rebalance
+    pub principal: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleInitialized {
+    pub oracle: Pubkey,
+    pub pool: Pubkey,
+    pub pyth_feed: Pubkey,
+    pub switchboard_feed: Pubkey,
+}
+
+#[event]
+pub struct PriceUpdated {
+    pub oracle: Pubkey,
+    /// Weighted Pyth and Switchboard price
+    pub price: i64,
+    pub ema_price: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorMetricsUpdated {
+    pub validator: Pubkey,
+    pub pool: Pubkey,
+    pub performance: u64,
+    pub mev_reward: u64,
+    /// Cumulative MEV rewards after the update
+    pub total_mev_rewards: u64,
+    pub risk_score: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ValidatorRegistered {
+    pub vote_account: Pubkey,
+    pub validator_state: Pubkey,
+    pub registered_count: u32,
+}
+
+#[event]
+pub struct ValidatorDeregistered {
+    pub vote_account: Pubkey,
+    pub validator_state: Pubkey,
+    pub registered_count: u32,
+}
+
+#[event]
+pub struct ValidatorApprovalChanged {
+    pub vote_account: Pubkey,
+    pub approved: bool,
+}
+
+#[event]
+pub struct AllowlistEnabledChanged {
+    pub enabled: bool,
+}
+
+#[event]
+pub struct LiquidityParametersUpdated {
+    pub pool: Pubkey,
+    /// Borrowed share of deposits (basis points)
+    pub utilization: u64,
+    pub risk_level: u16,
+    pub previous_fee_rate: u64,
+    pub fee_rate: u64,
+    /// Insurance fund target allocation after the update (basis points)
+    pub insurance_allocation: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeConfigUpdated {
+    pub pool: Pubkey,
+    pub base_fee: u64,
+    pub min_fee: u64,
+    pub max_fee: u64,
+    pub max_fee_change: u64,
+    /// Current fee rate, clamped to the new bounds
+    pub fee_rate: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepegConfigUpdated {
+    pub pool: Pubkey,
+    pub config: DepegConfig,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceDeposited {
+    pub insurance_fund: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+}
+
+#[event]
+pub struct InsuranceWithdrawalRequested {
+    pub insurance_fund: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub unlock_ts: i64,
+}
+
+#[event]
+pub struct InsuranceWithdrawn {
+    pub insurance_fund: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub assets: u64,
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceParamsUpdated {
+    pub insurance_fund: Pubkey,
+    pub fee_share: u16,
+    pub max_claim: u16,
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub lst_mint: Pubkey,
+    pub authority: Pubkey,
+    pub protocol: Pubkey,
+    pub oracle: Pubkey,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub pool: Pubkey,
+    /// Version 1 account the pool's state moved from, now closed
+    pub legacy_pool: Pubkey,
+    pub lst_mint: Pubkey,
+    pub authority: Pubkey,
+    pub protocol: Pubkey,
+    pub oracle: Pubkey,
+    /// Layout version the pool was migrated to
+    pub version: u8,
+    /// Risk score carried over from the original layout
+    pub lst_risk_score: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsuranceFundInitialized {
+    pub insurance_fund: Pubkey,
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub asset_mint: Pubkey,
+    pub vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub withdrawal_cooldown: i64,
+}
+
+#[event]
+pub struct ValidatorRegistryInitialized {
+    pub registry: Pubkey,
+    pub authority: Pubkey,
+    pub allowlist_enabled: bool,
+}
+
+#[event]
+pub struct LendingStrategyInitialized {
+    pub lending_strategy: Pubkey,
+    pub pool: Pubkey,
+    pub lending_program: Pubkey,
+    pub reserve: Pubkey,
+    pub collateral_account: Pubkey,
+    /// Share of pool liquidity that may be lent out (basis points)
+    pub max_allocation: u16,
+    /// Share of pool liquidity always kept idle (basis points)
+    pub liquidity_buffer: u16,
+}
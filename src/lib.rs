@@ -2,9 +2,12 @@ use anchor_lang::prelude::*;
 
 mod state;
 mod contexts;
+mod math;
 mod error;
+mod events;
 
 use contexts::*;
+use math::*;
 use state::*;
 use error::*;
 
@@ -16,29 +19,78 @@ pub mod solswap {
 
     // ... (previous functions remain the same)
 
-    /// Initialize a new validator state account
-    pub fn initialize_validator(
-        ctx: Context<InitializeValidator>,
-        validator_address: Pubkey,
-        bump: u8,
-    ) -> Result<()> {
-        let validator_state = &mut ctx.accounts.validator_state;
-        
-        *validator_state = ValidatorState {
-            validator_address,
-            performance_history: [0; 30],
-            current_index: 0,
-            mev_rewards: 0,
-            total_stake: 0,
-            average_apr: 0,
-            missed_blocks: 0,
-            last_update: 0,
-            commission: 0,
-            authority: ctx.accounts.authority.key(),
-            bump,
-        };
-        
-        Ok(())
+    /// Create the validator registry that governs registration
+    pub fn initialize_validator_registry(
+        ctx: Context<InitializeValidatorRegistry>,
+        allowlist_enabled: bool,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("registry").unwrap();
+        ctx.accounts.process(allowlist_enabled, bump)
+    }
+
+    /// Approve or revoke a vote account on the governance allowlist
+    pub fn set_validator_approval(
+        ctx: Context<UpdateValidatorAllowlist>,
+        vote_account: Pubkey,
+        approved: bool,
+    ) -> Result<()> {
+        ctx.accounts.set_approval(vote_account, approved)
+    }
+
+    /// Enable or disable the registration allowlist
+    pub fn set_allowlist_enabled(
+        ctx: Context<UpdateValidatorAllowlist>,
+        enabled: bool,
+    ) -> Result<()> {
+        ctx.accounts.set_allowlist_enabled(enabled)
+    }
+
+    /// Register a validator state PDA for a vote account
+    pub fn register_validator(ctx: Context<RegisterValidator>) -> Result<()> {
+        let bump = *ctx.bumps.get("validator_state").unwrap();
+        ctx.accounts.process(bump)
+    }
+
+    /// Close a validator state PDA and return its rent; signed by governance or
+    /// the original rent payer
+    pub fn deregister_validator(ctx: Context<DeregisterValidator>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Create the pool for an LST, naming its governance authority, reserve
+    /// protocol and oracle
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        authority: Pubkey,
+        protocol: Pubkey,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("pool_state").unwrap();
+        ctx.accounts.process(authority, protocol, oracle, bump)
+    }
+
+    /// Move a pool account created under the original layout to its LST's
+    /// pool address, naming its governance authority, reserve protocol and
+    /// oracle
+    pub fn migrate_pool_state(
+        ctx: Context<MigratePoolState>,
+        authority: Pubkey,
+        protocol: Pubkey,
+        oracle: Pubkey,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("pool_state").unwrap();
+        ctx.accounts.process(authority, protocol, oracle, bump)
+    }
+
+    /// Create the pool's oracle over its Pyth and Switchboard feeds
+    pub fn initialize_oracle(ctx: Context<InitializeOracle>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Record a new weighted oracle price from the oracle's feeds and update
+    /// its EMA
+    pub fn update_price(ctx: Context<UpdatePrice>) -> Result<()> {
+        ctx.accounts.process()
     }
 
     /// Update validator metrics and recalculate risk scores
@@ -49,15 +101,115 @@ pub mod solswap {
     ) -> Result<()> {
         ctx.accounts.process_update(new_performance, new_mev_reward)
     }
-}
 
-#[derive(Accounts)]
-pub struct InitializeValidator<'info> {
-    #[account(init, payer = authority, space = ValidatorState::LEN)]
-    pub validator_state: Account<'info, ValidatorState>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+    /// Verify the LST's backing against its protocol's reserves, halting
+    /// trading if it falls below the minimum reserve ratio
+    pub fn verify_reserves(ctx: Context<VerifyReserves>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Compare the LST's market price to its fair stake pool rate, restricting
+    /// trading if it has depegged
+    pub fn check_depeg(ctx: Context<CheckDepeg>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Set the LST's trading status, lifting or imposing restrictions
+    pub fn set_trading_status(ctx: Context<GovernTrading>, status: TradingStatus) -> Result<()> {
+        ctx.accounts.set_trading_status(status)
+    }
+
+    /// Update the depeg thresholds for sell-only mode and quarantine
+    pub fn set_depeg_config(ctx: Context<GovernTrading>, config: DepegConfig) -> Result<()> {
+        ctx.accounts.set_depeg_config(config)
+    }
+
+    /// Create the pool's insurance fund with its vault and share mint
+    pub fn initialize_insurance_fund(
+        ctx: Context<InitializeInsuranceFund>,
+        withdrawal_cooldown: i64,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("insurance_fund").unwrap();
+        ctx.accounts.process(withdrawal_cooldown, bump)
+    }
+
+    /// Deposit into the insurance fund in exchange for insurance shares
+    pub fn deposit_insurance(ctx: Context<DepositInsurance>, amount: u64) -> Result<()> {
+        ctx.accounts.process(amount)
+    }
+
+    /// Escrow insurance shares and start the withdrawal cooldown
+    pub fn request_insurance_withdrawal(
+        ctx: Context<RequestInsuranceWithdrawal>,
+        shares: u64,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("withdrawal").unwrap();
+        ctx.accounts.process(shares, bump)
+    }
+
+    /// Redeem escrowed insurance shares once the cooldown has passed
+    pub fn withdraw_insurance(ctx: Context<WithdrawInsurance>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Pay out the insurance fund to the pool after a verified shortfall
+    pub fn trigger_insurance_event(ctx: Context<TriggerInsuranceEvent>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Swap between SOL and the pool's LST, charging the dynamic fee
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        direction: SwapDirection,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.process(direction, amount_in, min_amount_out, ctx.remaining_accounts)
+    }
+
+    /// Update the base fee and the pool's fee bounds
+    pub fn set_fee_config(
+        ctx: Context<SetFeeConfig>,
+        base_fee: u64,
+        min_fee: u64,
+        max_fee: u64,
+        max_fee_change: u64,
+    ) -> Result<()> {
+        ctx.accounts.process(base_fee, min_fee, max_fee, max_fee_change)
+    }
+
+    /// Recompute utilization, risk and the dynamic fee rate
+    pub fn update_liquidity_parameters(ctx: Context<LiquidityProtection>) -> Result<()> {
+        ctx.accounts.update_liquidity_parameters()
+    }
+
+    /// Split collected swap fees between LPs and the insurance fund according
+    /// to the fund's target allocation
+    pub fn sweep_fees(ctx: Context<SweepFees>) -> Result<()> {
+        ctx.accounts.process()
+    }
+
+    /// Update the insurance fee share and per-claim cap
+    pub fn set_insurance_params(
+        ctx: Context<SetInsuranceParams>,
+        fee_share: u16,
+        max_claim: u16,
+    ) -> Result<()> {
+        ctx.accounts.process(fee_share, max_claim)
+    }
+
+    /// Attach a lending strategy that lends out a capped share of the pool's LST
+    pub fn initialize_lending_strategy(
+        ctx: Context<InitializeLendingStrategy>,
+        max_allocation: u16,
+        liquidity_buffer: u16,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("lending_strategy").unwrap();
+        ctx.accounts.process(max_allocation, liquidity_buffer, bump)
+    }
+
+    /// Harvest lending yield for LPs and move the position toward its target
+    pub fn rebalance_lending(ctx: Context<RebalanceLending>) -> Result<()> {
+        ctx.accounts.process()
+    }
 }
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use solswap_math::lst::{self, ExchangeRate};
+use crate::error::math_error;
+use crate::state::reserves::ProtocolReserves;
+
+pub use solswap_math::lst::{FeeParams, SwapQuote};
+
+/// Direction of a swap between SOL and the pool's LST
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapDirection {
+    /// User pays SOL and receives LST
+    SolToLst,
+    /// User pays LST and receives SOL
+    LstToSol,
+}
+
+impl From<SwapDirection> for lst::SwapDirection {
+    fn from(direction: SwapDirection) -> Self {
+        match direction {
+            SwapDirection::SolToLst => lst::SwapDirection::SolToLst,
+            SwapDirection::LstToSol => lst::SwapDirection::LstToSol,
+        }
+    }
+}
+
+impl From<&ProtocolReserves> for ExchangeRate {
+    fn from(reserves: &ProtocolReserves) -> Self {
+        ExchangeRate {
+            sol_reserves: reserves.sol_reserves,
+            lst_supply: reserves.lst_supply,
+        }
+    }
+}
+
+/// Prices a swap at the LST's verified exchange rate, charging the pool's
+/// dynamic fee adjusted for whether the trade restores the SOL/LST balance
+pub fn quote_swap(
+    direction: SwapDirection,
+    amount_in: u64,
+    sol_reserve: u64,
+    lst_reserve: u64,
+    rate: &ProtocolReserves,
+    fees: FeeParams,
+) -> Result<SwapQuote> {
+    lst::quote_swap(direction.into(), amount_in, sol_reserve, lst_reserve, rate.into(), fees)
+        .map_err(math_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate() -> ProtocolReserves {
+        // 1.1 SOL per LST
+        ProtocolReserves {
+            protocol: Pubkey::default(),
+            sol_reserves: 1_100,
+            lst_supply: 1_000,
+            last_verified: 1,
+        }
+    }
+
+    const FEES: FeeParams = FeeParams { fee_rate: 30, min_fee: 10, max_fee: 100 };
+
+    #[test]
+    fn test_trades_at_verified_rate_less_fee() {
+        // Balanced pool, small trade: close to the base rate either way
+        let quote = quote_swap(SwapDirection::SolToLst, 1_100_000, 1_000_000_000, 909_090_909, &rate(), FEES).unwrap();
+        assert_eq!(quote.fee_bps, 30);
+        assert_eq!(quote.fee, 3_300);
+        assert_eq!(quote.amount_out, 997_000);
+
+        let quote = quote_swap(SwapDirection::LstToSol, 1_000_000, 1_000_000_000, 909_090_909, &rate(), FEES).unwrap();
+        assert_eq!(quote.fee_bps, 30);
+        assert_eq!(quote.amount_out, 1_100_000 - 3_300);
+    }
+
+    #[test]
+    fn test_rebalancing_direction_is_cheaper() {
+        // Pool is SOL heavy: buying SOL with LST restores balance
+        let restoring = quote_swap(SwapDirection::LstToSol, 50_000_000, 1_500_000_000, 454_545_454, &rate(), FEES).unwrap();
+        let worsening = quote_swap(SwapDirection::SolToLst, 55_000_000, 1_500_000_000, 454_545_454, &rate(), FEES).unwrap();
+        assert!(restoring.fee_bps < 30);
+        assert!(worsening.fee_bps > 30);
+    }
+
+    #[test]
+    fn test_output_bounded_by_reserves() {
+        assert!(quote_swap(SwapDirection::SolToLst, 1_100_000_000, 0, 100, &rate(), FEES).is_err());
+        assert!(quote_swap(SwapDirection::LstToSol, 1_000, 100, 0, &ProtocolReserves::default(), FEES).is_err());
+    }
+}
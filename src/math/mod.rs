@@ -0,0 +1,5 @@
+pub mod lst_swap;
+pub mod risk_adjusted_curve;
+
+pub use lst_swap::*;
+pub use risk_adjusted_curve::*;
@@ -1,15 +1,13 @@
 use anchor_lang::prelude::*;
+use solswap_math::risk::{self, RiskMetrics};
+use crate::error::math_error;
 use crate::state::pool::PoolState;
 
-/// Constants for risk adjustment calculations
-pub const BASE_RISK_MULTIPLIER: u64 = 10_000; // 1.0 in basis points
-pub const MIN_RISK_MULTIPLIER: u64 = 5_000;   // 0.5 in basis points
-pub const MAX_RISK_MULTIPLIER: u64 = 15_000;  // 1.5 in basis points
-
-/// Weights for different risk components (must sum to 10000)
-pub const VALIDATOR_PERFORMANCE_WEIGHT: u64 = 4_000;  // 40%
-pub const RESERVE_RATIO_WEIGHT: u64 = 4_000;         // 40%
-pub const DECENTRALIZATION_WEIGHT: u64 = 2_000;      // 20%
+/// Risk multiplier bounds and component weights, shared with off-chain quoters
+pub use solswap_math::risk::{
+    BASE_RISK_MULTIPLIER, DECENTRALIZATION_WEIGHT, MAX_RISK_MULTIPLIER, MIN_RISK_MULTIPLIER,
+    RESERVE_RATIO_WEIGHT, VALIDATOR_PERFORMANCE_WEIGHT,
+};
 
 /// Calculates the adjusted output amount for a swap, incorporating risk metrics
 pub fn calculate_swap_with_risk_adjustment(
@@ -40,70 +38,16 @@ pub fn calculate_risk_multiplier(
     reserve_ratio: u64,
     decentralization_score: u64,
 ) -> Result<u64> {
-    // Normalize each metric to a 0-10000 scale
-    let normalized_performance = normalize_metric(validator_performance)?;
-    let normalized_reserves = normalize_metric(reserve_ratio)?;
-    let normalized_decentralization = normalize_metric(decentralization_score)?;
-    
-    // Calculate weighted components
-    let performance_component = normalized_performance
-        .checked_mul(VALIDATOR_PERFORMANCE_WEIGHT)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::MathOverflow)?;
-        
-    let reserve_component = normalized_reserves
-        .checked_mul(RESERVE_RATIO_WEIGHT)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::MathOverflow)?;
-        
-    let decentralization_component = normalized_decentralization
-        .checked_mul(DECENTRALIZATION_WEIGHT)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(10_000)
-        .ok_or(ErrorCode::MathOverflow)?;
-    
-    // Combine components and scale to final multiplier
-    let base_score = performance_component
-        .checked_add(reserve_component)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_add(decentralization_component)
-        .ok_or(ErrorCode::MathOverflow)?;
-    
-    // Convert base score to multiplier range
-    let multiplier_range = MAX_RISK_MULTIPLIER
-        .checked_sub(MIN_RISK_MULTIPLIER)
-        .ok_or(ErrorCode::MathOverflow)?;
-    
-    let risk_multiplier = MIN_RISK_MULTIPLIER
-        .checked_add(
-            base_score
-                .checked_mul(multiplier_range)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(10_000)
-                .ok_or(ErrorCode::MathOverflow)?
-        )
-        .ok_or(ErrorCode::MathOverflow)?;
-    
-    Ok(risk_multiplier)
-}
-
-/// Normalizes a metric to a 0-10000 scale (basis points)
-fn normalize_metric(metric: u64) -> Result<u64> {
-    if metric > 10_000 {
-        return Ok(10_000);
-    }
-    Ok(metric)
+    Ok(risk::risk_multiplier(RiskMetrics {
+        validator_performance,
+        reserve_ratio,
+        decentralization_score,
+    }))
 }
 
 /// Applies a risk multiplier (in basis points) to an amount
 fn apply_risk_multiplier(amount: u64, multiplier: u64) -> Result<u64> {
-    amount
-        .checked_mul(multiplier)
-        .ok_or(ErrorCode::MathOverflow)?
-        .checked_div(BASE_RISK_MULTIPLIER)
-        .ok_or(ErrorCode::MathOverflow)
+    risk::apply_risk_multiplier(amount, multiplier).map_err(math_error)
 }
 
 #[cfg(test)]
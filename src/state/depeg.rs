@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::state::pool::TradingStatus;
+use crate::state::reserves::ReportedReserves;
+
+/// Oracle prices quote the LST in SOL with 9 decimals (1 SOL = 1_000_000_000)
+pub const ORACLE_PRICE_PRECISION: u128 = 1_000_000_000;
+/// Oracle EMA older than this cannot be used for depeg checks
+pub const MAX_ORACLE_AGE: i64 = 60;
+
+/// Discounts to the fair stake-pool rate at which trading is restricted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DepegConfig {
+    /// Discount (basis points) that moves the LST into sell-only mode
+    pub sell_only_threshold: u16,
+    /// Discount (basis points) that quarantines the LST entirely
+    pub quarantine_threshold: u16,
+}
+
+impl Default for DepegConfig {
+    fn default() -> Self {
+        Self {
+            sell_only_threshold: 200,    // 2% below fair value
+            quarantine_threshold: 500,   // 5% below fair value
+        }
+    }
+}
+
+impl DepegConfig {
+    pub const LEN: usize = 2 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.sell_only_threshold > 0
+                && self.sell_only_threshold < self.quarantine_threshold
+                && self.quarantine_threshold <= 10_000,
+            ErrorCode::InvalidRiskParameters
+        );
+        Ok(())
+    }
+
+    /// Trading status implied by a discount to fair value
+    pub fn status_for(&self, discount: u64) -> TradingStatus {
+        if discount >= self.quarantine_threshold as u64 {
+            TradingStatus::Quarantined
+        } else if discount >= self.sell_only_threshold as u64 {
+            TradingStatus::SellOnly
+        } else {
+            TradingStatus::Active
+        }
+    }
+}
+
+/// Fair SOL price of one LST from its protocol's exchange rate, at
+/// `ORACLE_PRICE_PRECISION`
+pub fn fair_price(reported: &ReportedReserves) -> Result<u64> {
+    require!(reported.lst_supply > 0, ErrorCode::InvalidProtocolState);
+    let price = reported.sol_reserves as u128 * ORACLE_PRICE_PRECISION / reported.lst_supply as u128;
+    u64::try_from(price).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Discount of the market price to the fair price in basis points; zero when
+/// the LST trades at or above fair value
+pub fn depeg_discount(market_price: i64, fair_price: u64) -> u64 {
+    if fair_price == 0 {
+        return 0;
+    }
+    let market_price = market_price.max(0) as u128;
+    let fair_price = fair_price as u128;
+    if market_price >= fair_price {
+        return 0;
+    }
+    ((fair_price - market_price) * 10_000 / fair_price) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discount_to_fair_price() {
+        let fair = fair_price(&ReportedReserves { sol_reserves: 1_100, lst_supply: 1_000 }).unwrap();
+        assert_eq!(fair, 1_100_000_000);
+        assert_eq!(depeg_discount(1_100_000_000, fair), 0);
+        assert_eq!(depeg_discount(1_150_000_000, fair), 0);
+        assert_eq!(depeg_discount(1_078_000_000, fair), 200);
+        assert_eq!(depeg_discount(-1, fair), 10_000);
+    }
+
+    #[test]
+    fn test_status_thresholds() {
+        let config = DepegConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.status_for(199), TradingStatus::Active);
+        assert_eq!(config.status_for(200), TradingStatus::SellOnly);
+        assert_eq!(config.status_for(499), TradingStatus::SellOnly);
+        assert_eq!(config.status_for(500), TradingStatus::Quarantined);
+
+        let inverted = DepegConfig { sell_only_threshold: 500, quarantine_threshold: 200 };
+        assert!(inverted.validate().is_err());
+    }
+}
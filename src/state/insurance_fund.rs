@@ -1,4 +1,16 @@
 use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+/// Virtual shares and assets added to the share price calculation so the first
+/// depositor cannot inflate the price against later ones
+pub const VIRTUAL_SHARES: u64 = 1_000;
+pub const VIRTUAL_ASSETS: u64 = 1_000;
+/// Default time between requesting a withdrawal and completing it
+pub const DEFAULT_WITHDRAWAL_COOLDOWN: i64 = 7 * 86_400;
+/// Default cap on a single claim as a share of fund assets (basis points)
+pub const DEFAULT_MAX_CLAIM: u16 = 5_000;
+/// Default share of swap fees routed to the fund while below target (basis points)
+pub const DEFAULT_FEE_SHARE: u16 = 2_000;
 
 /// Maintains the state of the insurance fund that protects against unexpected events
 #[account]
@@ -21,6 +33,26 @@ pub struct InsuranceFund {
     pub authority: Pubkey,
     /// Bump seed for PDA
     pub bump: u8,
+    /// Pool this fund underwrites
+    pub pool: Pubkey,
+    /// Token the fund holds
+    pub asset_mint: Pubkey,
+    /// Token account holding the fund's assets
+    pub vault: Pubkey,
+    /// Mint of the insurance-share tokens issued to underwriters
+    pub share_mint: Pubkey,
+    /// Insurance shares outstanding, including those escrowed for withdrawal
+    pub total_shares: u64,
+    /// Seconds between requesting a withdrawal and completing it
+    pub withdrawal_cooldown: i64,
+    /// Cap on a single claim as a share of fund assets (basis points)
+    pub max_claim: u16,
+    /// Number of insurance events paid out
+    pub claim_count: u32,
+    /// Share of swap fees routed to the fund while below target (basis points)
+    pub fee_share: u16,
+    /// Fees routed into the fund and not yet returned to LPs
+    pub fee_contributions: u64,
 }
 
 impl InsuranceFund {
@@ -33,10 +65,28 @@ impl InsuranceFund {
         8 +    // last_rebalance
         8 +    // deployed_amount
         32 +   // authority
-        1;     // bump
+        1 +    // bump
+        32 +   // pool
+        32 +   // asset_mint
+        32 +   // vault
+        32 +   // share_mint
+        8 +    // total_shares
+        8 +    // withdrawal_cooldown
+        2 +    // max_claim
+        4 +    // claim_count
+        2 +    // fee_share
+        8;     // fee_contributions
 
     /// Initialize a new insurance fund with default parameters
-    pub fn new(authority: Pubkey, bump: u8) -> Self {
+    pub fn new(
+        authority: Pubkey,
+        bump: u8,
+        pool: Pubkey,
+        asset_mint: Pubkey,
+        vault: Pubkey,
+        share_mint: Pubkey,
+        withdrawal_cooldown: i64,
+    ) -> Self {
         Self {
             total_assets: 0,
             target_allocation: 500,     // 5% default target
@@ -47,6 +97,16 @@ impl InsuranceFund {
             deployed_amount: 0,
             authority,
             bump,
+            pool,
+            asset_mint,
+            vault,
+            share_mint,
+            total_shares: 0,
+            withdrawal_cooldown,
+            max_claim: DEFAULT_MAX_CLAIM,
+            claim_count: 0,
+            fee_share: DEFAULT_FEE_SHARE,
+            fee_contributions: 0,
         }
     }
 
@@ -62,4 +122,266 @@ impl InsuranceFund {
         self.current_risk_level = risk_level;
         Ok(())
     }
+
+    /// Shares minted for depositing `amount`, rounded down in the fund's favour
+    pub fn shares_for_deposit(&self, amount: u64) -> Result<u64> {
+        let shares = amount as u128 * (self.total_shares as u128 + VIRTUAL_SHARES as u128)
+            / (self.total_assets as u128 + VIRTUAL_ASSETS as u128);
+        u64::try_from(shares).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Assets paid out for redeeming `shares`: an exact pro-rata share of the
+    /// fund, rounded down in the fund's favour
+    pub fn assets_for_shares(&self, shares: u64) -> Result<u64> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+        let assets = shares as u128 * self.total_assets as u128 / self.total_shares as u128;
+        u64::try_from(assets).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Records a deposit, returning the shares to mint
+    pub fn deposit(&mut self, amount: u64) -> Result<u64> {
+        let shares = self.shares_for_deposit(amount)?;
+        require!(shares > 0, ErrorCode::InvalidInsuranceParams);
+        self.total_assets = self.total_assets.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        self.total_shares = self.total_shares.checked_add(shares).ok_or(ErrorCode::MathOverflow)?;
+        Ok(shares)
+    }
+
+    /// Records a redemption of `shares`, returning the assets to pay out
+    pub fn withdraw(&mut self, shares: u64) -> Result<u64> {
+        let assets = self.assets_for_shares(shares)?;
+        self.total_assets = self.total_assets.checked_sub(assets).ok_or(ErrorCode::MathOverflow)?;
+        self.total_shares = self.total_shares.checked_sub(shares).ok_or(ErrorCode::MathOverflow)?;
+        Ok(assets)
+    }
+
+    /// Credits fees paid into the vault, raising the share price for all underwriters
+    pub fn credit_fees(&mut self, amount: u64) -> Result<()> {
+        self.total_assets = self.total_assets.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records fees routed into the fund while it is below target
+    pub fn contribute_fees(&mut self, amount: u64) -> Result<()> {
+        self.credit_fees(amount)?;
+        self.fee_contributions = self.fee_contributions.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records fee contributions returned to LPs
+    pub fn release_fees(&mut self, amount: u64) -> Result<()> {
+        self.total_assets = self.total_assets.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+        self.fee_contributions = self.fee_contributions.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records a payout to the pool. The loss falls on all shares pro rata,
+    /// including those escrowed for withdrawal.
+    pub fn deploy(&mut self, payout: u64) -> Result<()> {
+        self.total_assets = self.total_assets.checked_sub(payout).ok_or(ErrorCode::MathOverflow)?;
+        self.deployed_amount = self.deployed_amount.checked_add(payout).ok_or(ErrorCode::MathOverflow)?;
+        self.claim_count = self.claim_count.saturating_add(1);
+        Ok(())
+    }
+}
+
+/// Split of collected swap fees between LPs and the insurance fund
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeRoute {
+    /// Fees moved into the insurance vault
+    pub to_insurance: u64,
+    /// Fees left with LPs
+    pub to_lps: u64,
+    /// Earlier fee contributions returned from the vault to LPs
+    pub released: u64,
+}
+
+/// Routes `fees` according to the fund's allocation of `pool_liquidity`.
+///
+/// Below the target allocation the fund takes `fee_share` of fees, never more
+/// than it needs to reach target. Above the maximum allocation, fees it took
+/// earlier are returned to LPs down to the maximum; underwriter deposits are
+/// never released this way.
+pub fn route_fees(fund: &InsuranceFund, fees: u64, pool_liquidity: u64) -> Result<FeeRoute> {
+    require!(fund.fee_share <= 10_000, ErrorCode::InvalidFeeConfig);
+
+    let target = (pool_liquidity as u128 * fund.target_allocation as u128 / 10_000) as u64;
+    let maximum = (pool_liquidity as u128 * fund.maximum_allocation as u128 / 10_000) as u64;
+
+    let mut route = FeeRoute { to_insurance: 0, to_lps: fees, released: 0 };
+    if fund.total_assets < target {
+        let share = (fees as u128 * fund.fee_share as u128 / 10_000) as u64;
+        route.to_insurance = share.min(target - fund.total_assets);
+        route.to_lps = fees - route.to_insurance;
+    } else if fund.total_assets > maximum {
+        route.released = (fund.total_assets - maximum).min(fund.fee_contributions);
+    }
+
+    Ok(route)
+}
+
+/// Outcome of an insurance claim
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InsurancePayout {
+    /// LP loss from the newly uncompensated shortfall
+    pub loss: u64,
+    /// Amount the fund pays towards it
+    pub payout: u64,
+}
+
+/// Sizes a claim for an LST shortfall.
+///
+/// `insured_value` is the SOL value of the pool's LST holdings at the last
+/// fully backed rate. Only the shortfall between `compensated_ratio` (the
+/// reserve ratio already paid out for) and `reserve_ratio` counts as new loss,
+/// so a persisting shortfall is never paid twice. The payout is capped at
+/// `max_claim` basis points of the fund's assets.
+pub fn calculate_insurance_payout(
+    insured_value: u64,
+    compensated_ratio: u64,
+    reserve_ratio: u64,
+    total_assets: u64,
+    max_claim: u16,
+) -> Result<InsurancePayout> {
+    require!(max_claim <= 10_000, ErrorCode::InvalidInsuranceParams);
+
+    let shortfall = compensated_ratio.saturating_sub(reserve_ratio) as u128;
+    let loss = insured_value as u128 * shortfall / 10_000;
+    let cap = total_assets as u128 * max_claim as u128 / 10_000;
+
+    Ok(InsurancePayout {
+        loss: u64::try_from(loss).map_err(|_| error!(ErrorCode::MathOverflow))?,
+        payout: loss.min(cap) as u64,
+    })
+}
+
+/// A pending withdrawal whose shares are held in escrow until the cooldown
+/// ends. Escrowed shares stay exposed to claims against the fund.
+#[account]
+pub struct InsuranceWithdrawal {
+    pub fund: Pubkey,
+    pub owner: Pubkey,
+    /// Shares held in escrow
+    pub shares: u64,
+    /// Earliest time the withdrawal can complete
+    pub unlock_ts: i64,
+    pub bump: u8,
+}
+
+impl InsuranceWithdrawal {
+    pub const SEED: &'static [u8] = b"insurance_withdrawal";
+
+    pub const LEN: usize = 8 +    // discriminator
+        32 +   // fund
+        32 +   // owner
+        8 +    // shares
+        8 +    // unlock_ts
+        1;     // bump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fund() -> InsuranceFund {
+        InsuranceFund::new(
+            Pubkey::default(),
+            0,
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            Pubkey::default(),
+            DEFAULT_WITHDRAWAL_COOLDOWN,
+        )
+    }
+
+    #[test]
+    fn test_fees_raise_share_price() {
+        let mut fund = fund();
+        let alice = fund.deposit(1_000_000).unwrap();
+        assert_eq!(alice, 1_000_000);
+
+        fund.credit_fees(100_000).unwrap();
+        let bob = fund.deposit(1_100_000).unwrap();
+        assert_eq!(bob, 1_000_090);
+
+        // Alice keeps the fees, less the small dilution from the virtual offset
+        assert_eq!(fund.withdraw(alice).unwrap(), 1_099_950);
+        assert_eq!(fund.withdraw(bob).unwrap(), 1_100_050);
+        assert_eq!(fund.total_shares, 0);
+        assert_eq!(fund.total_assets, 0);
+    }
+
+    #[test]
+    fn test_claim_covers_new_shortfall_only() {
+        // 1% shortfall on 1_000 SOL of LST, fully covered
+        let claim = calculate_insurance_payout(1_000_000_000_000, 10_000, 9_900, 100_000_000_000, 5_000).unwrap();
+        assert_eq!(claim, InsurancePayout { loss: 10_000_000_000, payout: 10_000_000_000 });
+
+        // Deepening to 3% only pays the additional 2%
+        let claim = calculate_insurance_payout(1_000_000_000_000, 9_900, 9_700, 100_000_000_000, 5_000).unwrap();
+        assert_eq!(claim.payout, 20_000_000_000);
+
+        // No further loss, no payout
+        let claim = calculate_insurance_payout(1_000_000_000_000, 9_700, 9_800, 100_000_000_000, 5_000).unwrap();
+        assert_eq!(claim, InsurancePayout { loss: 0, payout: 0 });
+    }
+
+    #[test]
+    fn test_claim_capped_by_fund() {
+        // 20% slashing exceeds half the fund
+        let claim = calculate_insurance_payout(1_000_000_000_000, 10_000, 8_000, 100_000_000_000, 5_000).unwrap();
+        assert_eq!(claim, InsurancePayout { loss: 200_000_000_000, payout: 50_000_000_000 });
+
+        let claim = calculate_insurance_payout(1_000_000_000_000, 10_000, 8_000, 0, 5_000).unwrap();
+        assert_eq!(claim.payout, 0);
+    }
+
+    #[test]
+    fn test_claim_loss_shared_pro_rata() {
+        let mut fund = fund();
+        let alice = fund.deposit(3_000_000).unwrap();
+        let bob = fund.deposit(1_000_000).unwrap();
+
+        fund.deploy(2_000_000).unwrap();
+        assert_eq!(fund.deployed_amount, 2_000_000);
+        assert_eq!(fund.withdraw(alice).unwrap(), 1_500_000);
+        assert_eq!(fund.withdraw(bob).unwrap(), 500_000);
+    }
+
+    #[test]
+    fn test_fee_routing_follows_allocation() {
+        let mut fund = fund();
+        fund.deposit(1_000_000).unwrap();
+
+        // 5% target of 100M liquidity is 5M: the fund takes its 20% share
+        let route = route_fees(&fund, 1_000_000, 100_000_000).unwrap();
+        assert_eq!(route, FeeRoute { to_insurance: 200_000, to_lps: 800_000, released: 0 });
+
+        // ...but no more than the remaining deficit
+        fund.contribute_fees(3_900_000).unwrap();
+        let route = route_fees(&fund, 1_000_000, 100_000_000).unwrap();
+        assert_eq!(route, FeeRoute { to_insurance: 100_000, to_lps: 900_000, released: 0 });
+
+        // At target, LPs keep everything
+        let route = route_fees(&fund, 1_000_000, 50_000_000).unwrap();
+        assert_eq!(route, FeeRoute { to_insurance: 0, to_lps: 1_000_000, released: 0 });
+
+        // Above the 10% maximum only fee contributions flow back
+        let route = route_fees(&fund, 1_000_000, 20_000_000).unwrap();
+        assert_eq!(route.released, 2_900_000);
+        let route = route_fees(&fund, 1_000_000, 1_000_000).unwrap();
+        assert_eq!(route.released, 3_900_000);
+    }
+
+    #[test]
+    fn test_donation_cannot_steal_next_deposit() {
+        let mut fund = fund();
+        fund.deposit(1).unwrap();
+        // A large credit before the next deposit does not round it to zero shares
+        fund.credit_fees(1_000_000).unwrap();
+        assert!(fund.deposit(1_000_000).unwrap() > 0);
+    }
 }
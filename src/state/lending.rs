@@ -0,0 +1,337 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+/// Offset of `liquidity` in a lending market reserve account
+pub const RESERVE_LIQUIDITY_OFFSET: usize = 104;
+/// Offset of `collateral_supply` in a lending market reserve account
+pub const RESERVE_COLLATERAL_SUPPLY_OFFSET: usize = 112;
+/// Default cap on the share of the pool's LST lent out (basis points)
+pub const DEFAULT_MAX_ALLOCATION: u16 = 3_000;
+/// Default share of the pool's LST kept idle after a shortfall withdrawal (basis points)
+pub const DEFAULT_LIQUIDITY_BUFFER: u16 = 1_000;
+
+/// Deposits a capped share of a pool's idle LST into a lending market
+#[account]
+pub struct LendingStrategy {
+    /// Pool whose LST reserve is lent out
+    pub pool: Pubkey,
+    /// Lending market program
+    pub lending_program: Pubkey,
+    /// Lending market reserve for the pool's LST
+    pub reserve: Pubkey,
+    /// Pool-owned account holding the market's collateral tokens
+    pub collateral_account: Pubkey,
+    /// Maximum share of the pool's LST to lend out (basis points)
+    pub max_allocation: u16,
+    /// Share of the pool's LST left idle when withdrawing for a swap (basis points)
+    pub liquidity_buffer: u16,
+    /// LST deposited and not yet withdrawn, excluding accrued interest
+    pub principal: u64,
+    /// Lamport value of the principal recorded in the pool's `total_borrows`
+    pub borrowed_value: u64,
+    /// Interest harvested back into the pool, in LST
+    pub total_yield: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+/// Liquidity and collateral totals of a lending market reserve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveBalances {
+    pub liquidity: u64,
+    pub collateral_supply: u64,
+}
+
+/// Step the strategy should take to reach its target position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LendingAction {
+    Deposit(u64),
+    Withdraw(u64),
+    Hold,
+}
+
+impl LendingStrategy {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 2 + 2 + 8 + 8 + 8 + 1;
+    pub const SEED: &'static [u8] = b"lending_strategy";
+
+    pub fn validate_params(max_allocation: u16, liquidity_buffer: u16) -> Result<()> {
+        require!(
+            max_allocation as u32 + liquidity_buffer as u32 <= 10_000,
+            ErrorCode::InvalidLendingParams
+        );
+        Ok(())
+    }
+
+    /// Principal to hold in the market given `idle` LST in the pool reserve
+    pub fn target_principal(&self, idle: u64) -> u64 {
+        let total = idle as u128 + self.principal as u128;
+        (total * self.max_allocation as u128 / 10_000) as u64
+    }
+
+    pub fn rebalance_action(&self, idle: u64) -> LendingAction {
+        let target = self.target_principal(idle);
+        if target > self.principal {
+            LendingAction::Deposit(target - self.principal)
+        } else if target < self.principal {
+            LendingAction::Withdraw(self.principal - target)
+        } else {
+            LendingAction::Hold
+        }
+    }
+
+    /// LST that can be deposited from `idle` while leaving the liquidity buffer
+    /// in the reserve
+    pub fn deposit_limit(&self, idle: u64) -> u64 {
+        let total = idle as u128 + self.principal as u128;
+        let buffer = (total * self.liquidity_buffer as u128 / 10_000) as u64;
+        idle.saturating_sub(buffer)
+    }
+
+    /// Principal to withdraw so `needed` LST can leave the reserve and still
+    /// leave the liquidity buffer idle, or None if the reserve already covers it
+    pub fn shortfall_withdrawal(&self, idle: u64, needed: u64) -> Option<u64> {
+        if needed <= idle {
+            return None;
+        }
+        let total = idle as u128 + self.principal as u128;
+        let buffer = (total.saturating_sub(needed as u128) * self.liquidity_buffer as u128 / 10_000) as u64;
+        Some((needed - idle).saturating_add(buffer).min(self.principal))
+    }
+
+    /// Records `amount` principal deposited, worth `value` lamports
+    pub fn record_deposit(&mut self, amount: u64, value: u64) -> Result<()> {
+        self.principal = self.principal.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        self.borrowed_value = self.borrowed_value.checked_add(value).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records `amount` principal withdrawn and returns the lamport value to
+    /// release from the pool's `total_borrows`
+    pub fn record_withdrawal(&mut self, amount: u64) -> u64 {
+        let amount = amount.min(self.principal);
+        if amount == 0 {
+            return 0;
+        }
+        let value = (self.borrowed_value as u128 * amount as u128 / self.principal as u128) as u64;
+        self.principal -= amount;
+        self.borrowed_value -= value;
+        value
+    }
+
+    /// Records `received` LST redeemed from the market, principal first, and
+    /// returns the value to release from `total_borrows` and the interest received
+    pub fn record_redemption(&mut self, received: u64) -> (u64, u64) {
+        let principal = received.min(self.principal);
+        (self.record_withdrawal(principal), received - principal)
+    }
+}
+
+impl ReserveBalances {
+    pub fn read(reserve: &AccountInfo) -> Result<Self> {
+        let data = reserve.try_borrow_data()?;
+        let read_u64 = |offset: usize| -> Result<u64> {
+            let bytes = data
+                .get(offset..offset + 8)
+                .ok_or(ErrorCode::InvalidLendingReserve)?;
+            Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        Ok(Self {
+            liquidity: read_u64(RESERVE_LIQUIDITY_OFFSET)?,
+            collateral_supply: read_u64(RESERVE_COLLATERAL_SUPPLY_OFFSET)?,
+        })
+    }
+
+    /// Liquidity redeemable for `collateral`, rounded down
+    pub fn position_value(&self, collateral: u64) -> u64 {
+        if self.collateral_supply == 0 {
+            return 0;
+        }
+        (collateral as u128 * self.liquidity as u128 / self.collateral_supply as u128) as u64
+    }
+
+    /// Collateral to redeem for at least `amount` liquidity, capped at `held`
+    pub fn collateral_for(&self, amount: u64, held: u64) -> u64 {
+        if self.liquidity == 0 {
+            return 0;
+        }
+        let collateral = (amount as u128 * self.collateral_supply as u128)
+            .div_ceil(self.liquidity as u128);
+        collateral.min(held as u128) as u64
+    }
+
+    /// Collateral redeemable for no more than `amount` liquidity
+    pub fn collateral_within(&self, amount: u64) -> u64 {
+        if self.liquidity == 0 {
+            return 0;
+        }
+        (amount as u128 * self.collateral_supply as u128 / self.liquidity as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::pool::PoolState;
+
+    /// A lending market reserve that mints collateral at the current exchange
+    /// rate and redeems it for a share of the liquidity
+    struct MockMarket {
+        balances: ReserveBalances,
+        held: u64,
+    }
+
+    impl MockMarket {
+        fn new() -> Self {
+            Self { balances: ReserveBalances { liquidity: 0, collateral_supply: 0 }, held: 0 }
+        }
+
+        fn deposit(&mut self, amount: u64) {
+            let minted = if self.balances.collateral_supply == 0 {
+                amount
+            } else {
+                self.balances.collateral_within(amount)
+            };
+            self.balances.liquidity += amount;
+            self.balances.collateral_supply += minted;
+            self.held += minted;
+        }
+
+        fn accrue(&mut self, interest: u64) {
+            self.balances.liquidity += interest;
+        }
+
+        fn withdraw(&mut self, collateral: u64) -> u64 {
+            let received = self.balances.position_value(collateral);
+            self.balances.liquidity -= received;
+            self.balances.collateral_supply -= collateral;
+            self.held -= collateral;
+            received
+        }
+    }
+
+    fn strategy(principal: u64) -> LendingStrategy {
+        LendingStrategy {
+            pool: Pubkey::default(),
+            lending_program: Pubkey::default(),
+            reserve: Pubkey::default(),
+            collateral_account: Pubkey::default(),
+            max_allocation: DEFAULT_MAX_ALLOCATION,
+            liquidity_buffer: DEFAULT_LIQUIDITY_BUFFER,
+            principal,
+            borrowed_value: principal,
+            total_yield: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_rebalance_towards_allocation_cap() {
+        assert_eq!(strategy(0).rebalance_action(1_000), LendingAction::Deposit(300));
+        assert_eq!(strategy(300).rebalance_action(700), LendingAction::Hold);
+        // Swaps drained the idle reserve, so part of the position comes back
+        assert_eq!(strategy(300).rebalance_action(200), LendingAction::Withdraw(150));
+    }
+
+    #[test]
+    fn test_shortfall_withdrawal_refills_buffer() {
+        let strategy = strategy(300);
+        assert_eq!(strategy.shortfall_withdrawal(700, 500), None);
+        // 100 short, plus 10% of the 200 left after the swap
+        assert_eq!(strategy.shortfall_withdrawal(400, 500), Some(120));
+        assert_eq!(strategy.shortfall_withdrawal(0, 300), Some(300));
+    }
+
+    #[test]
+    fn test_position_accrues_interest() {
+        let balances = ReserveBalances { liquidity: 1_100, collateral_supply: 1_000 };
+        assert_eq!(balances.position_value(500), 550);
+        assert_eq!(balances.collateral_for(550, 500), 500);
+        assert_eq!(balances.collateral_for(551, 500), 500);
+        // Harvesting takes only the interest and leaves the principal lent out
+        assert_eq!(balances.collateral_within(50), 45);
+        assert!(balances.position_value(45) <= 50);
+    }
+
+    #[test]
+    fn test_withdrawal_releases_proportional_value() {
+        let mut strategy = strategy(0);
+        strategy.record_deposit(300, 330).unwrap();
+        assert_eq!(strategy.record_withdrawal(100), 110);
+        assert_eq!(strategy.record_withdrawal(500), 220);
+        assert_eq!((strategy.principal, strategy.borrowed_value), (0, 0));
+    }
+
+    #[test]
+    fn test_deposit_leaves_liquidity_buffer() {
+        assert_eq!(strategy(0).deposit_limit(1_000), 900);
+        // The buffer is a share of all the pool's LST, lent or not
+        assert_eq!(strategy(300).deposit_limit(400), 330);
+        assert_eq!(strategy(900).deposit_limit(50), 0);
+    }
+
+    #[test]
+    fn test_mock_market_pays_interest() {
+        let mut market = MockMarket::new();
+        market.deposit(1_000);
+        market.accrue(100);
+        // A later depositor does not share in interest already accrued
+        market.deposit(1_100);
+        assert_eq!(market.balances.position_value(1_000), 1_100);
+
+        let collateral = market.balances.collateral_for(550, market.held);
+        assert_eq!(market.withdraw(collateral), 550);
+    }
+
+    #[test]
+    fn test_deposit_swap_shortfall_withdraw() {
+        let mut pool = PoolState::new(0, Pubkey::default(), Pubkey::default(), Pubkey::default(), Pubkey::default());
+        pool.protocol_reserves.sol_reserves = 1_100;
+        pool.protocol_reserves.lst_supply = 1_000;
+        pool.record_deposit(1_100_000).unwrap();
+        let rate = pool.protocol_reserves.clone();
+        let value = |amount: u64| rate.lamports_for(amount).unwrap() as u64;
+
+        let mut strategy = strategy(0);
+        strategy.borrowed_value = 0;
+        let mut market = MockMarket::new();
+        let mut idle = 1_000_000;
+
+        // Rebalance lends out up to the allocation cap
+        let LendingAction::Deposit(amount) = strategy.rebalance_action(idle) else {
+            panic!("expected a deposit");
+        };
+        let amount = amount.min(strategy.deposit_limit(idle));
+        assert_eq!(amount, 300_000);
+        pool.record_borrow(value(amount)).unwrap();
+        strategy.record_deposit(amount, value(amount)).unwrap();
+        market.deposit(amount);
+        idle -= amount;
+        market.accrue(30_000);
+
+        // A swap for more than the reserve holds pulls principal back first
+        let needed = 800_000;
+        let shortfall = strategy.shortfall_withdrawal(idle, needed).unwrap();
+        assert_eq!(shortfall, 120_000);
+        let received = market.withdraw(market.balances.collateral_for(shortfall, market.held));
+        assert_eq!(received, 120_000);
+        let (released, interest) = strategy.record_redemption(received);
+        pool.record_repayment(released, value(interest)).unwrap();
+        idle += received;
+
+        // The swap leaves the liquidity buffer behind
+        idle -= needed;
+        assert_eq!(idle, 20_000);
+        assert_eq!(strategy.principal, 180_000);
+        assert_eq!(pool.total_borrows, strategy.borrowed_value);
+
+        // Unwinding the position returns the principal and credits the interest
+        let received = market.withdraw(market.held);
+        assert_eq!(received, 210_000);
+        let (released, interest) = strategy.record_redemption(received);
+        assert_eq!(interest, 30_000);
+        pool.record_repayment(released, value(interest)).unwrap();
+        assert_eq!((strategy.principal, strategy.borrowed_value), (0, 0));
+        assert_eq!(pool.total_borrows, 0);
+        assert_eq!(pool.total_deposits, 1_133_000);
+    }
+}
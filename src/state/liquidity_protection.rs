@@ -1,25 +1,22 @@
 use anchor_lang::prelude::*;
+use solswap_math::fees;
 use crate::state::pool::PoolState;
 use crate::state::insurance_fund::InsuranceFund;
+use crate::state::reserves::reserve_risk_level;
 
-/// Constants for liquidity protection calculations
-pub const BASE_FEE_RATE: u64 = 30;           // 0.3% base fee in basis points
-pub const MIN_FEE_RATE: u64 = 10;            // 0.1% minimum fee
-pub const MAX_FEE_RATE: u64 = 100;           // 1% maximum fee
-pub const OPTIMAL_UTILIZATION: u64 = 8_000;   // 80% optimal utilization
-pub const SLOPE_1: u64 = 20;                 // Rate of increase below optimal
-pub const SLOPE_2: u64 = 100;                // Rate of increase above optimal
+/// Fee and utilization constants, shared with off-chain quoters
+pub use solswap_math::fees::{
+    BALANCE_FEE_SENSITIVITY, BASE_FEE_RATE, MAX_DIRECTIONAL_MULTIPLIER, MAX_FEE_CHANGE, MAX_FEE_RATE,
+    MIN_DIRECTIONAL_MULTIPLIER, MIN_FEE_RATE, OPTIMAL_UTILIZATION, SLOPE_1, SLOPE_2,
+};
+pub use solswap_math::fees::{balance_deviation, directional_fee, step_fee};
 
 /// Calculates current pool utilization in basis points (0-10000)
 pub fn calculate_pool_utilization(
     total_deposits: u64,
     total_borrows: u64,
 ) -> Result<u64> {
-    if total_deposits == 0 {
-        return Ok(0);
-    }
-
-    Ok(((total_borrows as u128 * 10_000) / total_deposits as u128) as u64)
+    Ok(fees::pool_utilization(total_deposits, total_borrows))
 }
 
 /// Assesses current risk level based on various metrics
@@ -27,22 +24,18 @@ pub fn assess_current_risk(
     pool_state: &PoolState,
     utilization: u64,
 ) -> Result<u16> {
-    // Weight different risk factors
-    let utilization_risk = if utilization > OPTIMAL_UTILIZATION {
-        // Higher risk when utilization exceeds optimal
-        ((utilization - OPTIMAL_UTILIZATION) * 60) / (10_000 - OPTIMAL_UTILIZATION)
+    // A verified reserve shortfall overrides the other factors
+    let reserve_risk = if pool_state.protocol_reserves.last_verified > 0 {
+        reserve_risk_level(pool_state.lst_performance_metrics.reserve_ratio) as u64
     } else {
-        // Lower risk below optimal utilization
-        (utilization * 40) / OPTIMAL_UTILIZATION
+        0
     };
 
-    // Consider LST-specific risks
-    let lst_risk = ((10_000 - pool_state.lst_performance_metrics.validator_performance) * 40) / 10_000;
-
-    let total_risk = (utilization_risk + lst_risk)
-        .min(10_000) as u16;
-
-    Ok(total_risk)
+    Ok(fees::risk_level(
+        utilization,
+        pool_state.lst_performance_metrics.validator_performance,
+        reserve_risk,
+    ))
 }
 
 /// Calculates dynamic fee based on utilization and risk level
@@ -51,20 +44,32 @@ pub fn calculate_dynamic_fee(
     risk_level: u16,
     base_fee: u64,
 ) -> Result<u64> {
-    let util_multiplier = if utilization <= OPTIMAL_UTILIZATION {
-        // Below optimal: gradual increase
-        BASE_FEE_RATE + (utilization * SLOPE_1) / OPTIMAL_UTILIZATION
-    } else {
-        // Above optimal: steeper increase
-        let excess_util = utilization - OPTIMAL_UTILIZATION;
-        BASE_FEE_RATE + SLOPE_1 + 
-            (excess_util * SLOPE_2) / (10_000 - OPTIMAL_UTILIZATION)
-    };
+    Ok(fees::dynamic_fee(utilization, risk_level, base_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_steps_are_bounded() {
+        assert_eq!(step_fee(30, 100, 5), 35);
+        assert_eq!(step_fee(30, 10, 5), 25);
+        assert_eq!(step_fee(30, 32, 5), 32);
+    }
+
+    #[test]
+    fn test_rebalancing_trades_pay_less() {
+        assert_eq!(balance_deviation(600, 400), 1_000);
+        assert_eq!(balance_deviation(500, 500), 0);
 
-    // Apply risk adjustment
-    let risk_multiplier = 10_000 + (risk_level as u64 * 5_000) / 10_000;
-    let fee_rate = (util_multiplier * risk_multiplier) / 10_000;
+        // Restoring balance by 2% earns a 20% discount; the reverse pays 20% more
+        assert_eq!(directional_fee(50, 1_000, 800, 10, 100), 40);
+        assert_eq!(directional_fee(50, 800, 1_000, 10, 100), 60);
+        assert_eq!(directional_fee(50, 1_000, 1_000, 10, 100), 50);
 
-    // Ensure fee is within bounds
-    Ok(fee_rate.clamp(MIN_FEE_RATE, MAX_FEE_RATE))
+        // Large shifts stop at half and double, then at the pool bounds
+        assert_eq!(directional_fee(50, 5_000, 0, 10, 100), 25);
+        assert_eq!(directional_fee(50, 0, 5_000, 10, 80), 80);
+    }
 }
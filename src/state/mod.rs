@@ -0,0 +1,21 @@
+pub mod depeg;
+pub mod insurance_fund;
+pub mod lending;
+pub mod liquidity_protection;
+pub mod lst_state;
+pub mod oracle;
+pub mod pool;
+pub mod reserves;
+pub mod validator;
+pub mod validator_registry;
+
+pub use depeg::*;
+pub use insurance_fund::*;
+pub use lending::*;
+pub use liquidity_protection::*;
+pub use lst_state::*;
+pub use oracle::*;
+pub use pool::*;
+pub use reserves::*;
+pub use validator::*;
+pub use validator_registry::*;
@@ -1,6 +1,20 @@
 use anchor_lang::prelude::*;
-use pyth_sdk_solana::Price as PythPrice;
 use switchboard_v2::AggregatorAccountData;
+use crate::error::ErrorCode;
+use crate::state::depeg::ORACLE_PRICE_PRECISION;
+
+pub use switchboard_v2::SWITCHBOARD_PROGRAM_ID;
+
+/// Pyth oracle program that owns price accounts
+pub mod pyth_program {
+    use anchor_lang::prelude::*;
+    declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+}
+
+/// Feed prices published longer ago than this are rejected (seconds)
+pub const MAX_FEED_AGE: i64 = 60;
+/// Widest confidence interval accepted, relative to the price (basis points)
+pub const MAX_CONFIDENCE_BPS: u128 = 100;
 
 /// Stores oracle state including EMA calculations and historical data
 #[account]
@@ -19,6 +33,10 @@ pub struct OracleState {
     pub pyth_weight: u16,
     /// Weight assigned to Switchboard oracle (basis points)
     pub switchboard_weight: u16,
+    /// Pyth price account the oracle reads
+    pub pyth_feed: Pubkey,
+    /// Switchboard aggregator the oracle reads
+    pub switchboard_feed: Pubkey,
 }
 
 impl OracleState {
@@ -29,10 +47,12 @@ impl OracleState {
         1200 + // price_history (150 * 8)
         1 +   // history_index
         2 +   // pyth_weight
-        2;    // switchboard_weight
+        2 +   // switchboard_weight
+        32 +  // pyth_feed
+        32;   // switchboard_feed
 
-    /// Initialize oracle state with default values
-    pub fn new() -> Self {
+    /// Initialize oracle state over its feeds with default values
+    pub fn new(pyth_feed: Pubkey, switchboard_feed: Pubkey) -> Self {
         Self {
             last_price: 0,
             ema_price: 0,
@@ -41,6 +61,8 @@ impl OracleState {
             history_index: 0,
             pyth_weight: 5000,  // 50% weight by default
             switchboard_weight: 5000, // 50% weight by default
+            pyth_feed,
+            switchboard_feed,
         }
     }
 
@@ -59,25 +81,97 @@ impl OracleState {
     }
 }
 
-/// Helper functions for price calculation
+impl solswap_math::quoter::PriceOracle for OracleState {
+    fn price(&self) -> i64 {
+        self.ema_price
+    }
+}
+
+/// Helper functions for price calculation. Both feeds must be the oracle's
+/// own, recently published and within the confidence limit; prices are
+/// returned at `ORACLE_PRICE_PRECISION`.
 pub fn get_weighted_median_price(
     pyth_price: &AccountInfo,
     switchboard_feed: &AccountInfo,
     oracle_state: &OracleState,
+    now: i64,
 ) -> Result<i64> {
+    require_keys_eq!(pyth_price.key(), oracle_state.pyth_feed, ErrorCode::InvalidOracleFeed);
+    require_keys_eq!(*pyth_price.owner, pyth_program::ID, ErrorCode::InvalidOracleFeed);
+    require_keys_eq!(switchboard_feed.key(), oracle_state.switchboard_feed, ErrorCode::InvalidOracleFeed);
+    require_keys_eq!(*switchboard_feed.owner, SWITCHBOARD_PROGRAM_ID, ErrorCode::InvalidOracleFeed);
+
     // Get Pyth price
-    let pyth_price_data = pyth_price.try_borrow_data()?;
-    let pyth_price = pyth_sdk_solana::load_price(&pyth_price_data)?;
-    let pyth_price_i64 = pyth_price.agg.price;
+    let pyth_feed = pyth_sdk_solana::load_price_feed_from_account_info(pyth_price)
+        .map_err(|_| error!(ErrorCode::InvalidOracleFeed))?;
+    let pyth = pyth_feed
+        .get_price_no_older_than(now, MAX_FEED_AGE as u64)
+        .ok_or(ErrorCode::StaleOracle)?;
+    require!(pyth.expo <= 0, ErrorCode::InvalidOracleFeed);
+    let decimals = pyth.expo.unsigned_abs();
+    let pyth_price_i64 = checked_price(
+        to_oracle_precision(pyth.price as i128, decimals)?,
+        to_oracle_precision(pyth.conf as i128, decimals)?,
+    )?;
 
     // Get Switchboard price
     let switchboard_state = AggregatorAccountData::new(switchboard_feed)?;
+    switchboard_state
+        .check_staleness(now, MAX_FEED_AGE)
+        .map_err(|_| error!(ErrorCode::StaleOracle))?;
     let switchboard_result = switchboard_state.get_result()?;
-    let switchboard_price_i64 = switchboard_result.mantissa;
+    let deviation = switchboard_state.latest_confirmed_round.std_deviation;
+    let switchboard_price_i64 = checked_price(
+        to_oracle_precision(switchboard_result.mantissa, switchboard_result.scale)?,
+        to_oracle_precision(deviation.mantissa, deviation.scale)?,
+    )?;
 
     // Calculate weighted average
     let weighted_price = (pyth_price_i64 as i128 * oracle_state.pyth_weight as i128 +
         switchboard_price_i64 as i128 * oracle_state.switchboard_weight as i128) / 10000;
 
     Ok(weighted_price as i64)
+}
+
+/// Converts a feed value with `decimals` decimal places to
+/// `ORACLE_PRICE_PRECISION`
+fn to_oracle_precision(value: i128, decimals: u32) -> Result<i64> {
+    let scale = 10i128.checked_pow(decimals).ok_or(ErrorCode::MathOverflow)?;
+    let value = value
+        .checked_mul(ORACLE_PRICE_PRECISION as i128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / scale;
+    i64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Returns `price` if it is positive and its confidence interval is within
+/// `MAX_CONFIDENCE_BPS` of it
+fn checked_price(price: i64, confidence: i64) -> Result<i64> {
+    require!(price > 0, ErrorCode::InvalidOracleFeed);
+    require!(
+        confidence.unsigned_abs() as u128 * 10_000 <= price as u128 * MAX_CONFIDENCE_BPS,
+        ErrorCode::LowConfidencePrice
+    );
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_prices_scale_to_oracle_precision() {
+        // 1.05 SOL quoted with 8 and 12 decimals
+        assert_eq!(to_oracle_precision(105_000_000, 8).unwrap(), 1_050_000_000);
+        assert_eq!(to_oracle_precision(1_050_000_000_000, 12).unwrap(), 1_050_000_000);
+        assert!(to_oracle_precision(i128::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wide_or_nonpositive_prices() {
+        assert_eq!(checked_price(1_000_000_000, 10_000_000).unwrap(), 1_000_000_000);
+        assert!(checked_price(1_000_000_000, 10_000_001).is_err());
+        assert!(checked_price(0, 0).is_err());
+        assert!(checked_price(-1, 0).is_err());
+    }
 }
\ No newline at end of file
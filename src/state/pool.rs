@@ -1,5 +1,39 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 use crate::state::lst_state::LSTMetrics;
+use crate::error::ErrorCode;
+use crate::state::depeg::DepegConfig;
+use crate::state::liquidity_protection::{BASE_FEE_RATE, MAX_FEE_CHANGE, MAX_FEE_RATE, MIN_FEE_RATE};
+use crate::state::reserves::{ProtocolReserves, FULL_RESERVE_RATIO};
+
+/// Whether the pool's LST can currently be traded, from least to most
+/// restrictive
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TradingStatus {
+    Active,
+    /// The pool only sells the LST: swaps that would add it to the pool are rejected
+    SellOnly,
+    /// All trading in the LST is stopped
+    Quarantined,
+}
+
+impl TradingStatus {
+    /// Whether the pool may take in the LST
+    pub fn allows_buy(&self) -> bool {
+        *self == TradingStatus::Active
+    }
+
+    /// Whether the pool may pay out the LST
+    pub fn allows_sell(&self) -> bool {
+        *self != TradingStatus::Quarantined
+    }
+}
+
+impl Default for TradingStatus {
+    fn default() -> Self {
+        TradingStatus::Active
+    }
+}
 
 #[account]
 pub struct PoolState {
@@ -10,13 +44,59 @@ pub struct PoolState {
     // LST-specific tracking
     pub lst_risk_score: u64,
     pub lst_performance_metrics: LSTMetrics,
+    /// Never updated on-chain; `solswap-indexer` reports pool volume
     pub volume_24h: u64,
     pub last_update_ts: i64,
+
+    /// Governance authority; fields from here on were appended to the
+    /// original layout
+    pub authority: Pubkey,
+
+    // Proof of reserves
+    pub lst_mint: Pubkey,
+    pub protocol_reserves: ProtocolReserves,
+    pub trading_status: TradingStatus,
+    /// Lowest reserve ratio the insurance fund has already compensated LPs for
+    pub compensated_reserve_ratio: u64,
+
+    // Depeg monitoring
+    pub oracle: Pubkey,
+    pub depeg_config: DepegConfig,
+
+    // Liquidity protection (added in version 2)
+    pub version: u8,
+    /// LP liquidity in the pool
+    pub total_deposits: u64,
+    /// Liquidity currently lent out
+    pub total_borrows: u64,
+    /// Fee before utilization and risk adjustment (basis points)
+    pub base_fee: u64,
+    /// Current dynamic fee (basis points)
+    pub fee_rate: u64,
+    /// Pool floor for the fee charged on a trade (basis points)
+    pub min_fee: u64,
+    /// Pool ceiling for the fee charged on a trade (basis points)
+    pub max_fee: u64,
+    /// Largest change to `fee_rate` in one update (basis points)
+    pub max_fee_change: u64,
+
+    // Pool token accounts, owned by the pool (added in version 2)
+    /// LP SOL liquidity, in wrapped SOL
+    pub sol_reserve: Pubkey,
+    /// The pool's LST holdings
+    pub lst_reserve: Pubkey,
+    /// Collects swap fees, in wrapped SOL, until they are swept
+    pub fee_account: Pubkey,
 }
 
 impl PoolState {
-    // Account size calculation for space allocation
-    pub const LEN: usize = 8 +  // discriminator
+    /// PDA seed; pools are keyed on their LST mint
+    pub const SEED: &'static [u8] = b"pool";
+    /// Current account layout version
+    pub const VERSION: u8 = 2;
+
+    /// Size of version 1 accounts, the original layout
+    pub const LEN_V1: usize = 8 +  // discriminator
         1 +   // is_initialized
         1 +   // bump
         8 +   // lst_risk_score
@@ -24,7 +104,28 @@ impl PoolState {
         8 +   // volume_24h
         8;    // last_update_ts
 
-    pub fn new(bump: u8) -> Self {
+    // Account size calculation for space allocation
+    pub const LEN: usize = Self::LEN_V1 +
+        32 +  // authority
+        32 +  // lst_mint
+        ProtocolReserves::LEN + // protocol_reserves
+        1 +   // trading_status
+        8 +   // compensated_reserve_ratio
+        32 +  // oracle
+        DepegConfig::LEN + // depeg_config
+        1 +   // version
+        8 +   // total_deposits
+        8 +   // total_borrows
+        8 +   // base_fee
+        8 +   // fee_rate
+        8 +   // min_fee
+        8 +   // max_fee
+        8 +   // max_fee_change
+        32 +  // sol_reserve
+        32 +  // lst_reserve
+        32;   // fee_account
+
+    pub fn new(bump: u8, authority: Pubkey, lst_mint: Pubkey, protocol: Pubkey, oracle: Pubkey) -> Self {
         Self {
             is_initialized: true,
             bump,
@@ -32,10 +133,223 @@ impl PoolState {
             lst_performance_metrics: LSTMetrics::default(),
             volume_24h: 0,
             last_update_ts: 0,
+            authority,
+            lst_mint,
+            protocol_reserves: ProtocolReserves {
+                protocol,
+                ..ProtocolReserves::default()
+            },
+            trading_status: TradingStatus::Active,
+            compensated_reserve_ratio: FULL_RESERVE_RATIO,
+            oracle,
+            depeg_config: DepegConfig::default(),
+            version: Self::VERSION,
+            total_deposits: 0,
+            total_borrows: 0,
+            base_fee: BASE_FEE_RATE,
+            fee_rate: BASE_FEE_RATE,
+            min_fee: MIN_FEE_RATE,
+            max_fee: MAX_FEE_RATE,
+            max_fee_change: MAX_FEE_CHANGE,
+            sol_reserve: Pubkey::default(),
+            lst_reserve: Pubkey::default(),
+            fee_account: Pubkey::default(),
         }
     }
+
+    /// Records the token accounts created for the pool
+    pub fn set_token_accounts(&mut self, sol_reserve: Pubkey, lst_reserve: Pubkey, fee_account: Pubkey) {
+        self.sol_reserve = sol_reserve;
+        self.lst_reserve = lst_reserve;
+        self.fee_account = fee_account;
+    }
+
+    /// Decodes a version 1 account, discriminator included
+    pub fn decode_v1(data: &[u8]) -> Result<PoolStateV1> {
+        require!(
+            data.len() >= 8 && data[..8] == PoolState::DISCRIMINATOR,
+            ErrorCode::PoolNotInitialized
+        );
+        require!(data.len() == Self::LEN_V1, ErrorCode::AlreadyMigrated);
+        PoolStateV1::deserialize(&mut &data[8..]).map_err(|_| error!(ErrorCode::PoolNotInitialized))
+    }
+
+    /// Current layout of a version 1 pool moved to the PDA with `bump`,
+    /// keeping its tracked metrics and starting the fields it did not have
+    /// from their defaults
+    pub fn from_v1(
+        v1: PoolStateV1,
+        bump: u8,
+        authority: Pubkey,
+        lst_mint: Pubkey,
+        protocol: Pubkey,
+        oracle: Pubkey,
+    ) -> Self {
+        Self {
+            is_initialized: v1.is_initialized,
+            lst_risk_score: v1.lst_risk_score,
+            lst_performance_metrics: v1.lst_performance_metrics,
+            volume_24h: v1.volume_24h,
+            last_update_ts: v1.last_update_ts,
+            ..Self::new(bump, authority, lst_mint, protocol, oracle)
+        }
+    }
+
+    /// Liquidity available to swaps and new loans
+    pub fn available_liquidity(&self) -> u64 {
+        self.total_deposits.saturating_sub(self.total_borrows)
+    }
+
+    /// Sets `total_deposits` to what the pool holds: its SOL reserve plus
+    /// `lst_held`, in the reserve or lent out, at the verified rate
+    pub fn sync_deposits(&mut self, sol_reserve: u64, lst_held: u64) -> Result<()> {
+        let lst_value = self
+            .protocol_reserves
+            .lamports_for(lst_held)
+            .ok_or(ErrorCode::ReservesNotVerified)?;
+        self.total_deposits = u64::try_from(lst_value + sol_reserve as u128).map_err(|_| ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposits = self.total_deposits.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records liquidity leaving the pool; lent-out liquidity cannot leave
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.available_liquidity(), ErrorCode::InsufficientLiquidity);
+        self.total_deposits -= amount;
+        Ok(())
+    }
+
+    pub fn record_borrow(&mut self, amount: u64) -> Result<()> {
+        require!(amount <= self.available_liquidity(), ErrorCode::InsufficientLiquidity);
+        self.total_borrows += amount;
+        Ok(())
+    }
+
+    /// Records a repayment; `interest` is credited to LPs as new deposits
+    pub fn record_repayment(&mut self, principal: u64, interest: u64) -> Result<()> {
+        self.total_borrows = self.total_borrows.checked_sub(principal).ok_or(ErrorCode::MathOverflow)?;
+        self.record_deposit(interest)
+    }
     
     pub fn update_metrics(&mut self, clock: &Sysvar<Clock>) {
         self.last_update_ts = clock.unix_timestamp;
     }
-}
\ No newline at end of file
+
+    /// Moves trading to `status` if it is more restrictive than the current
+    /// one, returning the previous status on change. Only governance lifts
+    /// restrictions.
+    pub fn escalate_trading_status(&mut self, status: TradingStatus) -> Option<TradingStatus> {
+        if status <= self.trading_status {
+            return None;
+        }
+        let previous = self.trading_status;
+        self.trading_status = status;
+        Some(previous)
+    }
+}
+
+/// The original `PoolState` layout, before the pool had an authority, proof of
+/// reserves, depeg monitoring or liquidity protection
+#[derive(AnchorDeserialize)]
+pub struct PoolStateV1 {
+    pub is_initialized: bool,
+    pub bump: u8,
+    pub lst_risk_score: u64,
+    pub lst_performance_metrics: LSTMetrics,
+    pub volume_24h: u64,
+    pub last_update_ts: i64,
+}
+
+impl solswap_math::quoter::LstPool for PoolState {
+    fn exchange_rate(&self) -> solswap_math::lst::ExchangeRate {
+        (&self.protocol_reserves).into()
+    }
+
+    fn fee_params(&self) -> solswap_math::lst::FeeParams {
+        solswap_math::lst::FeeParams {
+            fee_rate: self.fee_rate,
+            min_fee: self.min_fee,
+            max_fee: self.max_fee,
+        }
+    }
+
+    fn risk_metrics(&self) -> solswap_math::risk::RiskMetrics {
+        solswap_math::risk::RiskMetrics {
+            validator_performance: self.lst_performance_metrics.validator_performance,
+            reserve_ratio: self.lst_performance_metrics.reserve_ratio,
+            decentralization_score: self.lst_performance_metrics.decentralization_score,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_borrows_bounded_by_deposits() {
+        let mut pool = PoolState::new(0, Pubkey::default(), Pubkey::default(), Pubkey::default(), Pubkey::default());
+        pool.record_deposit(1_000).unwrap();
+        pool.record_borrow(600).unwrap();
+        assert!(pool.record_borrow(401).is_err());
+        assert!(pool.record_withdrawal(401).is_err());
+
+        pool.record_repayment(600, 10).unwrap();
+        assert_eq!((pool.total_deposits, pool.total_borrows), (1_010, 0));
+        pool.record_withdrawal(1_010).unwrap();
+        assert_eq!(pool.available_liquidity(), 0);
+    }
+
+    #[test]
+    fn test_deposits_follow_holdings() {
+        let mut pool = PoolState::new(0, Pubkey::default(), Pubkey::default(), Pubkey::default(), Pubkey::default());
+        assert!(pool.sync_deposits(1_000, 1_000).is_err());
+
+        pool.protocol_reserves.sol_reserves = 1_100;
+        pool.protocol_reserves.lst_supply = 1_000;
+        pool.sync_deposits(1_000, 2_000).unwrap();
+        assert_eq!(pool.total_deposits, 3_200);
+    }
+
+    #[test]
+    fn test_migrates_original_layout() {
+        // A version 1 account as the original program wrote it
+        let mut data = PoolState::DISCRIMINATOR.to_vec();
+        data.push(1); // is_initialized
+        data.push(254); // bump
+        data.extend(42u64.to_le_bytes()); // lst_risk_score
+        for metric in [7u64, 9_000, 8_000, 10_000] {
+            data.extend(metric.to_le_bytes()); // lst_performance_metrics
+        }
+        data.extend(5_000u64.to_le_bytes()); // volume_24h
+        data.extend(1_700_000_000i64.to_le_bytes()); // last_update_ts
+        assert_eq!(data.len(), PoolState::LEN_V1);
+
+        let authority = Pubkey::new_unique();
+        let lst_mint = Pubkey::new_unique();
+        let v1 = PoolState::decode_v1(&data).unwrap();
+        let pool = PoolState::from_v1(v1, 251, authority, lst_mint, Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut migrated = Vec::new();
+        pool.try_serialize(&mut migrated).unwrap();
+        assert_eq!(migrated.len(), PoolState::LEN);
+
+        // The pool takes the bump of its new address
+        let pool = PoolState::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!((pool.bump, pool.lst_risk_score, pool.volume_24h), (251, 42, 5_000));
+        assert_eq!(pool.lst_performance_metrics.validator_performance, 9_000);
+        assert_eq!(pool.last_update_ts, 1_700_000_000);
+        assert_eq!((pool.authority, pool.lst_mint), (authority, lst_mint));
+        assert_eq!(pool.version, PoolState::VERSION);
+        assert_eq!(pool.fee_rate, BASE_FEE_RATE);
+
+        // Only original-size accounts are migrated
+        assert!(PoolState::decode_v1(&migrated).is_err());
+        data[0] ^= 1;
+        assert!(PoolState::decode_v1(&data).is_err());
+    }
+}
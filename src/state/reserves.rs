@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::borsh::try_from_slice_unchecked;
+use spl_stake_pool::state::StakePool;
+use crate::error::ErrorCode;
+
+/// Marinade liquid staking program
+pub mod marinade {
+    use anchor_lang::prelude::*;
+
+    declare_id!("MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD");
+}
+/// Offset of `msol_supply` in Marinade's `State` account
+const MARINADE_MSOL_SUPPLY_OFFSET: usize = 504;
+/// Offset of `msol_price` in Marinade's `State` account
+const MARINADE_MSOL_PRICE_OFFSET: usize = 512;
+/// Marinade prices are fixed point with a 2^32 denominator
+const MARINADE_PRICE_DENOMINATOR: u128 = 1 << 32;
+
+/// Backing below this ratio halts trading in the LST (basis points)
+pub const MIN_RESERVE_RATIO: u64 = 9_900;
+/// Fully backed, in basis points
+pub const FULL_RESERVE_RATIO: u64 = 10_000;
+/// A verification older than this cannot price a swap or back an insurance claim
+pub const MAX_VERIFICATION_AGE: i64 = 3_600;
+
+/// Last verified reserves of the protocol issuing a pool's LST
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ProtocolReserves {
+    /// Protocol state account (SPL stake pool or Marinade state)
+    pub protocol: Pubkey,
+    /// Total SOL backing LSTs
+    pub sol_reserves: u64,
+    /// Total LSTs issued
+    pub lst_supply: u64,
+    /// Last verification timestamp
+    pub last_verified: i64,
+}
+
+impl ProtocolReserves {
+    pub const LEN: usize = 32 + 8 + 8 + 8;
+
+    /// Whether the reserves were verified within `MAX_VERIFICATION_AGE` of `now`
+    pub fn is_fresh(&self, now: i64) -> bool {
+        self.last_verified > 0 && now - self.last_verified <= MAX_VERIFICATION_AGE
+    }
+
+    /// Lamports backing `amount` LST at the last fully backed rate, or None
+    /// before the first verification
+    pub fn lamports_for(&self, amount: u64) -> Option<u128> {
+        if self.lst_supply == 0 {
+            return None;
+        }
+        Some(amount as u128 * self.sol_reserves as u128 / self.lst_supply as u128)
+    }
+}
+
+/// Reserves reported by a protocol's own accounting
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReportedReserves {
+    /// SOL the protocol reports under management
+    pub sol_reserves: u64,
+    /// LST supply the protocol's accounting is based on
+    pub lst_supply: u64,
+}
+
+/// Reads reported reserves from an SPL stake pool or Marinade state account
+pub fn read_reported_reserves(protocol_state: &AccountInfo, lst_mint: &Pubkey) -> Result<ReportedReserves> {
+    let data = protocol_state.try_borrow_data()?;
+
+    if protocol_state.owner == &spl_stake_pool::id() {
+        let stake_pool = try_from_slice_unchecked::<StakePool>(&data)
+            .map_err(|_| ErrorCode::InvalidProtocolState)?;
+        require!(stake_pool.is_valid(), ErrorCode::InvalidProtocolState);
+        require!(stake_pool.pool_mint == *lst_mint, ErrorCode::InvalidProtocolState);
+
+        return Ok(ReportedReserves {
+            sol_reserves: stake_pool.total_lamports,
+            lst_supply: stake_pool.pool_token_supply,
+        });
+    }
+
+    if protocol_state.owner == &marinade::ID {
+        let read_u64 = |offset: usize| -> Result<u64> {
+            let bytes = data
+                .get(offset..offset + 8)
+                .ok_or(ErrorCode::InvalidProtocolState)?;
+            Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        // msol_mint is the first field after the discriminator
+        let msol_mint = data.get(8..40).ok_or(ErrorCode::InvalidProtocolState)?;
+        require!(msol_mint == lst_mint.as_ref(), ErrorCode::InvalidProtocolState);
+
+        let msol_supply = read_u64(MARINADE_MSOL_SUPPLY_OFFSET)?;
+        let msol_price = read_u64(MARINADE_MSOL_PRICE_OFFSET)?;
+        let sol_reserves = msol_supply as u128 * msol_price as u128 / MARINADE_PRICE_DENOMINATOR;
+
+        return Ok(ReportedReserves {
+            sol_reserves: u64::try_from(sol_reserves).map_err(|_| ErrorCode::MathOverflow)?,
+            lst_supply: msol_supply,
+        });
+    }
+
+    err!(ErrorCode::InvalidProtocolState)
+}
+
+/// Ratio of SOL actually backing the circulating LST supply (basis points).
+///
+/// Two shortfalls are detected: LST minted beyond what the protocol accounts
+/// for (`mint_supply` above `reported.lst_supply`), and a fall in the SOL per
+/// LST rate since the last verification, such as after slashing. The result
+/// is the smaller of the two, capped at 100%.
+pub fn calculate_backing_ratio(
+    reported: &ReportedReserves,
+    mint_supply: u64,
+    previous: &ProtocolReserves,
+) -> u64 {
+    if mint_supply == 0 {
+        return FULL_RESERVE_RATIO;
+    }
+
+    let supply_ratio = reported.lst_supply as u128 * FULL_RESERVE_RATIO as u128 / mint_supply as u128;
+
+    // Compare rates as cross products: (sol / supply) / (prev_sol / prev_supply)
+    let rate_ratio = if previous.last_verified == 0 || previous.sol_reserves == 0 || reported.lst_supply == 0 {
+        FULL_RESERVE_RATIO as u128
+    } else {
+        reported.sol_reserves as u128 * previous.lst_supply as u128 * FULL_RESERVE_RATIO as u128
+            / (previous.sol_reserves as u128 * reported.lst_supply as u128)
+    };
+
+    supply_ratio.min(rate_ratio).min(FULL_RESERVE_RATIO as u128) as u64
+}
+
+/// Pool risk level (0-10000) implied by a backing ratio: zero when fully
+/// backed, rising linearly to the maximum at `MIN_RESERVE_RATIO`
+pub fn reserve_risk_level(reserve_ratio: u64) -> u16 {
+    let shortfall = FULL_RESERVE_RATIO.saturating_sub(reserve_ratio);
+    let tolerance = FULL_RESERVE_RATIO - MIN_RESERVE_RATIO;
+    (shortfall * 10_000 / tolerance).min(10_000) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verified(sol_reserves: u64, lst_supply: u64) -> ProtocolReserves {
+        ProtocolReserves {
+            protocol: Pubkey::default(),
+            sol_reserves,
+            lst_supply,
+            last_verified: 1,
+        }
+    }
+
+    #[test]
+    fn test_fully_backed() {
+        let reported = ReportedReserves { sol_reserves: 1_100, lst_supply: 1_000 };
+        assert_eq!(calculate_backing_ratio(&reported, 1_000, &ProtocolReserves::default()), 10_000);
+        // Rewards raise the rate, which never counts as over-backing
+        assert_eq!(calculate_backing_ratio(&reported, 1_000, &verified(1_050, 1_000)), 10_000);
+        assert_eq!(reserve_risk_level(10_000), 0);
+    }
+
+    #[test]
+    fn test_verification_expires() {
+        assert!(!ProtocolReserves::default().is_fresh(0));
+        assert!(verified(1_100, 1_000).is_fresh(1 + MAX_VERIFICATION_AGE));
+        assert!(!verified(1_100, 1_000).is_fresh(2 + MAX_VERIFICATION_AGE));
+    }
+
+    #[test]
+    fn test_unbacked_mint_detected() {
+        let reported = ReportedReserves { sol_reserves: 1_100, lst_supply: 1_000 };
+        assert_eq!(calculate_backing_ratio(&reported, 1_010, &ProtocolReserves::default()), 9_900);
+    }
+
+    #[test]
+    fn test_rate_drop_detected() {
+        // 2% slashing since the last verification
+        let reported = ReportedReserves { sol_reserves: 1_078, lst_supply: 1_000 };
+        assert_eq!(calculate_backing_ratio(&reported, 1_000, &verified(1_100, 1_000)), 9_800);
+        assert_eq!(reserve_risk_level(9_800), 10_000);
+        assert_eq!(reserve_risk_level(9_950), 5_000);
+    }
+}
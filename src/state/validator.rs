@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 /// Stores performance metrics and stake information for individual validators
 #[account]
 pub struct ValidatorState {
-    /// The validator's identity address
+    /// The validator's vote account address, also the PDA seed
     pub validator_address: Pubkey,
     
     /// Rolling 30-day performance history, updated daily
@@ -34,6 +34,9 @@ pub struct ValidatorState {
     /// Authority that can update validator metrics
     pub authority: Pubkey,
     
+    /// Account that paid rent at registration and receives it back on close
+    pub rent_payer: Pubkey,
+    
     /// Bump seed for PDA
     pub bump: u8,
 }
@@ -50,8 +53,30 @@ impl ValidatorState {
         8 +    // last_update
         2 +    // commission
         32 +   // authority
+        32 +   // rent_payer
         1;     // bump
 
+    /// PDA seed prefix, combined with the vote account address
+    pub const SEED: &'static [u8] = b"validator";
+
+    /// Creates a fresh validator state for a newly registered vote account
+    pub fn new(validator_address: Pubkey, authority: Pubkey, rent_payer: Pubkey, bump: u8) -> Self {
+        Self {
+            validator_address,
+            performance_history: [0; 30],
+            current_index: 0,
+            mev_rewards: 0,
+            total_stake: 0,
+            average_apr: 0,
+            missed_blocks: 0,
+            last_update: 0,
+            commission: 0,
+            authority,
+            rent_payer,
+            bump,
+        }
+    }
+
     /// Calculates the average performance over the last 30 days
     pub fn calculate_average_performance(&self) -> u64 {
         let mut sum = 0u64;
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+/// Maximum number of vote accounts that governance can pre-approve
+pub const MAX_APPROVED_VALIDATORS: usize = 64;
+
+/// Governs which validators may register a `ValidatorState` PDA
+#[account]
+pub struct ValidatorRegistry {
+    /// Governance authority that manages the allowlist and metric updates
+    pub authority: Pubkey,
+    /// When enabled, only approved vote accounts can register
+    pub allowlist_enabled: bool,
+    /// Vote accounts approved by governance
+    pub approved_validators: Vec<Pubkey>,
+    /// Number of currently registered validators
+    pub registered_count: u32,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ValidatorRegistry {
+    pub const LEN: usize = 8 +    // discriminator
+        32 +   // authority
+        1 +    // allowlist_enabled
+        4 + 32 * MAX_APPROVED_VALIDATORS + // approved_validators
+        4 +    // registered_count
+        1;     // bump
+
+    pub fn new(authority: Pubkey, allowlist_enabled: bool, bump: u8) -> Self {
+        Self {
+            authority,
+            allowlist_enabled,
+            approved_validators: Vec::new(),
+            registered_count: 0,
+            bump,
+        }
+    }
+
+    /// Returns true if the vote account is on the governance allowlist
+    pub fn is_approved(&self, vote_account: &Pubkey) -> bool {
+        self.approved_validators.contains(vote_account)
+    }
+
+    /// Returns true if the vote account may register under the current policy
+    pub fn can_register(&self, vote_account: &Pubkey) -> bool {
+        !self.allowlist_enabled || self.is_approved(vote_account)
+    }
+
+    /// Adds a vote account to the allowlist
+    pub fn approve(&mut self, vote_account: Pubkey) -> Result<()> {
+        if self.is_approved(&vote_account) {
+            return Ok(());
+        }
+        require!(
+            self.approved_validators.len() < MAX_APPROVED_VALIDATORS,
+            ErrorCode::AllowlistFull
+        );
+        self.approved_validators.push(vote_account);
+        Ok(())
+    }
+
+    /// Removes a vote account from the allowlist
+    pub fn revoke(&mut self, vote_account: &Pubkey) {
+        self.approved_validators.retain(|v| v != vote_account);
+    }
+
+    /// Counts a new registration if the vote account may register
+    pub fn register(&mut self, vote_account: &Pubkey) -> Result<()> {
+        require!(self.can_register(vote_account), ErrorCode::ValidatorNotApproved);
+        self.registered_count = self
+            .registered_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Counts a deregistration. Only governance or the account that paid for the
+    /// registration may remove a validator; revoking approval does not make it
+    /// removable by anyone else, since validators registered before the
+    /// allowlist was enabled were never approved.
+    pub fn deregister(&mut self, signer: &Pubkey, rent_payer: &Pubkey) -> Result<()> {
+        require!(
+            signer == &self.authority || signer == rent_payer,
+            ErrorCode::InvalidAuthority
+        );
+        self.registered_count = self.registered_count.saturating_sub(1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_registration() {
+        let mut registry = ValidatorRegistry::new(Pubkey::new_unique(), false, 255);
+        let vote_account = Pubkey::new_unique();
+        assert!(registry.can_register(&vote_account));
+        registry.register(&vote_account).unwrap();
+        assert_eq!(registry.registered_count, 1);
+    }
+
+    #[test]
+    fn test_allowlist_approval() {
+        let mut registry = ValidatorRegistry::new(Pubkey::new_unique(), true, 255);
+        let vote_account = Pubkey::new_unique();
+        assert!(registry.register(&vote_account).is_err());
+
+        registry.approve(vote_account).unwrap();
+        // Approving twice does not duplicate the entry
+        registry.approve(vote_account).unwrap();
+        assert_eq!(registry.approved_validators.len(), 1);
+        registry.register(&vote_account).unwrap();
+        assert_eq!(registry.registered_count, 1);
+
+        registry.revoke(&vote_account);
+        assert!(!registry.can_register(&vote_account));
+
+        for _ in 0..MAX_APPROVED_VALIDATORS {
+            registry.approve(Pubkey::new_unique()).unwrap();
+        }
+        assert!(registry.approve(Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_deregister_requires_authority_or_payer() {
+        let authority = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let mut registry = ValidatorRegistry::new(authority, false, 255);
+        let vote_account = Pubkey::new_unique();
+        registry.register(&vote_account).unwrap();
+        registry.register(&Pubkey::new_unique()).unwrap();
+
+        // Registered before the allowlist was enabled and never approved
+        registry.allowlist_enabled = true;
+        assert!(registry.deregister(&Pubkey::new_unique(), &payer).is_err());
+        assert_eq!(registry.registered_count, 2);
+
+        registry.deregister(&payer, &payer).unwrap();
+        registry.deregister(&authority, &payer).unwrap();
+        assert_eq!(registry.registered_count, 0);
+    }
+}